@@ -0,0 +1,342 @@
+use std::{fmt::Write as _, fs::File, io::Write};
+
+// 靜態報表: one HTML file with inline SVG, no scripts or outside assets
+// hands are indices into the labels, the same file serves every variant
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct Metadata {
+    pub variant: String,
+    pub rules: String,
+    pub checksum: u64,                // of the pattern file
+    pub extra: Vec<(String, String)>, // 起胡, 狀況 and the like
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct HtmlReport {
+    meta: Metadata,
+    labels: Vec<(String, String)>, // shown name, key in tooltips
+    total: f64,
+    joint: Vec<Vec<f64>>,
+    points: Vec<f64>,
+    scores: Vec<f64>,
+}
+
+const WIDTH: usize = 860;
+const LABELWIDTH: usize = 190;
+const ROW: usize = 20;
+const CELL: usize = 16;
+
+impl HtmlReport {
+    pub fn new(meta: Metadata, labels: Vec<(String, String)>) -> Self {
+        let n = labels.len();
+        Self {
+            meta,
+            labels,
+            total: 0.0,
+            joint: vec![vec![0.0; n]; n],
+            points: vec![0.0; n],
+            scores: Vec::new(),
+        }
+    }
+
+    // one winning list: the hands it holds, its score and weight
+    pub fn add(&mut self, hands: &[usize], score: u64, weight: f64) {
+        self.total += weight;
+        hands.iter().for_each(|a| {
+            self.points[*a] += weight * score as f64;
+            hands.iter().for_each(|b| self.joint[*a][*b] += weight);
+        });
+        if self.scores.len() <= score as usize {
+            self.scores.resize(score as usize + 1, 0.0);
+        }
+        self.scores[score as usize] += weight;
+    }
+
+    fn frequency(&self, a: usize) -> f64 {
+        self.joint[a][a] / self.total
+    }
+
+    // hands that happen, most frequent first
+    fn seen(&self) -> Vec<usize> {
+        let mut seen: Vec<usize> = (0..self.labels.len())
+            .filter(|a| self.joint[*a][*a] > 0.0)
+            .collect();
+        seen.sort_by(|a, b| self.joint[*b][*b].total_cmp(&self.joint[*a][*a]));
+        seen
+    }
+
+    pub fn render(&self) -> String {
+        let mut html = String::new();
+        html.push_str(
+            "<!DOCTYPE html>\n<html lang=\"zh-Hant\">\n<head>\n<meta charset=\"utf-8\">\n",
+        );
+        let _ = writeln!(
+            html,
+            "<title>{} 役種報表</title>",
+            escape(&self.meta.variant)
+        );
+        html.push_str(
+            "<style>\nbody{font-family:sans-serif;margin:2em;color:#222}\n\
+             table{border-collapse:collapse}td,th{padding:2px 10px;text-align:left}\n\
+             th{background:#eee}svg text{font-size:12px}\n</style>\n</head>\n<body>\n",
+        );
+        let _ = writeln!(html, "<h1>{} 役種報表</h1>", escape(&self.meta.variant));
+
+        html.push_str("<table>\n");
+        let rows = [
+            ("variant".to_string(), self.meta.variant.clone()),
+            ("rules".to_string(), self.meta.rules.clone()),
+            (
+                "checksum".to_string(),
+                format!("{:016x}", self.meta.checksum),
+            ),
+        ];
+        rows.iter()
+            .chain(self.meta.extra.iter())
+            .for_each(|(k, v)| {
+                let _ = writeln!(
+                    html,
+                    "<tr><th>{}</th><td>{}</td></tr>",
+                    escape(k),
+                    escape(v)
+                );
+            });
+        html.push_str("</table>\n");
+
+        html.push_str("<h2>役種頻率</h2>\n");
+        html.push_str(&self.frequency_bars());
+        html.push_str("<h2>分數分布</h2>\n");
+        html.push_str(&self.score_bars());
+        html.push_str("<h2>共現 P(B|A)</h2>\n");
+        html.push_str(&self.heatmap());
+        html.push_str("</body>\n</html>\n");
+        html
+    }
+
+    // share of combinations holding each hand, log scale down to the rarest
+    fn frequency_bars(&self) -> String {
+        let seen = self.seen();
+        if seen.is_empty() {
+            return "<p>no winning hand</p>\n".to_string();
+        }
+        let floor = seen
+            .iter()
+            .map(|a| self.frequency(*a).log10().floor())
+            .fold(0.0, f64::min)
+            .min(-1.0);
+        let scale = (WIDTH - LABELWIDTH - 150) as f64 / -floor;
+        let height = seen.len() * ROW + ROW;
+
+        let mut svg = String::new();
+        let _ = writeln!(
+            svg,
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\">",
+            WIDTH, height
+        );
+        // decades
+        (0..=(-floor) as usize).for_each(|d| {
+            let x = LABELWIDTH as f64 + d as f64 * scale;
+            let _ = writeln!(
+                svg,
+                "<line x1=\"{:.1}\" y1=\"0\" x2=\"{:.1}\" y2=\"{}\" stroke=\"#ddd\"/>\
+                 <text x=\"{:.1}\" y=\"{}\" fill=\"#888\">1e{}</text>",
+                x,
+                x,
+                height - ROW,
+                x + 2.0,
+                height - 5,
+                d as i32 + floor as i32
+            );
+        });
+        seen.iter().enumerate().for_each(|(row, a)| {
+            let p = self.frequency(*a);
+            let y = row * ROW;
+            let w = (p.log10() - floor) * scale;
+            let _ = writeln!(
+                svg,
+                "<g><title>{} {:.6}% average {:.3}</title>\
+                 <text x=\"{}\" y=\"{}\" text-anchor=\"end\">{}</text>\
+                 <rect x=\"{}\" y=\"{}\" width=\"{:.1}\" height=\"{}\" fill=\"#4a78b5\"/>\
+                 <text x=\"{:.1}\" y=\"{}\">{:.4}%</text></g>",
+                escape(&self.labels[*a].1),
+                p * 100.0,
+                self.points[*a] / self.joint[*a][*a],
+                LABELWIDTH - 6,
+                y + ROW - 6,
+                escape(&self.labels[*a].0),
+                LABELWIDTH,
+                y + 3,
+                w.max(1.0),
+                ROW - 6,
+                LABELWIDTH as f64 + w.max(1.0) + 4.0,
+                y + ROW - 6,
+                p * 100.0
+            );
+        });
+        svg.push_str("</svg>\n");
+        svg
+    }
+
+    // share of each score, P(≥k) in the tooltip
+    fn score_bars(&self) -> String {
+        let total: f64 = self.scores.iter().sum();
+        if total <= 0.0 {
+            return "<p>no winning hand</p>\n".to_string();
+        }
+        let n = self.scores.len();
+        let max = self.scores.iter().cloned().fold(0.0, f64::max) / total;
+        let (top, chart) = (20, 220);
+        let bar = ((WIDTH - 40) / n).clamp(4, 40);
+        let mut svg = String::new();
+        let _ = writeln!(
+            svg,
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\">",
+            40 + bar * n,
+            top + chart + 30
+        );
+        let mut at_least = 1.0;
+        self.scores.iter().enumerate().for_each(|(k, w)| {
+            let p = w / total;
+            let h = p / max * chart as f64;
+            let x = 40 + k * bar;
+            let _ = writeln!(
+                svg,
+                "<g><title>{}: {:.4}% P(≥{}) {:.4}%</title>\
+                 <rect x=\"{}\" y=\"{:.1}\" width=\"{}\" height=\"{:.1}\" fill=\"#d08a2e\"/>\
+                 <text x=\"{}\" y=\"{}\" text-anchor=\"middle\">{}</text></g>",
+                k,
+                p * 100.0,
+                k,
+                at_least * 100.0,
+                x + 1,
+                (top + chart) as f64 - h,
+                bar - 2,
+                h,
+                x + bar / 2,
+                top + chart + 16,
+                k
+            );
+            at_least -= p;
+        });
+        let _ = writeln!(
+            svg,
+            "<text x=\"0\" y=\"{}\">{:.1}%</text><text x=\"0\" y=\"{}\">0</text>",
+            top,
+            max * 100.0,
+            top + chart
+        );
+        svg.push_str("</svg>\n");
+        svg
+    }
+
+    // row A, column B, darker is closer to 1
+    fn heatmap(&self) -> String {
+        let seen = self.seen();
+        let n = seen.len();
+        let left = LABELWIDTH;
+        let top = LABELWIDTH;
+        let mut svg = String::new();
+        let _ = writeln!(
+            svg,
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\">",
+            left + n * CELL + 10,
+            top + n * CELL + 10
+        );
+        seen.iter().enumerate().for_each(|(i, a)| {
+            let _ = writeln!(
+                svg,
+                "<text x=\"{}\" y=\"{}\" text-anchor=\"end\">{}</text>\
+                 <text transform=\"translate({},{}) rotate(-90)\">{}</text>",
+                left - 4,
+                top + i * CELL + CELL - 4,
+                escape(&self.labels[*a].0),
+                left + i * CELL + CELL - 4,
+                top - 4,
+                escape(&self.labels[*a].0)
+            );
+        });
+        seen.iter().enumerate().for_each(|(i, a)| {
+            seen.iter().enumerate().for_each(|(j, b)| {
+                let p = self.joint[*a][*b] / self.joint[*a][*a];
+                let shade = (255.0 * (1.0 - p)).round() as u8;
+                let _ = writeln!(
+                    svg,
+                    "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" \
+                     fill=\"rgb({},{},255)\" stroke=\"#fff\">\
+                     <title>P({} | {}) = {:.5}</title></rect>",
+                    left + j * CELL,
+                    top + i * CELL,
+                    CELL,
+                    CELL,
+                    shade,
+                    shade,
+                    escape(&self.labels[*b].1),
+                    escape(&self.labels[*a].1),
+                    p
+                );
+            });
+        });
+        svg.push_str("</svg>\n");
+        svg
+    }
+
+    pub fn write(&self, filename: &str) -> std::io::Result<()> {
+        let mut file = File::create(filename)?;
+        file.write_all(self.render().as_bytes())
+    }
+}
+
+fn escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn report() -> HtmlReport {
+        let meta = Metadata {
+            variant: "four".to_string(),
+            rules: "a<b>".to_string(),
+            checksum: 0xabc,
+            extra: vec![("起胡".to_string(), "3".to_string())],
+        };
+        let labels = ["平和", "對對和", "清一色"]
+            .iter()
+            .zip(["AllChows", "AllPungs", "FullFlush"])
+            .map(|(l, k)| (l.to_string(), k.to_string()))
+            .collect();
+        let mut report = HtmlReport::new(meta, labels);
+        report.add(&[0], 1, 3.0);
+        report.add(&[1, 2], 8, 1.0);
+        report
+    }
+
+    #[test]
+    fn accumulates_hands_and_scores() {
+        let r = report();
+        assert_eq!(r.frequency(0), 0.75);
+        assert_eq!(r.frequency(2), 0.25);
+        assert_eq!(r.seen(), vec![0, 1, 2]);
+        assert_eq!(r.scores, vec![0.0, 3.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 1.0]);
+        assert_eq!(r.joint[1][2], 1.0);
+        assert_eq!(r.joint[0][1], 0.0);
+    }
+
+    #[test]
+    fn self_contained_html() {
+        let html = report().render();
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(html.contains("0000000000000abc"));
+        assert!(html.contains("a&lt;b&gt;"));
+        assert!(html.contains("<td>3</td>"));
+        assert_eq!(html.matches("<svg").count(), 3);
+        // nothing is fetched
+        assert!(!html.contains("<script"));
+        assert!(!html.contains("src="));
+        assert!(!html.contains("href="));
+    }
+}
@@ -0,0 +1,4 @@
+// 各變體共用的檔案格式與工具
+pub mod html;
+pub mod pattern;
+pub mod settlement;
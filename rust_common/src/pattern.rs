@@ -0,0 +1,356 @@
+use std::{
+    fs::File,
+    io::{Error, ErrorKind, Read, Write},
+};
+
+use arrayvec::ArrayVec;
+
+// 牌型檔
+// header: magic, version, variant, tile count, encoding, record count,
+// checksum, generator, followed by fixed-size records of hainum bytes
+// 各變體只提供自己的 Variant 與張數
+pub const MAGIC: [u8; 4] = *b"MJPT";
+pub const VERSION: u16 = 1;
+
+const FIXEDHEADERLEN: usize = 4 + 2 + 1 + 1 + 1 + 8 + 8 + 2;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Variant {
+    Four,    // 四人麻將, 14 張
+    Three,   // 三人麻將, 11 張
+    Sixteen, // 台灣十六張, 17 張
+    Sanma,   // 日式三麻, 14 張
+    Custom,  // 自訂變體, 張數見檔頭
+}
+
+impl Variant {
+    pub fn name(&self) -> String {
+        match *self {
+            Self::Four => "four".to_string(),
+            Self::Three => "three".to_string(),
+            Self::Sixteen => "sixteen".to_string(),
+            Self::Sanma => "sanma".to_string(),
+            Self::Custom => "custom".to_string(),
+        }
+    }
+}
+
+impl TryFrom<u8> for Variant {
+    type Error = ();
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            1 => Ok(Self::Four),
+            2 => Ok(Self::Three),
+            3 => Ok(Self::Sixteen),
+            4 => Ok(Self::Sanma),
+            5 => Ok(Self::Custom),
+            _ => Err(()),
+        }
+    }
+}
+
+impl From<Variant> for u8 {
+    fn from(v: Variant) -> Self {
+        match v {
+            Variant::Four => 1,
+            Variant::Three => 2,
+            Variant::Sixteen => 3,
+            Variant::Sanma => 4,
+            Variant::Custom => 5,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Encoding {
+    OffsetAscii, // 'A' + 牌序
+}
+
+impl TryFrom<u8> for Encoding {
+    type Error = ();
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(Self::OffsetAscii),
+            _ => Err(()),
+        }
+    }
+}
+
+impl From<Encoding> for u8 {
+    fn from(e: Encoding) -> Self {
+        match e {
+            Encoding::OffsetAscii => 0,
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct PatternHeader {
+    pub version: u16,
+    pub variant: Variant,
+    pub hainum: u8,
+    pub encoding: Encoding,
+    pub records: u64,
+    pub checksum: u64,
+    pub generator: String,
+}
+
+impl PatternHeader {
+    pub fn new(generator: &str, variant: Variant, hainum: usize, data: &[u8]) -> Self {
+        Self {
+            version: VERSION,
+            variant,
+            hainum: hainum as u8,
+            encoding: Encoding::OffsetAscii,
+            records: (data.len() / hainum) as u64,
+            checksum: checksum(data),
+            generator: generator.to_string(),
+        }
+    }
+}
+
+// FNV-1a 64
+pub fn checksum(data: &[u8]) -> u64 {
+    data.iter().fold(0xcbf29ce484222325, |h, b| {
+        (h ^ *b as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+pub fn encode(header: &PatternHeader, data: &[u8]) -> Vec<u8> {
+    let generator = header.generator.as_bytes();
+    let mut buffer = Vec::with_capacity(FIXEDHEADERLEN + generator.len() + data.len());
+    buffer.extend_from_slice(&MAGIC);
+    buffer.extend_from_slice(&header.version.to_le_bytes());
+    buffer.push(header.variant.into());
+    buffer.push(header.hainum);
+    buffer.push(header.encoding.into());
+    buffer.extend_from_slice(&header.records.to_le_bytes());
+    buffer.extend_from_slice(&header.checksum.to_le_bytes());
+    buffer.extend_from_slice(&(generator.len() as u16).to_le_bytes());
+    buffer.extend_from_slice(generator);
+    buffer.extend_from_slice(data);
+    buffer
+}
+
+pub fn decode(
+    buffer: &[u8],
+    expected_variant: Variant,
+    expected_hainum: usize,
+) -> std::io::Result<(PatternHeader, &[u8])> {
+    let invalid = |msg: String| Error::new(ErrorKind::InvalidData, msg);
+
+    if buffer.len() < FIXEDHEADERLEN || buffer[0..4] != MAGIC {
+        Err(invalid(
+            "no pattern header, convert legacy file with `convert` first".to_string(),
+        ))?
+    }
+    let version = u16::from_le_bytes([buffer[4], buffer[5]]);
+    if version != VERSION {
+        Err(invalid(format!("unsupported pattern version {}", version)))?
+    }
+    let variant = Variant::try_from(buffer[6])
+        .map_err(|_| invalid(format!("unknown variant {}", buffer[6])))?;
+    if variant != expected_variant {
+        Err(invalid(format!(
+            "file holds {} player patterns, expected {}",
+            variant.name(),
+            expected_variant.name()
+        )))?
+    }
+    let hainum = buffer[7];
+    if hainum as usize != expected_hainum {
+        Err(invalid(format!(
+            "file holds {} tile hands, expected {}",
+            hainum, expected_hainum
+        )))?
+    }
+    let encoding = Encoding::try_from(buffer[8])
+        .map_err(|_| invalid(format!("unknown tile encoding {}", buffer[8])))?;
+    let records = u64::from_le_bytes(buffer[9..17].try_into().unwrap());
+    let sum = u64::from_le_bytes(buffer[17..25].try_into().unwrap());
+    let generator_len = u16::from_le_bytes([buffer[25], buffer[26]]) as usize;
+    let data_start = FIXEDHEADERLEN + generator_len;
+    if buffer.len() < data_start {
+        Err(invalid("truncated pattern header".to_string()))?
+    }
+    let generator = String::from_utf8_lossy(&buffer[FIXEDHEADERLEN..data_start]).to_string();
+
+    let data = &buffer[data_start..];
+    // records comes from the file, a huge count must not overflow
+    let expected = records
+        .checked_mul(hainum as u64)
+        .ok_or_else(|| invalid(format!("impossible record count {}", records)))?;
+    if data.len() as u64 != expected {
+        Err(invalid(format!(
+            "incomplete file, expected {} records, found {} bytes",
+            records,
+            data.len()
+        )))?
+    }
+    if checksum(data) != sum {
+        Err(invalid("checksum mismatch".to_string()))?
+    }
+
+    Ok((
+        PatternHeader {
+            version,
+            variant,
+            hainum,
+            encoding,
+            records,
+            checksum: sum,
+            generator,
+        },
+        data,
+    ))
+}
+
+pub fn write_patterns(
+    filename: &str,
+    generator: &str,
+    variant: Variant,
+    hainum: usize,
+    data: &[u8],
+) -> std::io::Result<()> {
+    assert_eq!(data.len() % hainum, 0);
+    let header = PatternHeader::new(generator, variant, hainum, data);
+    let mut file = File::create(filename)?;
+    file.write_all(&encode(&header, data))
+}
+
+// 張數固定的變體, 紀錄直接放進 ArrayVec
+pub fn read_patterns<const HAINUM: usize>(
+    filename: &str,
+    variant: Variant,
+) -> std::io::Result<(PatternHeader, Vec<ArrayVec<u8, HAINUM>>)> {
+    let buffer = read_all(filename)?;
+    let (header, data) = decode(&buffer, variant, HAINUM)?;
+    let records = data.chunks(HAINUM).map(|c| c.try_into().unwrap()).collect();
+
+    Ok((header, records))
+}
+
+// 張數執行時才知道的變體
+pub fn read_records(
+    filename: &str,
+    variant: Variant,
+    hainum: usize,
+) -> std::io::Result<(PatternHeader, Vec<Vec<u8>>)> {
+    let buffer = read_all(filename)?;
+    let (header, data) = decode(&buffer, variant, hainum)?;
+    let records = data.chunks(hainum).map(|c| c.to_vec()).collect();
+
+    Ok((header, records))
+}
+
+// 舊格式: 無檔頭, 只有 hainum 位元組的紀錄
+// is_tile 判斷單一位元組是否為該變體的牌
+pub fn read_legacy(
+    filename: &str,
+    hainum: usize,
+    is_tile: impl Fn(u8) -> bool,
+) -> std::io::Result<Vec<u8>> {
+    let invalid = |msg: String| Error::new(ErrorKind::InvalidData, msg);
+    let buffer = read_all(filename)?;
+
+    if buffer.starts_with(&MAGIC) {
+        Err(invalid(format!(
+            "{} already has a pattern header",
+            filename
+        )))?
+    }
+    if buffer.len() % hainum != 0 {
+        Err(invalid(format!(
+            "file size {} is not a multiple of {}",
+            buffer.len(),
+            hainum
+        )))?
+    }
+    if let Some(i) = buffer
+        .chunks(hainum)
+        .position(|record| !is_legacy_record(record, &is_tile))
+    {
+        Err(invalid(format!(
+            "record {} is not a {} tile hand",
+            i, hainum
+        )))?
+    }
+
+    Ok(buffer)
+}
+
+// records are sorted with at most four copies of a tile
+fn is_legacy_record(record: &[u8], is_tile: impl Fn(u8) -> bool) -> bool {
+    record.iter().all(|c| is_tile(*c))
+        && record.windows(2).all(|w| w[0] <= w[1])
+        && record.windows(5).all(|w| w[0] != w[4])
+}
+
+fn read_all(filename: &str) -> std::io::Result<Vec<u8>> {
+    let mut reader = File::open(filename)?;
+    let fsize = reader.metadata()?.len() as usize;
+    let mut buffer = Vec::with_capacity(fsize);
+    let size_read = reader.read_to_end(&mut buffer)?;
+    assert_eq!(size_read, fsize);
+    Ok(buffer)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RECORD: &[u8; 14] = b"AABBBCCCDDDEEE";
+
+    fn header(data: &[u8]) -> PatternHeader {
+        PatternHeader::new("test", Variant::Four, RECORD.len(), data)
+    }
+
+    #[test]
+    fn header_round_trip() {
+        let data = RECORD.repeat(3);
+        let header = header(&data);
+        let buffer = encode(&header, &data);
+        let (decoded, records) = decode(&buffer, Variant::Four, RECORD.len()).unwrap();
+
+        assert_eq!(decoded, header);
+        assert_eq!(decoded.records, 3);
+        assert_eq!(records, &data[..]);
+    }
+
+    #[test]
+    fn reject_legacy_and_corrupt() {
+        let data = RECORD.repeat(2);
+        assert!(decode(&data, Variant::Four, RECORD.len()).is_err());
+
+        let mut buffer = encode(&header(&data), &data);
+        *buffer.last_mut().unwrap() = b'F';
+        assert!(decode(&buffer, Variant::Four, RECORD.len()).is_err());
+    }
+
+    #[test]
+    fn reject_other_variant_and_hainum() {
+        let data = RECORD.repeat(2);
+        let buffer = encode(&header(&data), &data);
+        assert!(decode(&buffer, Variant::Three, RECORD.len()).is_err());
+        assert!(decode(&buffer, Variant::Four, 11).is_err());
+    }
+
+    #[test]
+    fn reject_overflowing_record_count() {
+        let data = RECORD.repeat(2);
+        let mut header = header(&data);
+        header.records = u64::MAX / 2;
+        assert!(decode(&encode(&header, &data), Variant::Four, RECORD.len()).is_err());
+    }
+
+    #[test]
+    fn legacy_record_checks() {
+        let is_tile = |c: u8| c.is_ascii_uppercase();
+        assert!(is_legacy_record(RECORD, is_tile));
+        assert!(!is_legacy_record(b"AABBBCCCDDDEEa", is_tile));
+        assert!(!is_legacy_record(b"BAABBCCCDDDEEE", is_tile));
+        assert!(!is_legacy_record(b"AAAAABBCCCDDDE", is_tile));
+    }
+}
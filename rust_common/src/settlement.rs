@@ -0,0 +1,270 @@
+use std::error::Error;
+
+use serde::{Deserialize, Serialize};
+
+// 榮和時誰付
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum DiscardPayment {
+    DiscarderPays, // 放銃者包付全部
+    EveryonePays,  // 各家照付
+}
+
+// 三人打時缺席一家的份
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum AbsentShare {
+    Dropped, // 不付, 自摸損
+    Split,   // 由在場者均分
+}
+
+// 結算規則, every player pays `base + per_point * score` to the winner
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub struct SettlementRules {
+    pub players: usize,       // 3 or 4, each variant checks its own
+    pub base: i64,            // 底
+    pub per_point: i64,       // 每分
+    pub dealer_doubles: bool, // 莊家輸贏加倍
+    pub discard: DiscardPayment,
+    pub absent: AbsentShare,
+    pub responsibility: bool, // 包牌者代付全部
+}
+
+// 一局的和了, seats from 0
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub struct Win {
+    pub winner: usize,
+    pub dealer: usize,
+    pub score: u16, // `HandList::score`
+    #[serde(default)]
+    pub discarder: Option<usize>, // None for 自摸
+    #[serde(default)]
+    pub liable: Option<usize>, // 包牌
+}
+
+impl SettlementRules {
+    pub fn validate(&self) -> Result<(), Box<dyn Error>> {
+        if self.players < 2 {
+            Err(format!(
+                "{} players, need a winner and a payer",
+                self.players
+            ))?
+        }
+        if self.base < 0 || self.per_point < 0 {
+            Err("base and per point must not be negative")?
+        }
+        Ok(())
+    }
+
+    // 每家的份, before anyone pays for another
+    fn share(&self, win: &Win, payer: usize) -> i64 {
+        let unit = self.base + self.per_point * win.score as i64;
+        match self.dealer_doubles && (win.winner == win.dealer || payer == win.dealer) {
+            true => unit * 2,
+            false => unit,
+        }
+    }
+
+    // signed payments, positive to the winner, summing to zero
+    pub fn settle(&self, win: &Win) -> Result<Vec<i64>, Box<dyn Error>> {
+        self.validate()?;
+        let seats = [
+            Some(win.winner),
+            Some(win.dealer),
+            win.discarder,
+            win.liable,
+        ];
+        if seats.iter().flatten().any(|s| *s >= self.players) {
+            Err(format!("seats must be below {}", self.players))?
+        }
+        if win.discarder == Some(win.winner) || win.liable == Some(win.winner) {
+            Err("the winner cannot be a payer")?
+        }
+
+        let payers: Vec<usize> = (0..self.players).filter(|p| *p != win.winner).collect();
+        let mut shares: Vec<i64> = payers.iter().map(|p| self.share(win, *p)).collect();
+        // 缺席的一家不是莊家
+        if self.players == 3 && self.absent == AbsentShare::Split {
+            let absent = self.share(win, usize::MAX);
+            let n = payers.len() as i64;
+            shares
+                .iter_mut()
+                .enumerate()
+                .for_each(|(i, s)| *s += absent / n + ((i as i64) < absent % n) as i64);
+        }
+
+        let total: i64 = shares.iter().sum();
+        let mut payments = vec![0; self.players];
+        payments[win.winner] = total;
+        match (self.responsibility, win.liable, win.discarder, self.discard) {
+            (true, Some(p), _, _) | (_, _, Some(p), DiscardPayment::DiscarderPays) => {
+                payments[p] = -total
+            }
+            _ => payers
+                .iter()
+                .zip(shares.iter())
+                .for_each(|(p, s)| payments[*p] = -s),
+        }
+
+        Ok(payments)
+    }
+}
+
+// 一場的帳
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Ledger {
+    rules: SettlementRules,
+    entries: Vec<(Win, Vec<i64>)>,
+}
+
+// 每家的統計
+#[derive(Clone, PartialEq, Eq, Debug, Default, Serialize)]
+pub struct PlayerSummary {
+    pub total: i64,
+    pub wins: usize,
+    pub self_draws: usize,
+    pub deal_ins: usize, // 放銃
+    pub liable: usize,   // 包牌
+    pub best_win: i64,
+    pub worst_loss: i64,
+}
+
+impl Ledger {
+    pub fn new(rules: SettlementRules) -> Result<Self, Box<dyn Error>> {
+        rules.validate()?;
+        Ok(Self {
+            rules,
+            entries: Vec::new(),
+        })
+    }
+
+    // settle and keep one win, returning its payments
+    pub fn record(&mut self, win: Win) -> Result<&[i64], Box<dyn Error>> {
+        let payments = self.rules.settle(&win)?;
+        self.entries.push((win, payments));
+        Ok(&self.entries.last().unwrap().1)
+    }
+
+    pub fn entries(&self) -> &[(Win, Vec<i64>)] {
+        &self.entries
+    }
+
+    pub fn totals(&self) -> Vec<i64> {
+        (0..self.rules.players)
+            .map(|p| self.entries.iter().map(|(_, pay)| pay[p]).sum())
+            .collect()
+    }
+
+    pub fn summary(&self) -> Vec<PlayerSummary> {
+        let mut summary = vec![PlayerSummary::default(); self.rules.players];
+        self.entries.iter().for_each(|(win, payments)| {
+            summary[win.winner].wins += 1;
+            if win.discarder.is_none() {
+                summary[win.winner].self_draws += 1;
+            }
+            if let Some(d) = win.discarder {
+                summary[d].deal_ins += 1;
+            }
+            if let Some(l) = win.liable {
+                summary[l].liable += 1;
+            }
+            summary.iter_mut().zip(payments).for_each(|(s, p)| {
+                s.total += p;
+                s.best_win = s.best_win.max(*p);
+                s.worst_loss = s.worst_loss.min(*p);
+            });
+        });
+        summary
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rules(players: usize) -> SettlementRules {
+        SettlementRules {
+            players,
+            base: 1,
+            per_point: 1,
+            dealer_doubles: true,
+            discard: DiscardPayment::DiscarderPays,
+            absent: AbsentShare::Dropped,
+            responsibility: true,
+        }
+    }
+
+    fn win(winner: usize, score: u16, discarder: Option<usize>) -> Win {
+        Win {
+            winner,
+            dealer: 0,
+            score,
+            discarder,
+            liable: None,
+        }
+    }
+
+    #[test]
+    fn self_draw_and_dealer() {
+        let r = rules(4);
+        // 3 points, unit 4, the dealer pays double
+        assert_eq!(r.settle(&win(1, 3, None)).unwrap(), vec![-8, 16, -4, -4]);
+        // the dealer wins double from everyone
+        assert_eq!(r.settle(&win(0, 3, None)).unwrap(), vec![24, -8, -8, -8]);
+    }
+
+    #[test]
+    fn discard_payment() {
+        let mut r = rules(4);
+        assert_eq!(r.settle(&win(1, 3, Some(2))).unwrap(), vec![0, 16, -16, 0]);
+        r.discard = DiscardPayment::EveryonePays;
+        assert_eq!(r.settle(&win(1, 3, Some(2))).unwrap(), vec![-8, 16, -4, -4]);
+
+        // 包牌 pays everything, even with everyone paying
+        let mut w = win(1, 3, Some(2));
+        w.liable = Some(3);
+        assert_eq!(r.settle(&w).unwrap(), vec![0, 16, 0, -16]);
+        r.responsibility = false;
+        assert_eq!(r.settle(&w).unwrap(), vec![-8, 16, -4, -4]);
+    }
+
+    #[test]
+    fn three_players_absent_share() {
+        let mut r = rules(3);
+        assert_eq!(r.settle(&win(1, 3, None)).unwrap(), vec![-8, 12, -4]);
+        // the absent non-dealer share of 4 is split
+        r.absent = AbsentShare::Split;
+        assert_eq!(r.settle(&win(1, 3, None)).unwrap(), vec![-10, 16, -6]);
+        // 5 split unevenly when the base is odd
+        r.base = 2;
+        r.dealer_doubles = false;
+        assert_eq!(r.settle(&win(1, 3, None)).unwrap(), vec![-8, 15, -7]);
+    }
+
+    #[test]
+    fn rejects_bad_wins() {
+        let r = rules(3);
+        assert!(r.settle(&win(3, 1, None)).is_err());
+        assert!(r.settle(&win(1, 1, Some(1))).is_err());
+        assert!(rules(1).settle(&win(0, 1, None)).is_err());
+    }
+
+    #[test]
+    fn ledger_summary() {
+        let mut ledger = Ledger::new(rules(4)).unwrap();
+        ledger.record(win(1, 3, None)).unwrap();
+        ledger.record(win(2, 1, Some(1))).unwrap();
+        assert!(ledger.record(win(4, 1, None)).is_err());
+        assert_eq!(ledger.entries().len(), 2);
+
+        let totals = ledger.totals();
+        assert_eq!(totals, vec![-8, 8, 4, -4]);
+        assert_eq!(totals.iter().sum::<i64>(), 0);
+
+        let summary = ledger.summary();
+        assert_eq!(summary[1].wins, 1);
+        assert_eq!(summary[1].self_draws, 1);
+        assert_eq!(summary[1].deal_ins, 1);
+        assert_eq!(summary[1].best_win, 16);
+        assert_eq!(summary[1].worst_loss, -8);
+        assert_eq!(summary[0].total, -8);
+    }
+}
@@ -0,0 +1,144 @@
+use std::{collections::HashMap, error::Error, time::Instant};
+
+use rust_common::pattern::read_records;
+
+use crate::{
+    decompose::{allsets, comb},
+    hand::{Hand, HANDVARIANT},
+    handchecker::HandList,
+    joker::{best, jokers},
+    pattern::VARIANT,
+    variant::CustomVariant,
+};
+
+mod decompose;
+mod hand;
+mod handchecker;
+mod joker;
+mod pattern;
+mod set;
+mod variant;
+
+// usage: agari <variant.json> [patterns.dat]
+fn main() -> Result<(), Box<dyn Error>> {
+    let args: Vec<String> = std::env::args().collect();
+    if args.len() < 2 {
+        eprintln!("usage: {} <variant.json> [patterns.dat]", args[0]);
+        std::process::exit(1);
+    }
+    let variant = CustomVariant::from_file(&args[1])?;
+    let filename = match args.get(2) {
+        Some(f) => f.clone(),
+        None => format!("patterns_custom_{}.dat", variant.name),
+    };
+
+    let time_read = Instant::now();
+    let (header, raw_hai_sets) = read_records(&filename, VARIANT, variant.hainum())?;
+    assert_eq!(raw_hai_sets.len() as u64, header.records);
+    if !header
+        .generator
+        .ends_with(&serde_json::to_string(&variant)?)
+    {
+        Err(format!(
+            "{} was not listed for variant {}",
+            filename, variant.name
+        ))?
+    }
+    println!(
+        "time read and chunks in {:.2} s",
+        time_read.elapsed().as_secs_f32()
+    );
+
+    println!("produce hand patterns");
+    let start = Instant::now();
+    // 自風取第一個風牌
+    let wind = variant.winds().first().cloned();
+    let mut hands: HashMap<HandList, (u64, u64)> = HashMap::new();
+    // 百搭數: 和牌形、組合數
+    let mut by_jokers: Vec<(u64, u64)> = vec![(0, 0); variant.jokers as usize + 1];
+    raw_hai_sets.into_iter().for_each(|raw_hai| {
+        let combinations = comb(&variant, &raw_hai);
+        let j = jokers(&raw_hai);
+        by_jokers[j].0 += 1;
+        by_jokers[j].1 += combinations;
+        // 有百搭的規則每個牌形只取得分最高的拆法, 有無百搭同樣計
+        let lists: Vec<HandList> = match variant.jokers {
+            0 => allsets(&variant, &raw_hai)
+                .into_iter()
+                .map(|s| s.to_handchecker(&variant, wind).hands())
+                .collect(),
+            _ => best(&variant, &raw_hai, wind)
+                .into_iter()
+                .map(|(_, l)| l)
+                .collect(),
+        };
+        lists.into_iter().for_each(|l| {
+            let v = hands.entry(l).or_insert((0, 0));
+            v.0 += 1;
+            v.1 += combinations;
+        });
+    });
+
+    println!(
+        "time produce patterns in {:.2} s",
+        start.elapsed().as_secs_f32()
+    );
+
+    println!("calculate combinations and total scores");
+    // 役種、和牌形、組合數、總分
+    let mut result: Vec<(Hand, u64, u64, u64)> = (0..HANDVARIANT)
+        .map(|i| (Hand::try_from(i).unwrap(), 0, 0, 0))
+        .collect();
+
+    hands
+        .into_iter()
+        .for_each(|(handlist, (pattern, combination))| {
+            let score = handlist.score() as u64;
+            (0..HANDVARIANT).filter(|i| handlist[*i]).for_each(|i| {
+                result[i].1 += pattern;
+                result[i].2 += combination;
+                result[i].3 += combination * score;
+            });
+        });
+
+    println!("end of process");
+    println!();
+
+    println!("{:4}{:8}{:13}{:4}", "役種", "和牌形", "組合數", "平均分數");
+    result
+        .into_iter()
+        .filter(|(_, pattern, _, _)| *pattern > 0)
+        .for_each(|(hand, pattern, combination, score)| {
+            println!(
+                "{:<4}{:>8}{:>16} {:>.*}",
+                hand.name(),
+                pattern,
+                combination,
+                5,
+                (score as f64 / combination as f64).to_string()
+            );
+        });
+
+    // 百搭使和牌組合增加的比例, 以無百搭的組合數為基準
+    if variant.jokers > 0 {
+        println!();
+        println!("{:4}{:8}{:13}{:4}", "百搭", "和牌形", "組合數", "累計倍率");
+        let base = by_jokers[0].1 as f64;
+        let mut total = 0;
+        by_jokers
+            .iter()
+            .enumerate()
+            .for_each(|(j, (pattern, combination))| {
+                total += combination;
+                println!(
+                    "{:<6}{:>8}{:>16} {:.5}",
+                    j,
+                    pattern,
+                    combination,
+                    total as f64 / base
+                );
+            });
+    }
+
+    Ok(())
+}
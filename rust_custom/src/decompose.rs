@@ -0,0 +1,138 @@
+use crate::{
+    set::{Meld, MeldKind, Set},
+    variant::{CustomVariant, Tile, JOKER},
+};
+
+// 所有門前拆法, 各拆法不重複
+pub fn allsets(variant: &CustomVariant, raw: &[u8]) -> Vec<Set> {
+    assert_eq!(raw.len(), variant.hainum());
+    let mut counts = vec![0u8; variant.tiles()];
+    raw.iter()
+        .map(|r| variant.decode(*r).expect("tile out of the variant"))
+        .for_each(|t| counts[t as usize] += 1);
+
+    let mut result = Vec::new();
+    for pair in 0..counts.len() {
+        if counts[pair] < 2 {
+            continue;
+        }
+        counts[pair] -= 2;
+        let mut melds = Vec::with_capacity(variant.melds as usize);
+        get_melds(variant, &mut counts, &mut melds, &mut |melds| {
+            result.push(Set {
+                pair: pair as Tile,
+                melds: melds.to_vec(),
+            })
+        });
+        counts[pair] += 2;
+    }
+
+    result
+}
+
+// the first remaining tile is either in a pung or heads a chow
+fn get_melds(
+    variant: &CustomVariant,
+    counts: &mut [u8],
+    melds: &mut Vec<Meld>,
+    found: &mut dyn FnMut(&[Meld]),
+) {
+    let first = match counts.iter().position(|c| *c > 0) {
+        Some(i) => i,
+        None => return found(melds),
+    };
+
+    if counts[first] >= 3 {
+        counts[first] -= 3;
+        melds.push(Meld::new(first as Tile, MeldKind::ConcealedPung));
+        get_melds(variant, counts, melds, found);
+        melds.pop();
+        counts[first] += 3;
+    }
+
+    if variant.is_chow_head(first as Tile) && counts[first + 1] > 0 && counts[first + 2] > 0 {
+        (first..first + 3).for_each(|i| counts[i] -= 1);
+        melds.push(Meld::new(first as Tile, MeldKind::ConcealedChow));
+        get_melds(variant, counts, melds, found);
+        melds.pop();
+        (first..first + 3).for_each(|i| counts[i] += 1);
+    }
+}
+
+// 每種牌取法 C(copies, count) 之積, 百搭取法 C(jokers, count)
+#[allow(dead_code)]
+pub fn comb(variant: &CustomVariant, raw: &[u8]) -> u64 {
+    assert_eq!(raw.len(), variant.hainum());
+    let mut counts = vec![0u64; variant.tiles()];
+    raw.iter()
+        .filter(|r| **r != JOKER)
+        .map(|r| variant.decode(*r).expect("tile out of the variant"))
+        .for_each(|t| counts[t as usize] += 1);
+    let jokers = raw.iter().filter(|r| **r == JOKER).count() as u64;
+
+    counts
+        .into_iter()
+        .map(|c| binomial(variant.copies as u64, c))
+        .product::<u64>()
+        * binomial(variant.jokers as u64, jokers)
+}
+
+fn binomial(n: u64, k: u64) -> u64 {
+    assert!(k <= n, "more than {} copies of a tile", n);
+    (0..k).fold(1, |acc, i| acc * (n - i) / (i + 1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::variant::{Honor, HonorKind};
+
+    fn variant(copies: u8, melds: u8) -> CustomVariant {
+        CustomVariant {
+            name: "test".to_string(),
+            suits: 1,
+            numbers: 9,
+            honors: vec![Honor {
+                name: "Red".to_string(),
+                kind: HonorKind::Dragon,
+            }],
+            copies,
+            melds,
+            jokers: 0,
+        }
+    }
+
+    #[test]
+    fn comb_uses_copies() {
+        let v = variant(4, 4);
+        // Red Red 111 222 333 999
+        let raw = *b"AABBBCCCDDDJJJ";
+        assert_eq!(comb(&v, &raw), 6 * 4 * 4 * 4 * 4);
+
+        let v = variant(3, 4);
+        assert_eq!(comb(&v, &raw), 3);
+
+        // two of the eight jokers stand in for Red Red
+        let mut v = variant(4, 4);
+        v.jokers = 8;
+        let raw = *b"**BBBCCCDDDJJJ";
+        assert_eq!(comb(&v, &raw), 28 * 4 * 4 * 4 * 4);
+    }
+
+    #[test]
+    fn allsets_pungs_and_chows() {
+        let v = variant(4, 4);
+        let raw = *b"AABBBCCCDDDJJJ";
+        let sets = allsets(&v, &raw);
+
+        // three pungs or three chows of 123
+        assert_eq!(sets.len(), 2);
+        assert!(sets.iter().all(|s| s.pair == 0 && s.melds.len() == 4));
+
+        // 1112345678999 + 5 only splits around the pair of 5
+        let raw = *b"BBBCDEFFGHIJJJ";
+        let sets = allsets(&v, &raw);
+        assert_eq!(sets.len(), 1);
+        assert_eq!(sets[0].pair, 5);
+    }
+}
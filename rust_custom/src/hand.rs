@@ -0,0 +1,133 @@
+use serde::{Deserialize, Serialize};
+
+pub const HANDVARIANT: usize = Hand::NoPoint as usize + 1;
+#[allow(dead_code)]
+pub const HANDMAXSCORE: u16 = 32;
+
+// 通用役種, 大小與張數由變體描述決定
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum Hand {
+    AllChows,              // 平和
+    DragonPung,            // 役牌三元
+    WindPung,              // 役牌自風
+    PureStraight,          // 一氣通貫
+    AllPungs,              // 對對和
+    LittleDragons,         // 小三元
+    BigDragons,            // 大三元
+    LittleWinds,           // 小四喜
+    AllSimples,            // 斷幺九
+    OutsideHands,          // 混全帶幺九
+    AllTerminalsAndHonors, // 混老頭
+    HalfFlush,             // 混一色
+    FullFlush,             // 清一色
+    TwoConcealedPungs,     // 二暗刻
+    ThreeConcealedPungs,   // 三暗刻
+    PureDoubleChow,        // 一般高
+    MixedTripleChow,       // 三色同順
+    TriplePung,            // 三色同刻
+    AllHonors,             // 字一色
+    BigWinds,              // 大四喜
+    AllTerminals,          // 清老頭
+    NoJoker,               // 無百搭
+    NoPoint,               // 無役
+}
+
+#[allow(dead_code)]
+impl Hand {
+    pub fn score(&self) -> u16 {
+        match self {
+            Self::AllChows => 1,
+            Self::DragonPung => 2,
+            Self::WindPung => 2,
+            Self::PureStraight => 4,
+            Self::AllPungs => 4,
+            Self::LittleDragons => 6,
+            Self::BigDragons => 8,
+            Self::LittleWinds => 16,
+            Self::AllSimples => 1,
+            Self::OutsideHands => 4,
+            Self::AllTerminalsAndHonors => 12,
+            Self::HalfFlush => 4,
+            Self::FullFlush => 8,
+            Self::TwoConcealedPungs => 1,
+            Self::ThreeConcealedPungs => 3,
+            Self::PureDoubleChow => 1,
+            Self::MixedTripleChow => 3,
+            Self::TriplePung => 12,
+            // 絕對滿貫
+            Self::AllHonors => HANDMAXSCORE,
+            Self::BigWinds => HANDMAXSCORE,
+            Self::AllTerminals => HANDMAXSCORE,
+            // 只在有百搭的變體
+            Self::NoJoker => 2,
+            // 無役
+            Self::NoPoint => 0,
+        }
+    }
+
+    // 絕對滿貫
+    pub fn is_limit(&self) -> bool {
+        matches!(self, Self::AllHonors | Self::BigWinds | Self::AllTerminals)
+    }
+
+    pub fn name(&self) -> String {
+        match self {
+            Self::AllChows => "平和".to_string(),
+            Self::DragonPung => "役牌".to_string(),
+            Self::WindPung => "自風".to_string(),
+            Self::PureStraight => "一氣".to_string(),
+            Self::AllPungs => "對對和".to_string(),
+            Self::LittleDragons => "小三元".to_string(),
+            Self::BigDragons => "大三元".to_string(),
+            Self::LittleWinds => "小四喜".to_string(),
+            Self::AllSimples => "斷幺九".to_string(),
+            Self::OutsideHands => "混全帶".to_string(),
+            Self::AllTerminalsAndHonors => "混老頭".to_string(),
+            Self::HalfFlush => "混一色".to_string(),
+            Self::FullFlush => "清一色".to_string(),
+            Self::TwoConcealedPungs => "二暗刻".to_string(),
+            Self::ThreeConcealedPungs => "三暗刻".to_string(),
+            Self::PureDoubleChow => "一般高".to_string(),
+            Self::MixedTripleChow => "三色順".to_string(),
+            Self::TriplePung => "三色刻".to_string(),
+            Self::AllHonors => "字一色".to_string(),
+            Self::BigWinds => "大四喜".to_string(),
+            Self::AllTerminals => "清老頭".to_string(),
+            Self::NoJoker => "無百搭".to_string(),
+            Self::NoPoint => "無役".to_string(),
+        }
+    }
+}
+
+impl TryFrom<usize> for Hand {
+    type Error = ();
+
+    fn try_from(value: usize) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(Self::AllChows),
+            1 => Ok(Self::DragonPung),
+            2 => Ok(Self::WindPung),
+            3 => Ok(Self::PureStraight),
+            4 => Ok(Self::AllPungs),
+            5 => Ok(Self::LittleDragons),
+            6 => Ok(Self::BigDragons),
+            7 => Ok(Self::LittleWinds),
+            8 => Ok(Self::AllSimples),
+            9 => Ok(Self::OutsideHands),
+            10 => Ok(Self::AllTerminalsAndHonors),
+            11 => Ok(Self::HalfFlush),
+            12 => Ok(Self::FullFlush),
+            13 => Ok(Self::TwoConcealedPungs),
+            14 => Ok(Self::ThreeConcealedPungs),
+            15 => Ok(Self::PureDoubleChow),
+            16 => Ok(Self::MixedTripleChow),
+            17 => Ok(Self::TriplePung),
+            18 => Ok(Self::AllHonors),
+            19 => Ok(Self::BigWinds),
+            20 => Ok(Self::AllTerminals),
+            21 => Ok(Self::NoJoker),
+            22 => Ok(Self::NoPoint),
+            _ => Err(()),
+        }
+    }
+}
@@ -0,0 +1,349 @@
+use bitvec::array::BitArray;
+use bitvec::BitArr;
+use itertools::Itertools;
+
+use crate::hand::Hand;
+use crate::hand::HANDMAXSCORE;
+use crate::hand::HANDVARIANT;
+use crate::set::{Meld, Set};
+use crate::variant::{CustomVariant, Tile};
+
+#[derive(Clone, Hash, PartialEq, Eq, Debug)]
+pub struct HandList(BitArr!(for HANDVARIANT, in u32));
+
+#[allow(dead_code)]
+impl HandList {
+    pub fn new() -> HandList {
+        HandList(BitArray::ZERO)
+    }
+
+    pub fn score(&self) -> u16 {
+        let s: u16 = self
+            .iter()
+            .enumerate()
+            .take(HANDVARIANT)
+            .map(|(i, b)| Hand::try_from(i).unwrap().score() * *b as u16)
+            .sum();
+
+        s.min(HANDMAXSCORE)
+    }
+}
+
+impl std::ops::Deref for HandList {
+    type Target = BitArr!(for HANDVARIANT, in u32);
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl std::ops::DerefMut for HandList {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl Set {
+    pub fn to_handchecker<'a>(
+        &self,
+        variant: &'a CustomVariant,
+        wind: Option<Tile>,
+    ) -> HandChecker<'a> {
+        HandChecker {
+            variant,
+            pair: self.pair,
+            melds: self.melds.clone(),
+            wind,
+        }
+    }
+}
+
+// 通用判定, 牌的性質全由變體描述決定
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct HandChecker<'a> {
+    variant: &'a CustomVariant,
+    pair: Tile,
+    melds: Vec<Meld>,
+    wind: Option<Tile>, // 自風, None when the variant has no winds
+}
+
+#[allow(dead_code)]
+impl HandChecker<'_> {
+    pub fn pair(&self) -> Tile {
+        self.pair
+    }
+
+    pub fn melds(&self) -> &Vec<Meld> {
+        &self.melds
+    }
+
+    pub fn wind(&self) -> Option<Tile> {
+        self.wind
+    }
+
+    pub fn hands(&self) -> HandList {
+        let mut checker = HandList::new();
+        // all_chow_pung must be first
+        self.all_chow_pung(&mut checker);
+        self.score_pungs(&mut checker);
+        self.honors(&mut checker);
+        self.straight_simple_terminal(&mut checker);
+        self.pure_mix(&mut checker);
+        self.conceal_pungs(&mut checker);
+        self.same_chows(&mut checker);
+
+        // 絕對滿貫 clears every other hand
+        if (0..HANDVARIANT).any(|i| checker[i] && Hand::try_from(i).unwrap().is_limit()) {
+            (0..HANDVARIANT)
+                .filter(|i| !Hand::try_from(*i).unwrap().is_limit())
+                .for_each(|i| checker.set(i, false));
+        }
+        if checker.not_any() {
+            checker.set(Hand::NoPoint as usize, true);
+        }
+
+        checker
+    }
+
+    // 平和、對對和
+    fn all_chow_pung(&self, checker: &mut HandList) {
+        if self.melds.iter().all(|m| m.kind.is_chow()) {
+            checker.set(Hand::AllChows as usize, true);
+        } else if self.melds.iter().all(|m| m.kind.is_pung()) {
+            checker.set(Hand::AllPungs as usize, true);
+        }
+    }
+
+    // 役牌
+    fn score_pungs(&self, checker: &mut HandList) {
+        if self.melds.iter().any(|m| self.variant.is_dragon(m.head)) {
+            checker.set(Hand::DragonPung as usize, true);
+        }
+        if self.melds.iter().any(|m| Some(m.head) == self.wind) {
+            checker.set(Hand::WindPung as usize, true);
+        }
+    }
+
+    // 字牌類, every wind or dragon of the variant counts
+    fn honors(&self, checker: &mut HandList) {
+        let big_little = |kinds: Vec<Tile>, big: Hand, little: Hand, checker: &mut HandList| {
+            if kinds.len() < 2 {
+                return;
+            }
+            let melds = self
+                .melds
+                .iter()
+                .filter(|m| kinds.contains(&m.head))
+                .count();
+            if melds == kinds.len() {
+                checker.set(big as usize, true);
+            } else if melds + 1 == kinds.len() && kinds.contains(&self.pair) {
+                checker.set(little as usize, true);
+            }
+        };
+
+        big_little(
+            self.variant.winds(),
+            Hand::BigWinds,
+            Hand::LittleWinds,
+            checker,
+        );
+        big_little(
+            self.variant.dragons(),
+            Hand::BigDragons,
+            Hand::LittleDragons,
+            checker,
+        );
+    }
+
+    // 一氣、幺九類
+    fn straight_simple_terminal(&self, checker: &mut HandList) {
+        let v = self.variant;
+
+        // 一氣: chows from 1 to the last number in one suit
+        if v.numbers.is_multiple_of(3) && v.numbers >= 9 {
+            let is_straight = |s: u8| {
+                (0..v.numbers / 3).all(|k| {
+                    self.melds
+                        .iter()
+                        .any(|m| m.kind.is_chow() && m.head == v.tile(s, k * 3 + 1))
+                })
+            };
+            if (0..v.suits).any(is_straight) {
+                checker.set(Hand::PureStraight as usize, true);
+                return;
+            }
+        }
+
+        // 斷幺
+        let melds_simple = self
+            .melds
+            .iter()
+            .all(|m| m.tiles().iter().all(|t| v.is_simple(*t)));
+        if v.is_simple(self.pair) && melds_simple {
+            checker.set(Hand::AllSimples as usize, true);
+            return;
+        }
+
+        // 清老、混老、混全
+        let pair_outside = !v.is_simple(self.pair);
+        match checker[Hand::AllPungs as usize] {
+            true => {
+                if v.is_terminal(self.pair) && self.melds.iter().all(|m| v.is_terminal(m.head)) {
+                    checker.set(Hand::AllTerminals as usize, true);
+                } else if pair_outside && self.melds.iter().all(|m| !v.is_simple(m.head)) {
+                    checker.set(Hand::AllTerminalsAndHonors as usize, true);
+                }
+            }
+            false => {
+                let melds_outside = self
+                    .melds
+                    .iter()
+                    .all(|m| m.tiles().iter().any(|t| !v.is_simple(*t)));
+                if pair_outside && melds_outside {
+                    checker.set(Hand::OutsideHands as usize, true);
+                }
+            }
+        }
+    }
+
+    // 一色類、三色類
+    fn pure_mix(&self, checker: &mut HandList) {
+        let v = self.variant;
+
+        // 字一色
+        let has_honor = v.is_honor(self.pair) || self.melds.iter().any(|m| v.is_honor(m.head));
+        let suits: Vec<u8> = std::iter::once(self.pair)
+            .chain(self.melds.iter().map(|m| m.head))
+            .filter_map(|t| v.suit(t))
+            .unique()
+            .collect();
+        match (suits.len(), has_honor) {
+            (0, _) => {
+                checker.set(Hand::AllHonors as usize, true);
+                return;
+            }
+            (1, true) => checker.set(Hand::HalfFlush as usize, true),
+            (1, false) => checker.set(Hand::FullFlush as usize, true),
+            _ => (),
+        }
+
+        // 三色: the same number in three different suits
+        let three_suits = |chow: bool| {
+            self.melds
+                .iter()
+                .filter(|m| m.kind.is_chow() == chow && !v.is_honor(m.head))
+                .map(|m| (v.number(m.head), v.suit(m.head)))
+                .unique()
+                .counts_by(|(n, _)| n)
+                .into_values()
+                .any(|c| c >= 3)
+        };
+        if three_suits(true) {
+            checker.set(Hand::MixedTripleChow as usize, true);
+        }
+        if three_suits(false) {
+            checker.set(Hand::TriplePung as usize, true);
+        }
+    }
+
+    // 暗刻
+    fn conceal_pungs(&self, checker: &mut HandList) {
+        match self.melds.iter().filter(|m| m.kind.is_pung()).count() {
+            0 | 1 => (),
+            2 => checker.set(Hand::TwoConcealedPungs as usize, true),
+            _ => checker.set(Hand::ThreeConcealedPungs as usize, true),
+        }
+    }
+
+    // 一般高
+    fn same_chows(&self, checker: &mut HandList) {
+        let mut chows: Vec<Tile> = self
+            .melds
+            .iter()
+            .filter(|m| m.kind.is_chow())
+            .map(|m| m.head)
+            .collect();
+        chows.sort();
+        if chows.windows(2).any(|w| w[0] == w[1]) {
+            checker.set(Hand::PureDoubleChow as usize, true);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::decompose::allsets;
+
+    fn four() -> CustomVariant {
+        CustomVariant::from_file(concat!(env!("CARGO_MANIFEST_DIR"), "/variants/four.json"))
+            .unwrap()
+    }
+
+    fn hands(v: &CustomVariant, raw: &[u8]) -> Vec<HandList> {
+        let wind = v.winds().first().cloned();
+        allsets(v, raw)
+            .iter()
+            .map(|s| s.to_handchecker(v, wind).hands())
+            .collect()
+    }
+
+    #[test]
+    fn four_player_hands() {
+        let v = four();
+        let mut raw: Vec<u8> = [
+            (0, 1),
+            (0, 2),
+            (0, 3),
+            (1, 4),
+            (1, 5),
+            (1, 6),
+            (1, 7),
+            (1, 8),
+            (1, 9),
+            (2, 5),
+            (2, 5),
+            (0, 5),
+            (0, 6),
+            (0, 7),
+        ]
+        .iter()
+        .map(|(s, n)| v.encode(v.tile(*s, *n)))
+        .collect();
+        raw.sort();
+        let lists = hands(&v, &raw);
+        assert_eq!(lists.len(), 1);
+        assert!(lists[0][Hand::AllChows as usize]);
+        assert!(!lists[0][Hand::PureStraight as usize]);
+        assert_eq!(lists[0].score(), 1);
+
+        // 白白 東東東 南南南 西西西 北北北
+        let raw = *b"CCDDDEEEFFFGGG";
+        let lists = hands(&v, &raw);
+        assert_eq!(lists.len(), 1);
+        let hl = &lists[0];
+        assert!(hl[Hand::AllHonors as usize] && hl[Hand::BigWinds as usize]);
+        assert_eq!(hl.count_ones(), 2);
+        assert_eq!(hl.score(), HANDMAXSCORE);
+    }
+
+    #[test]
+    fn three_player_hands() {
+        let v =
+            CustomVariant::from_file(concat!(env!("CARGO_MANIFEST_DIR"), "/variants/three.json"))
+                .unwrap();
+        // Moon Moon, Sun Sun Sun, Red Red Red, 111 in the first suit
+        let raw = *b"AABBBCCCFFF";
+        let lists = hands(&v, &raw);
+        assert_eq!(lists.len(), 1);
+        let hl = &lists[0];
+        assert!(hl[Hand::LittleDragons as usize]);
+        assert!(hl[Hand::DragonPung as usize]);
+        assert!(hl[Hand::WindPung as usize]);
+        assert!(hl[Hand::AllPungs as usize]);
+        assert!(hl[Hand::AllTerminalsAndHonors as usize]);
+        assert!(hl[Hand::HalfFlush as usize]);
+        assert!(hl[Hand::ThreeConcealedPungs as usize]);
+    }
+}
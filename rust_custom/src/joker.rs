@@ -0,0 +1,122 @@
+use itertools::Itertools;
+
+use crate::{
+    decompose::allsets,
+    hand::{Hand, HANDVARIANT},
+    handchecker::HandList,
+    set::Set,
+    variant::{CustomVariant, Tile, JOKER},
+};
+
+pub fn jokers(raw: &[u8]) -> usize {
+    raw.iter().filter(|r| **r == JOKER).count()
+}
+
+// every way the jokers stand in for tiles, at most `copies` of a tile
+pub fn substitutions(variant: &CustomVariant, raw: &[u8]) -> Vec<Vec<u8>> {
+    let tiles: Vec<u8> = raw.iter().cloned().filter(|r| *r != JOKER).collect();
+    let mut counts = vec![0u8; variant.tiles()];
+    tiles
+        .iter()
+        .map(|r| variant.decode(*r).expect("tile out of the variant"))
+        .for_each(|t| counts[t as usize] += 1);
+
+    (0..variant.tiles() as Tile)
+        .combinations_with_replacement(raw.len() - tiles.len())
+        .filter(|sub| {
+            let mut added = counts.clone();
+            sub.iter().for_each(|t| added[*t as usize] += 1);
+            added.iter().all(|c| *c <= variant.copies)
+        })
+        .map(|sub| {
+            let mut hai: Vec<u8> = tiles
+                .iter()
+                .cloned()
+                .chain(sub.into_iter().map(|t| variant.encode(t)))
+                .collect();
+            hai.sort();
+            hai
+        })
+        .collect()
+}
+
+// 無百搭 goes to hands without jokers, a limit hand stays alone
+pub fn with_no_joker(list: &HandList) -> HandList {
+    let mut list = list.clone();
+    if !(0..HANDVARIANT).any(|i| list[i] && Hand::try_from(i).unwrap().is_limit()) {
+        list.set(Hand::NoPoint as usize, false);
+        list.set(Hand::NoJoker as usize, true);
+    }
+    list
+}
+
+// the best scoring substitution, the first one on ties, None when none wins
+pub fn best(variant: &CustomVariant, raw: &[u8], wind: Option<Tile>) -> Option<(Set, HandList)> {
+    let no_joker = variant.jokers > 0 && jokers(raw) == 0;
+    substitutions(variant, raw)
+        .iter()
+        .flat_map(|s| allsets(variant, s))
+        .map(|s| {
+            let list = s.to_handchecker(variant, wind).hands();
+            match no_joker {
+                true => (s, with_no_joker(&list)),
+                false => (s, list),
+            }
+        })
+        .fold(
+            None,
+            |best: Option<(Set, HandList)>, (s, list)| match best {
+                Some(b) if b.1.score() >= list.score() => Some(b),
+                _ => Some((s, list)),
+            },
+        )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn four() -> CustomVariant {
+        let mut v: CustomVariant =
+            serde_json::from_str(include_str!("../variants/four.json")).unwrap();
+        v.jokers = 4;
+        v
+    }
+
+    #[test]
+    fn joker_fills_pair_and_meld() {
+        let v = four();
+        // 中中中 發發發 白白白 1a1a1a 5a + 百搭 is 大三元 with 5a5a
+        let raw = *b"*AAABBBCCCHHHL";
+        assert_eq!(substitutions(&v, &raw).len(), v.tiles());
+
+        let (set, list) = best(&v, &raw, None).unwrap();
+        assert_eq!(set.pair, v.tile(0, 5));
+        assert!(list[Hand::BigDragons as usize]);
+        assert!(!list[Hand::NoJoker as usize]);
+
+        // the same hand without a joker earns 無百搭
+        let (_, list) = best(&v, b"AAABBBCCCHHHLL", None).unwrap();
+        assert!(list[Hand::NoJoker as usize]);
+    }
+
+    #[test]
+    fn no_substitution_wins() {
+        let v = four();
+        let mut raw = vec![JOKER, JOKER];
+        raw.extend((0..12).map(|i| v.encode(i * 3)));
+        assert!(best(&v, &raw, None).is_none());
+    }
+
+    #[test]
+    fn no_joker_leaves_limits_alone() {
+        let mut list = HandList::new();
+        list.set(Hand::AllHonors as usize, true);
+        assert_eq!(with_no_joker(&list), list);
+
+        let mut list = HandList::new();
+        list.set(Hand::NoPoint as usize, true);
+        let list = with_no_joker(&list);
+        assert!(list[Hand::NoJoker as usize] && !list[Hand::NoPoint as usize]);
+    }
+}
@@ -0,0 +1,262 @@
+use std::{error::Error, time::Instant};
+
+use decompose::allsets;
+use itertools::Itertools;
+use pattern::VARIANT;
+use rust_common::pattern::write_patterns;
+use set::{Meld, MeldKind};
+use variant::{CustomVariant, Tile, JOKER};
+
+mod decompose;
+mod pattern;
+mod set;
+mod variant;
+
+// usage: list <variant.json>
+fn main() -> Result<(), Box<dyn Error>> {
+    let args: Vec<String> = std::env::args().collect();
+    if args.len() < 2 {
+        eprintln!("usage: {} <variant.json>", args[0]);
+        std::process::exit(1);
+    }
+    let variant = CustomVariant::from_file(&args[1])?;
+
+    let start = Instant::now();
+    let mut data = patterns(&variant);
+    let hainum = variant.hainum();
+    let joker_data = with_jokers(&variant, &data);
+    data.extend(joker_data);
+
+    // 依牌序排序, 百搭在前; 不同和牌換成百搭後可能相同
+    let mut order: Vec<usize> = (0..data.len() / hainum).collect();
+    order.sort_by(|a, b| {
+        data[a * hainum..(a + 1) * hainum].cmp(&data[b * hainum..(b + 1) * hainum])
+    });
+    order.dedup_by(|a, b| {
+        data[*a * hainum..(*a + 1) * hainum] == data[*b * hainum..(*b + 1) * hainum]
+    });
+    let sorted: Vec<u8> = order
+        .into_iter()
+        .flat_map(|i| data[i * hainum..(i + 1) * hainum].iter().cloned())
+        .collect();
+
+    let filename = format!("patterns_custom_{}.dat", variant.name);
+    write_patterns(&filename, &generator(&variant), VARIANT, hainum, &sorted)?;
+    println!(
+        "{} patterns of {} tiles into {} in {:.2} s",
+        sorted.len() / hainum,
+        hainum,
+        filename,
+        start.elapsed().as_secs_f32()
+    );
+
+    Ok(())
+}
+
+// the variant description is kept in the header so agari can check it
+fn generator(variant: &CustomVariant) -> String {
+    format!(
+        "{} list {} {}",
+        env!("CARGO_PKG_NAME"),
+        env!("CARGO_PKG_VERSION"),
+        serde_json::to_string(variant).unwrap()
+    )
+}
+
+// 雀頭加不遞減的面子組, 每種和牌只在其第一種拆法時輸出
+fn patterns(variant: &CustomVariant) -> Vec<u8> {
+    let melds: Vec<Meld> = (0..variant.tiles() as Tile)
+        .flat_map(|t| {
+            let pung = match variant.copies >= 3 {
+                true => Some(Meld::new(t, MeldKind::ConcealedPung)),
+                false => None,
+            };
+            let chow = match variant.is_chow_head(t) {
+                true => Some(Meld::new(t, MeldKind::ConcealedChow)),
+                false => None,
+            };
+            pung.into_iter().chain(chow)
+        })
+        .collect();
+
+    let mut data = Vec::new();
+    let mut counts = vec![0u8; variant.tiles()];
+    let mut chosen = Vec::with_capacity(variant.melds as usize);
+    for pair in 0..variant.tiles() {
+        counts[pair] += 2;
+        add_melds(
+            variant,
+            &melds,
+            0,
+            pair as Tile,
+            &mut counts,
+            &mut chosen,
+            &mut data,
+        );
+        counts[pair] -= 2;
+    }
+
+    data
+}
+
+fn add_melds(
+    variant: &CustomVariant,
+    melds: &[Meld],
+    from: usize,
+    pair: Tile,
+    counts: &mut [u8],
+    chosen: &mut Vec<Meld>,
+    data: &mut Vec<u8>,
+) {
+    if chosen.len() == variant.melds as usize {
+        let record: Vec<u8> = counts
+            .iter()
+            .enumerate()
+            .flat_map(|(t, c)| std::iter::repeat_n(variant.encode(t as Tile), *c as usize))
+            .collect();
+        if is_first_set(variant, &record, pair, chosen) {
+            data.extend(record);
+        }
+        return;
+    }
+
+    for (i, m) in melds.iter().enumerate().skip(from) {
+        let tiles = m.tiles();
+        tiles.iter().for_each(|t| counts[*t as usize] += 1);
+        if tiles.iter().all(|t| counts[*t as usize] <= variant.copies) {
+            chosen.push(*m);
+            add_melds(variant, melds, i, pair, counts, chosen, data);
+            chosen.pop();
+        }
+        tiles.iter().for_each(|t| counts[*t as usize] -= 1);
+    }
+}
+
+fn is_first_set(variant: &CustomVariant, record: &[u8], pair: Tile, chosen: &[Meld]) -> bool {
+    let first = allsets(variant, record).into_iter().next().unwrap();
+    let mut melds = first.melds;
+    melds.sort();
+    let mut chosen = chosen.to_vec();
+    chosen.sort();
+
+    first.pair == pair && melds == chosen
+}
+
+// 每種和牌中至多 jokers 張換成百搭
+fn with_jokers(variant: &CustomVariant, data: &[u8]) -> Vec<u8> {
+    let mut result = Vec::new();
+    if variant.jokers == 0 {
+        return result;
+    }
+    data.chunks(variant.hainum()).for_each(|record| {
+        let grouped = record.iter().group_by(|r| **r);
+        let groups: Vec<(u8, usize)> = grouped.into_iter().map(|(r, g)| (r, g.count())).collect();
+        let mut taken = vec![0; groups.len()];
+        replace_tiles(variant.jokers as usize, &groups, 0, &mut taken, &mut result);
+    });
+    result
+}
+
+// taken[i] tiles of groups[i] are replaced, at least one in all
+fn replace_tiles(
+    jokers: usize,
+    groups: &[(u8, usize)],
+    from: usize,
+    taken: &mut [usize],
+    result: &mut Vec<u8>,
+) {
+    if from == groups.len() {
+        let replaced: usize = taken.iter().sum();
+        if replaced > 0 {
+            result.extend(std::iter::repeat_n(JOKER, replaced));
+            groups
+                .iter()
+                .zip(taken.iter())
+                .for_each(|((r, c), t)| result.extend(std::iter::repeat_n(*r, c - t)));
+        }
+        return;
+    }
+
+    let left = jokers - taken[..from].iter().sum::<usize>();
+    for t in 0..=left.min(groups[from].1) {
+        taken[from] = t;
+        replace_tiles(jokers, groups, from + 1, taken, result);
+    }
+    taken[from] = 0;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::variant::Honor;
+
+    #[test]
+    fn one_meld_one_suit() {
+        // 1 to 3 in one suit, one meld and a pair
+        let variant = CustomVariant {
+            name: "tiny".to_string(),
+            suits: 1,
+            numbers: 3,
+            honors: vec![],
+            copies: 4,
+            melds: 1,
+            jokers: 0,
+        };
+        let data = patterns(&variant);
+        // pungs with a different pair: 3 * 2, pung and pair of one tile is five copies
+        // chow 123 with any pair: 3
+        assert_eq!(data.len() / variant.hainum(), 9);
+
+        let mut records: Vec<&[u8]> = data.chunks(variant.hainum()).collect();
+        records.sort();
+        records.dedup();
+        assert_eq!(records.len(), 9);
+    }
+
+    #[test]
+    fn duplicate_hands_listed_once() {
+        // 111222333 with any pair decomposes twice but is listed once
+        let variant = CustomVariant {
+            name: "small".to_string(),
+            suits: 1,
+            numbers: 3,
+            honors: vec![Honor {
+                name: "Red".to_string(),
+                kind: variant::HonorKind::Dragon,
+            }],
+            copies: 4,
+            melds: 3,
+            jokers: 0,
+        };
+        let data = patterns(&variant);
+        let mut records: Vec<&[u8]> = data.chunks(variant.hainum()).collect();
+        let total = records.len();
+        records.sort();
+        records.dedup();
+        assert_eq!(records.len(), total);
+        assert!(records.contains(&&b"AABBBCCCDDD"[..]));
+    }
+
+    #[test]
+    fn jokers_replace_tiles() {
+        let mut variant = CustomVariant {
+            name: "tiny".to_string(),
+            suits: 1,
+            numbers: 3,
+            honors: vec![],
+            copies: 4,
+            melds: 1,
+            jokers: 2,
+        };
+        // AABBB: up to two of the A pair and the B pung
+        let data = with_jokers(&variant, b"AABBB");
+        let records: Vec<&[u8]> = data.chunks(variant.hainum()).collect();
+        assert_eq!(
+            records,
+            vec![&b"*AABB"[..], b"**AAB", b"*ABBB", b"**ABB", b"**BBB"]
+        );
+
+        variant.jokers = 0;
+        assert!(with_jokers(&variant, b"AABBB").is_empty());
+    }
+}
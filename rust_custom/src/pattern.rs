@@ -0,0 +1,4 @@
+use rust_common::pattern::Variant;
+
+// 牌型檔格式見 rust_common::pattern, 張數由變體描述決定
+pub const VARIANT: Variant = Variant::Custom;
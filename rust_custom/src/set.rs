@@ -0,0 +1,47 @@
+use crate::variant::Tile;
+
+// 自訂變體只列舉門前和牌
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, PartialOrd, Ord)]
+pub enum MeldKind {
+    ConcealedChow, // 暗順
+    ConcealedPung, // 暗刻
+}
+
+#[allow(dead_code)]
+impl MeldKind {
+    pub fn is_chow(&self) -> bool {
+        matches!(self, Self::ConcealedChow)
+    }
+
+    pub fn is_pung(&self) -> bool {
+        !self.is_chow()
+    }
+}
+
+// 面子
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, PartialOrd, Ord)]
+pub struct Meld {
+    pub(crate) head: Tile,
+    pub(crate) kind: MeldKind,
+}
+
+#[allow(dead_code)]
+impl Meld {
+    pub fn new(head: Tile, kind: MeldKind) -> Self {
+        Self { head, kind }
+    }
+
+    pub fn tiles(&self) -> [Tile; 3] {
+        match self.kind {
+            MeldKind::ConcealedChow => [self.head, self.head + 1, self.head + 2],
+            MeldKind::ConcealedPung => [self.head; 3],
+        }
+    }
+}
+
+// 一雀頭加面子, 面子已排序
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub struct Set {
+    pub(crate) pair: Tile,
+    pub(crate) melds: Vec<Meld>,
+}
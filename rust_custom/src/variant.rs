@@ -0,0 +1,253 @@
+use std::{error::Error, fs::File, io::BufReader};
+
+use serde::{Deserialize, Serialize};
+
+// 牌以索引表示: 先字牌, 再依花色排數牌, 編碼為 'A' + 索引
+pub type Tile = u8;
+
+// 百搭, stands in for any tile in a pattern record
+pub const JOKER: u8 = b'*';
+
+// 編碼上限, 'A' + 索引 仍須是一個位元組
+const MAXTILEVARIANT: usize = (u8::MAX - b'A') as usize + 1;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HonorKind {
+    Wind,   // 風牌
+    Dragon, // 三元牌
+    Other,
+}
+
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub struct Honor {
+    pub name: String,
+    pub kind: HonorKind,
+}
+
+// 變體描述
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub struct CustomVariant {
+    pub name: String,
+    pub suits: u8,   // 花色數
+    pub numbers: u8, // 每色張數
+    #[serde(default)]
+    pub honors: Vec<Honor>,
+    #[serde(default = "default_copies")]
+    pub copies: u8, // 每種牌的張數
+    pub melds: u8, // 面子數
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub jokers: u8, // 牌山中的百搭張數
+}
+
+fn default_copies() -> u8 {
+    4
+}
+
+fn is_zero(n: &u8) -> bool {
+    *n == 0
+}
+
+#[allow(dead_code)]
+impl CustomVariant {
+    pub fn from_file(filename: &str) -> Result<Self, Box<dyn Error>> {
+        let reader = BufReader::new(File::open(filename)?);
+        let variant: Self = serde_json::from_reader(reader)?;
+        variant.validate()?;
+        Ok(variant)
+    }
+
+    pub fn validate(&self) -> Result<(), Box<dyn Error>> {
+        if self.name.is_empty()
+            || !self
+                .name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+        {
+            Err(format!(
+                "variant name {:?} must be ascii letters, digits, '-' or '_'",
+                self.name
+            ))?
+        }
+        if self.suits > 0 && self.numbers == 0 {
+            Err("suits need at least one number")?
+        }
+        if self.tiles() == 0 {
+            Err("variant has no tiles")?
+        }
+        if self.tiles() > MAXTILEVARIANT {
+            Err(format!(
+                "{} tile kinds exceed the encoding limit of {}",
+                self.tiles(),
+                MAXTILEVARIANT
+            ))?
+        }
+        if self.copies < 2 {
+            Err("at least two copies of a tile are needed for the pair")?
+        }
+        if self.melds == 0 {
+            Err("a hand needs at least one meld")?
+        }
+        if self.jokers as usize >= self.hainum() {
+            Err("a hand needs at least one tile besides the jokers")?
+        }
+        if self.hainum() > u8::MAX as usize {
+            Err(format!(
+                "{} tile hands do not fit the pattern header",
+                self.hainum()
+            ))?
+        }
+        Ok(())
+    }
+
+    // 牌種數
+    pub fn tiles(&self) -> usize {
+        self.honors.len() + self.suits as usize * self.numbers as usize
+    }
+
+    // 和牌張數
+    pub fn hainum(&self) -> usize {
+        self.melds as usize * 3 + 2
+    }
+
+    pub fn is_honor(&self, t: Tile) -> bool {
+        (t as usize) < self.honors.len()
+    }
+
+    pub fn is_wind(&self, t: Tile) -> bool {
+        self.honor_kind(t) == Some(HonorKind::Wind)
+    }
+
+    pub fn is_dragon(&self, t: Tile) -> bool {
+        self.honor_kind(t) == Some(HonorKind::Dragon)
+    }
+
+    pub fn honor_kind(&self, t: Tile) -> Option<HonorKind> {
+        self.honors.get(t as usize).map(|h| h.kind)
+    }
+
+    pub fn winds(&self) -> Vec<Tile> {
+        (0..self.honors.len() as u8)
+            .filter(|t| self.is_wind(*t))
+            .collect()
+    }
+
+    pub fn dragons(&self) -> Vec<Tile> {
+        (0..self.honors.len() as u8)
+            .filter(|t| self.is_dragon(*t))
+            .collect()
+    }
+
+    // 花色, None for honors
+    pub fn suit(&self, t: Tile) -> Option<u8> {
+        match self.is_honor(t) {
+            true => None,
+            false => Some(((t as usize - self.honors.len()) / self.numbers as usize) as u8),
+        }
+    }
+
+    // 1 based, 0 for honors
+    pub fn number(&self, t: Tile) -> u8 {
+        match self.is_honor(t) {
+            true => 0,
+            false => ((t as usize - self.honors.len()) % self.numbers as usize) as u8 + 1,
+        }
+    }
+
+    pub fn tile(&self, suit: u8, number: u8) -> Tile {
+        (self.honors.len() + suit as usize * self.numbers as usize + number as usize - 1) as Tile
+    }
+
+    pub fn is_terminal(&self, t: Tile) -> bool {
+        !self.is_honor(t) && (self.number(t) == 1 || self.number(t) == self.numbers)
+    }
+
+    pub fn is_simple(&self, t: Tile) -> bool {
+        !(self.is_honor(t) || self.is_terminal(t))
+    }
+
+    // 可為順子首張
+    pub fn is_chow_head(&self, t: Tile) -> bool {
+        !self.is_honor(t) && self.number(t) + 2 <= self.numbers
+    }
+
+    pub fn encode(&self, t: Tile) -> u8 {
+        t + b'A'
+    }
+
+    pub fn decode(&self, c: u8) -> Option<Tile> {
+        match c >= b'A' && ((c - b'A') as usize) < self.tiles() {
+            true => Some(c - b'A'),
+            false => None,
+        }
+    }
+
+    // 字牌用描述中的名稱, 數牌為 數字 + 花色字母
+    pub fn tile_name(&self, t: Tile) -> String {
+        match self.suit(t) {
+            None => self.honors[t as usize].name.clone(),
+            Some(s) => format!("{}{}", self.number(t), (b'a' + s) as char),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn three() -> CustomVariant {
+        serde_json::from_str(
+            r#"{
+                "name": "three",
+                "suits": 3,
+                "numbers": 6,
+                "honors": [
+                    {"name": "Moon", "kind": "dragon"},
+                    {"name": "Sun", "kind": "dragon"},
+                    {"name": "Red", "kind": "wind"},
+                    {"name": "Green", "kind": "wind"},
+                    {"name": "White", "kind": "wind"}
+                ],
+                "melds": 3
+            }"#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn tile_layout() {
+        let v = three();
+        assert!(v.validate().is_ok());
+        assert_eq!(v.copies, 4);
+        assert_eq!(v.tiles(), 23);
+        assert_eq!(v.hainum(), 11);
+        assert_eq!(v.dragons(), vec![0, 1]);
+        assert_eq!(v.winds(), vec![2, 3, 4]);
+
+        let t = v.tile(1, 6);
+        assert_eq!(t, 16);
+        assert_eq!((v.suit(t), v.number(t)), (Some(1), 6));
+        assert!(v.is_terminal(t));
+        assert!(!v.is_chow_head(t));
+        assert!(v.is_chow_head(v.tile(2, 4)));
+        assert_eq!(v.tile_name(t), "6b");
+        assert_eq!(v.tile_name(0), "Moon");
+        assert_eq!(v.decode(v.encode(22)), Some(22));
+        assert_eq!(v.decode(b'A' + 23), None);
+    }
+
+    #[test]
+    fn reject_bad_variants() {
+        let mut v = three();
+        v.copies = 1;
+        assert!(v.validate().is_err());
+
+        let mut v = three();
+        v.name = "../three".to_string();
+        assert!(v.validate().is_err());
+
+        let mut v = three();
+        v.suits = 40;
+        assert!(v.validate().is_err());
+    }
+}
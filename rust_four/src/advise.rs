@@ -0,0 +1,69 @@
+use std::error::Error;
+
+use advisor::advise;
+use context::WinContext;
+use tile::Tile;
+
+mod advisor;
+mod context;
+mod decompose;
+mod hand;
+mod handchecker;
+mod set;
+mod tile;
+
+// expected score of every discard from a complete hand
+// usage: advise <tiles> [wind] [--visible <tiles>] [--json]
+// tiles and wind in pattern file letters, A is 中, D is 東, H is 一索
+fn main() -> Result<(), Box<dyn Error>> {
+    let mut args: Vec<String> = std::env::args().skip(1).collect();
+    let json = args.iter().any(|a| a == "--json");
+    args.retain(|a| a != "--json");
+    let visible = match args.iter().position(|a| a == "--visible") {
+        Some(i) => {
+            let v = args.get(i + 1).ok_or("--visible needs tiles")?.clone();
+            args.drain(i..i + 2);
+            v
+        }
+        None => String::new(),
+    };
+    let hand = args
+        .first()
+        .ok_or("usage: advise <tiles> [wind] [--visible <tiles>] [--json]")?;
+    let wind = match args.get(1).and_then(|w| w.chars().next()) {
+        Some(w) => match Tile::try_from(w) {
+            Ok(t) if t.is_wind() => t,
+            _ => Err("wind must be one of D, E, F, G")?,
+        },
+        None => Tile::East,
+    };
+
+    let advice = advise(
+        hand.as_bytes(),
+        visible.as_bytes(),
+        wind,
+        &WinContext::default(),
+    )?;
+    if json {
+        println!("{}", serde_json::to_string_pretty(&advice)?);
+        return Ok(());
+    }
+
+    println!("{:>6}{:>6}{:>10}  聽牌", "打", "枚數", "期望分");
+    advice.iter().for_each(|d| {
+        let waits: Vec<String> = d
+            .waits
+            .iter()
+            .map(|w| format!("{:?}×{}={}", w.tile, w.live, w.score))
+            .collect();
+        println!(
+            "{:>7}{:>8}{:>13.5}  {}",
+            format!("{:?}", d.tile),
+            d.live,
+            d.expected,
+            waits.join(" ")
+        );
+    });
+
+    Ok(())
+}
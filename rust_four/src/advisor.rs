@@ -0,0 +1,156 @@
+use std::error::Error;
+
+use serde::Serialize;
+
+use crate::{
+    context::WinContext,
+    decompose::allsets,
+    set::HAINUM,
+    tile::{Tile, TILEVARIANT},
+};
+
+// 每種牌的張數
+const COPIES: usize = 4;
+
+// 聽牌: the tile, how many are still live and the best score it completes
+#[derive(Clone, PartialEq, Eq, Debug, Serialize)]
+pub struct Wait {
+    pub tile: Tile,
+    pub live: usize,
+    pub score: u16,
+}
+
+// 打牌建議 for one discard
+// expected is the score won on the next draw from the unseen tiles
+#[derive(Clone, PartialEq, Debug, Serialize)]
+pub struct Discard {
+    pub tile: Tile,
+    pub waits: Vec<Wait>,
+    pub live: usize,
+    pub expected: f64,
+}
+
+fn counts(raw: &[u8]) -> Result<[usize; TILEVARIANT], Box<dyn Error>> {
+    let mut counts = [0; TILEVARIANT];
+    for r in raw {
+        match Tile::try_from(*r as char) {
+            Ok(t) if !t.is_bonus() => counts[t as usize] += 1,
+            _ => Err(format!("{} is not a tile of the pattern", *r as char))?,
+        }
+    }
+    if counts.iter().any(|c| *c > COPIES) {
+        Err(format!("more than {} copies of a tile", COPIES))?
+    }
+    Ok(counts)
+}
+
+fn raw_of(counts: &[usize; TILEVARIANT]) -> Vec<u8> {
+    counts
+        .iter()
+        .enumerate()
+        .flat_map(|(i, c)| std::iter::repeat_n(b'A' + i as u8, *c))
+        .collect()
+}
+
+// the best score of a complete hand, None when it does not win
+fn best_score(counts: &[usize; TILEVARIANT], wind: Tile, context: &WinContext) -> Option<u16> {
+    allsets(&raw_of(counts))
+        .into_iter()
+        .map(|mut s| {
+            s.set_wind(wind);
+            s.hands(context).score()
+        })
+        .max()
+}
+
+// every discard of a complete hand, best expected score first
+// visible holds the tiles seen elsewhere on the table
+pub fn advise(
+    hand: &[u8],
+    visible: &[u8],
+    wind: Tile,
+    context: &WinContext,
+) -> Result<Vec<Discard>, Box<dyn Error>> {
+    if hand.len() != HAINUM {
+        Err(format!("expect {} tiles in hand", HAINUM))?
+    }
+    let held = counts(hand)?;
+    let mut seen = counts(visible)?;
+    if held.iter().zip(seen.iter()).any(|(h, s)| h + s > COPIES) {
+        Err(format!("more than {} copies of a tile", COPIES))?
+    }
+
+    let mut result: Vec<Discard> = (0..TILEVARIANT)
+        .filter(|i| held[*i] > 0)
+        .map(|d| {
+            let mut rest = held;
+            rest[d] -= 1;
+            seen[d] += 1;
+            let unseen = COPIES * TILEVARIANT - (HAINUM - 1) - seen.iter().sum::<usize>();
+            let waits: Vec<Wait> = (0..TILEVARIANT)
+                .filter(|t| rest[*t] < COPIES)
+                .filter_map(|t| {
+                    let mut complete = rest;
+                    complete[t] += 1;
+                    let score = best_score(&complete, wind, context)?;
+                    Some(Wait {
+                        tile: Tile::try_from(t).unwrap(),
+                        live: COPIES - rest[t] - seen[t],
+                        score,
+                    })
+                })
+                .collect();
+            seen[d] -= 1;
+
+            let live = waits.iter().map(|w| w.live).sum();
+            let points: usize = waits.iter().map(|w| w.live * w.score as usize).sum();
+            Discard {
+                tile: Tile::try_from(d).unwrap(),
+                waits,
+                live,
+                expected: points as f64 / unseen as f64,
+            }
+        })
+        .collect();
+    result.sort_by(|a, b| b.expected.total_cmp(&a.expected).then(b.live.cmp(&a.live)));
+
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn waits_of_each_discard() {
+        // 中中中 發發發 一二三索 四五六索 九索 北, discard 北 to wait on 九索
+        let advice = advise(b"AAABBBHIJKLMPG", b"", Tile::East, &WinContext::default()).unwrap();
+        let best = &advice[0];
+        assert_eq!(best.tile, Tile::North);
+        assert_eq!(best.waits.len(), 1);
+        assert_eq!(best.waits[0].tile, Tile::B9);
+        assert_eq!(best.waits[0].live, 3);
+        assert!(best.waits[0].score > 0);
+        // discarding 九索 waits on 北 instead
+        let single = advice.iter().find(|d| d.tile == Tile::B9).unwrap();
+        assert_eq!(single.waits[0].tile, Tile::North);
+        assert_eq!(single.live, 3);
+        // discards breaking a meld do not wait at all
+        assert!(advice
+            .iter()
+            .any(|d| d.waits.is_empty() && d.expected == 0.0));
+    }
+
+    #[test]
+    fn visible_tiles_are_not_live() {
+        let hand = b"AAABBBHIJKLMPG";
+        let context = WinContext::default();
+        let open = advise(hand, b"", Tile::East, &context).unwrap();
+        let seen = advise(hand, b"PP", Tile::East, &context).unwrap();
+        let wait = |a: &[Discard]| a.iter().find(|d| d.tile == Tile::North).unwrap().live;
+        assert_eq!(wait(&open), 3);
+        assert_eq!(wait(&seen), 1);
+        assert!(advise(hand, b"AA", Tile::East, &context).is_err());
+        assert!(advise(b"AAA", b"", Tile::East, &context).is_err());
+    }
+}
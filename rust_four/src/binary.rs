@@ -0,0 +1,200 @@
+use crate::{
+    handchecker::{HandChecker, HandList},
+    set::{Meld, MeldKind, Set, SetBuilder, SETNUM},
+    tile::Tile,
+};
+
+// 二進位格式
+// fixed-size little-endian encoding, tiles and kinds by enum order
+pub trait Binary: Sized {
+    const LEN: usize;
+
+    fn write_bytes(&self, buffer: &mut Vec<u8>);
+    fn read_bytes(buffer: &[u8]) -> Option<Self>;
+
+    #[allow(dead_code)]
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut buffer = Vec::with_capacity(Self::LEN);
+        self.write_bytes(&mut buffer);
+        buffer
+    }
+
+    #[allow(dead_code)]
+    fn from_bytes(buffer: &[u8]) -> Option<Self> {
+        match buffer.len() == Self::LEN {
+            true => Self::read_bytes(buffer),
+            false => None,
+        }
+    }
+}
+
+impl Binary for Tile {
+    const LEN: usize = 1;
+
+    fn write_bytes(&self, buffer: &mut Vec<u8>) {
+        buffer.push(*self as u8);
+    }
+
+    fn read_bytes(buffer: &[u8]) -> Option<Self> {
+        Tile::try_from(buffer.first()?.checked_add(b'A')? as char).ok()
+    }
+}
+
+impl Binary for MeldKind {
+    const LEN: usize = 1;
+
+    fn write_bytes(&self, buffer: &mut Vec<u8>) {
+        buffer.push(*self as u8);
+    }
+
+    fn read_bytes(buffer: &[u8]) -> Option<Self> {
+        match buffer.first()? {
+            0 => Some(MeldKind::RevealedChow),
+            1 => Some(MeldKind::ConcealedChow),
+            2 => Some(MeldKind::RevealedPung),
+            3 => Some(MeldKind::ConcealedPung),
+            4 => Some(MeldKind::RevealedKong),
+            5 => Some(MeldKind::ConcealedKong),
+            _ => None,
+        }
+    }
+}
+
+impl Binary for Meld {
+    const LEN: usize = Tile::LEN + MeldKind::LEN;
+
+    fn write_bytes(&self, buffer: &mut Vec<u8>) {
+        self.head.write_bytes(buffer);
+        self.kind.write_bytes(buffer);
+    }
+
+    fn read_bytes(buffer: &[u8]) -> Option<Self> {
+        Some(Meld::new(
+            Tile::read_bytes(buffer)?,
+            MeldKind::read_bytes(buffer.get(Tile::LEN..)?)?,
+        ))
+    }
+}
+
+impl Binary for Set {
+    const LEN: usize = Tile::LEN + Meld::LEN * SETNUM;
+
+    fn write_bytes(&self, buffer: &mut Vec<u8>) {
+        self.pair.write_bytes(buffer);
+        self.melds.iter().for_each(|m| m.write_bytes(buffer));
+    }
+
+    fn read_bytes(buffer: &[u8]) -> Option<Self> {
+        buffer
+            .get(Tile::LEN..Self::LEN)?
+            .chunks(Meld::LEN)
+            .try_fold(
+                SetBuilder::new().add_pair(Tile::read_bytes(buffer)?),
+                |sb, c| sb.add_meld(Meld::read_bytes(c)?).ok(),
+            )?
+            .build()
+            .ok()
+    }
+}
+
+// wind follows the pair, same layout as Set otherwise
+impl Binary for HandChecker {
+    const LEN: usize = Tile::LEN + Set::LEN;
+
+    fn write_bytes(&self, buffer: &mut Vec<u8>) {
+        self.pair().write_bytes(buffer);
+        self.wind().write_bytes(buffer);
+        self.melds().iter().for_each(|m| m.write_bytes(buffer));
+    }
+
+    fn read_bytes(buffer: &[u8]) -> Option<Self> {
+        let pair = buffer.get(..Tile::LEN)?;
+        let wind = Tile::read_bytes(buffer.get(Tile::LEN..)?)?;
+        let set = [pair, buffer.get(2 * Tile::LEN..Self::LEN)?].concat();
+        Some(Set::read_bytes(&set)?.to_handchecker(wind))
+    }
+}
+
+impl Binary for HandList {
+    const LEN: usize = 8;
+
+    fn write_bytes(&self, buffer: &mut Vec<u8>) {
+        buffer.extend_from_slice(&self.bits().to_le_bytes());
+    }
+
+    fn read_bytes(buffer: &[u8]) -> Option<Self> {
+        Some(HandList::from_bits(u64::from_le_bytes(
+            buffer.get(..Self::LEN)?.try_into().ok()?,
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::context::WinContext;
+    use crate::hand::Hand;
+
+    fn checker() -> HandChecker {
+        SetBuilder::new()
+            .add_pair(Tile::D9)
+            .add_meld(Meld::new(Tile::B1, MeldKind::ConcealedChow))
+            .unwrap()
+            .add_meld(Meld::new(Tile::C5, MeldKind::RevealedPung))
+            .unwrap()
+            .add_meld(Meld::new(Tile::Red, MeldKind::ConcealedKong))
+            .unwrap()
+            .add_meld(Meld::new(Tile::D2, MeldKind::RevealedChow))
+            .unwrap()
+            .build()
+            .unwrap()
+            .to_handchecker(Tile::South)
+    }
+
+    #[test]
+    fn binary_round_trip() {
+        let checker = checker();
+        let bytes = checker.to_bytes();
+        assert_eq!(bytes.len(), HandChecker::LEN);
+        assert_eq!(HandChecker::from_bytes(&bytes), Some(checker.clone()));
+
+        let hands = checker.hands(&WinContext::default());
+        assert_eq!(HandList::from_bytes(&hands.to_bytes()), Some(hands));
+        assert_eq!(Tile::from_bytes(&[33]), Some(Tile::D9));
+        assert_eq!(Tile::from_bytes(&[34]), Some(Tile::F1));
+        assert_eq!(Tile::from_bytes(&[42]), None);
+        assert_eq!(HandChecker::from_bytes(&bytes[1..]), None);
+    }
+
+    #[test]
+    fn json_round_trip() {
+        let checker = checker();
+        let json = serde_json::to_string(&checker).unwrap();
+        assert_eq!(serde_json::from_str::<HandChecker>(&json).unwrap(), checker);
+
+        let hands = checker.hands(&WinContext::default());
+        let json = serde_json::to_string(&hands).unwrap();
+        assert_eq!(serde_json::from_str::<HandList>(&json).unwrap(), hands);
+    }
+
+    #[test]
+    fn json_is_stable() {
+        let meld = Meld::new(Tile::B1, MeldKind::ConcealedChow);
+        assert_eq!(
+            serde_json::to_string(&meld).unwrap(),
+            r#"{"head":"B1","kind":"ConcealedChow"}"#
+        );
+
+        let mut hands = HandList::new();
+        hands.set(Hand::AllChows as usize, true);
+        hands.set(Hand::HalfFlush as usize, true);
+        assert_eq!(
+            serde_json::to_string(&hands).unwrap(),
+            r#"["AllChows","HalfFlush"]"#
+        );
+
+        // incomplete sets are rejected
+        assert!(serde_json::from_str::<Set>(r#"{"pair":"Red","melds":[]}"#).is_err());
+    }
+}
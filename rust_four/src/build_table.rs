@@ -0,0 +1,56 @@
+use std::time::Instant;
+
+use rust_common::pattern::read_patterns;
+
+use crate::{
+    pattern::{HAINUM, VARIANT},
+    table::LookupTable,
+};
+
+mod binary;
+mod context;
+mod decompose;
+mod hand;
+mod handchecker;
+mod pattern;
+mod set;
+mod table;
+mod tile;
+
+fn main() -> std::io::Result<()> {
+    let time_read = Instant::now();
+    let (header, raw_hai_sets) = read_patterns::<HAINUM>("patterns_general_four.dat", VARIANT)?;
+    println!(
+        "time read and chunks in {:.2} s",
+        time_read.elapsed().as_secs_f32()
+    );
+
+    println!("build lookup table");
+    let start = Instant::now();
+    let table = LookupTable::build(&raw_hai_sets, header.checksum);
+    println!(
+        "time build {} entries in {:.2} s",
+        table.len(),
+        start.elapsed().as_secs_f32()
+    );
+
+    let filename = "table_four.dat";
+    table.write(filename)?;
+
+    let table = LookupTable::read(filename)?;
+    let start = Instant::now();
+    let total: u64 = raw_hai_sets
+        .iter()
+        .map(|r| table.score(r).expect("pattern missing from table") as u64)
+        .sum();
+    let elapsed = start.elapsed().as_secs_f64();
+    println!(
+        "scored {} hands in {:.2} ms ({:.0} hands/ms), average best score {:.5}",
+        raw_hai_sets.len(),
+        elapsed * 1000.0,
+        raw_hai_sets.len() as f64 / (elapsed * 1000.0),
+        total as f64 / raw_hai_sets.len() as f64
+    );
+
+    Ok(())
+}
@@ -0,0 +1,175 @@
+use std::{
+    collections::HashMap,
+    error::Error,
+    fs::File,
+    io::{BufWriter, Write},
+    time::Instant,
+};
+
+use rust_common::pattern::read_patterns;
+
+use crate::{
+    comparison::{best_payout, compare, ScoreChanges},
+    context::{ContextWeights, WinContext},
+    decompose::{allsets, comb_available},
+    hand::{Hand, HANDVARIANT},
+    handchecker::HandList,
+    pattern::{HAINUM, VARIANT},
+    report::{classes, take_option},
+    scoring::ScoringModel,
+};
+
+mod comparison;
+mod context;
+mod cooccurrence;
+mod decompose;
+mod distribution;
+mod hand;
+mod handchecker;
+mod pattern;
+mod report;
+mod scoring;
+mod set;
+mod tile;
+
+use tile::available;
+
+// two rule sets side by side, in one pass over the patterns
+// usage: compare a.json b.json [--context context.json] [--minimum k]
+//        [--visible tiles] [--changes changed.csv]
+// a rule file replaces some scores like rescore's --rules, `current` is the
+// table of `Hand::score`
+// patterns whose best score changed go to changed.csv, 花牌 and 狀況役 left out
+fn main() -> Result<(), Box<dyn Error>> {
+    let mut args: Vec<String> = std::env::args().skip(1).collect();
+    let weights = match take_option(&mut args, "--context")? {
+        Some(f) => ContextWeights::from_file(&f)?,
+        None => ContextWeights::default(),
+    };
+    let minimum: u16 = match take_option(&mut args, "--minimum")? {
+        Some(k) => k.parse()?,
+        None => 0,
+    };
+    let available = available(
+        take_option(&mut args, "--visible")?
+            .unwrap_or_default()
+            .as_bytes(),
+    )?;
+    let changes_file =
+        take_option(&mut args, "--changes")?.unwrap_or("changed_patterns_four.csv".to_string());
+    if args.len() != 2 {
+        Err("usage: compare a.json b.json [--context context.json] [--minimum k] [--visible tiles] [--changes changed.csv]")?
+    }
+    let model = |name: &str| -> Result<ScoringModel, Box<dyn Error>> {
+        let model = match name {
+            "current" => ScoringModel::additive(),
+            f => ScoringModel::additive().with_values_file(f)?,
+        };
+        Ok(ScoringModel { minimum, ..model })
+    };
+    let (a, b) = (model(&args[0])?, model(&args[1])?);
+
+    let time_read = Instant::now();
+    let (header, raw_hai_sets) = read_patterns::<HAINUM>("patterns_general_four.dat", VARIANT)?;
+    assert_eq!(raw_hai_sets.len() as u64, header.records);
+    println!(
+        "time read and chunks in {:.2} s",
+        time_read.elapsed().as_secs_f32()
+    );
+
+    let start = Instant::now();
+    let mut hands: HashMap<HandList, (u64, u64)> = HashMap::new();
+    let mut changes = ScoreChanges::new();
+    let mut writer = BufWriter::new(File::create(&changes_file)?);
+    writeln!(writer, "pattern,a,b,delta,combinations")?;
+    for raw_hai in raw_hai_sets.into_iter() {
+        let combinations = comb_available(&raw_hai, &available);
+        if combinations == 0 {
+            continue;
+        }
+        let mut sets = allsets(&raw_hai);
+        sets.dedup();
+        let lists: Vec<HandList> = sets
+            .into_iter()
+            .map(|s| s.hands(&WinContext::default()))
+            .collect();
+
+        let (pa, pb) = (best_payout(&lists, &a), best_payout(&lists, &b));
+        if changes.add(pa, pb, combinations) {
+            writeln!(
+                writer,
+                "{},{},{},{},{}",
+                String::from_utf8_lossy(&raw_hai),
+                pa,
+                pb,
+                pb as i64 - pa as i64,
+                combinations
+            )?;
+        }
+        lists.into_iter().for_each(|l| {
+            let v = hands.entry(l).or_insert((0, 0));
+            v.0 += 1;
+            v.1 += combinations;
+        });
+    }
+    writer.flush()?;
+    println!(
+        "time produce patterns in {:.2} s",
+        start.elapsed().as_secs_f32()
+    );
+
+    let (sa, sb) = compare(&hands, &classes(&weights), &a, &b);
+
+    println!();
+    println!("A: {}, B: {}", a.name, b.name);
+    println!(
+        "{:24}{:>10}{:>10}{:>10}{:>10}{:>10}{:>10}{:>10}{:>10}{:>10}",
+        "役種", "頻率 A", "頻率 B", "Δ", "平均 A", "平均 B", "Δ", "佔比 A", "佔比 B", "Δ"
+    );
+    (0..HANDVARIANT)
+        .map(|i| Hand::try_from(i).unwrap())
+        .for_each(|h| {
+            println!(
+                "{:24}{:>12.5}{:>12.5}{:>+10.5}{:>12.4}{:>12.4}{:>+10.4}{:>12.5}{:>12.5}{:>+10.5}",
+                format!("{:?}", h),
+                sa.frequency(h),
+                sb.frequency(h),
+                sb.frequency(h) - sa.frequency(h),
+                sa.average(h),
+                sb.average(h),
+                sb.average(h) - sa.average(h),
+                sa.share(h),
+                sb.share(h),
+                sb.share(h) - sa.share(h)
+            );
+        });
+    println!(
+        "平均分數 A {:.5} B {:.5} Δ {:+.5}",
+        sa.mean(),
+        sb.mean(),
+        sb.mean() - sa.mean()
+    );
+
+    // 分數變動的和牌形, best payout of B against A
+    println!();
+    let (patterns, combinations) = changes.changed();
+    let (all_patterns, all_combinations) = changes
+        .by_delta
+        .values()
+        .fold((0, 0), |acc, v| (acc.0 + v.0, acc.1 + v.1));
+    println!(
+        "{} of {} patterns changed, {:.5} of the combinations, listed in {}",
+        patterns,
+        all_patterns,
+        combinations as f64 / all_combinations as f64,
+        changes_file
+    );
+    println!("{:>6}{:>12}{:>24}", "Δ", "和牌形", "組合數");
+    changes
+        .by_delta
+        .iter()
+        .filter(|(d, _)| **d != 0)
+        .for_each(|(d, (p, c))| println!("{:>+6}{:>15}{:>27}", d, p, c));
+
+    Ok(())
+}
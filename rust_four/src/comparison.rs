@@ -0,0 +1,207 @@
+use std::collections::{BTreeMap, HashMap};
+
+use crate::{
+    hand::{Hand, HANDVARIANT},
+    handchecker::HandList,
+    scoring::ScoringModel,
+};
+
+// 規則統計, per hand over the combinations reaching 起和
+#[derive(Clone, PartialEq, Debug)]
+pub struct RuleStats {
+    wins: f64,
+    points: f64,
+    combination: [f64; HANDVARIANT],
+    scored: [f64; HANDVARIANT], // payout of the combinations holding the hand
+    contribution: [f64; HANDVARIANT], // payout shared by the fan of each hand
+}
+
+#[allow(dead_code)]
+impl RuleStats {
+    pub fn new() -> Self {
+        Self {
+            wins: 0.0,
+            points: 0.0,
+            combination: [0.0; HANDVARIANT],
+            scored: [0.0; HANDVARIANT],
+            contribution: [0.0; HANDVARIANT],
+        }
+    }
+
+    pub fn add(&mut self, list: &HandList, weight: f64, model: &ScoringModel) {
+        let payout = match model.payout(list) {
+            Some(p) => p as f64,
+            None => return,
+        };
+        let fan = model.fan(list) as f64;
+        self.wins += weight;
+        self.points += weight * payout;
+        (0..HANDVARIANT).filter(|i| list[*i]).for_each(|i| {
+            self.combination[i] += weight;
+            self.scored[i] += weight * payout;
+            if fan > 0.0 {
+                self.contribution[i] += weight * payout * model.value(list, i) as f64 / fan;
+            }
+        });
+    }
+
+    // share of the winning combinations holding the hand
+    pub fn frequency(&self, h: Hand) -> f64 {
+        match self.wins > 0.0 {
+            true => self.combination[h as usize] / self.wins,
+            false => 0.0,
+        }
+    }
+
+    // average payout of the combinations holding the hand
+    pub fn average(&self, h: Hand) -> f64 {
+        match self.combination[h as usize] > 0.0 {
+            true => self.scored[h as usize] / self.combination[h as usize],
+            false => 0.0,
+        }
+    }
+
+    // share of all points paid
+    pub fn share(&self, h: Hand) -> f64 {
+        match self.points > 0.0 {
+            true => self.contribution[h as usize] / self.points,
+            false => 0.0,
+        }
+    }
+
+    pub fn mean(&self) -> f64 {
+        match self.wins > 0.0 {
+            true => self.points / self.wins,
+            false => 0.0,
+        }
+    }
+}
+
+// both rule sets over agari's HandList to (patterns, combinations),
+// every list weighted by the 花牌 and 和牌狀況 classes
+pub fn compare(
+    hands: &HashMap<HandList, (u64, u64)>,
+    classes: &[(HandList, f64)],
+    a: &ScoringModel,
+    b: &ScoringModel,
+) -> (RuleStats, RuleStats) {
+    let mut stats = (RuleStats::new(), RuleStats::new());
+    hands.iter().for_each(|(handlist, (_, combination))| {
+        classes.iter().for_each(|(c, weight)| {
+            let list = handlist.with_bonus(c);
+            let weight = *combination as f64 * weight;
+            stats.0.add(&list, weight, a);
+            stats.1.add(&list, weight, b);
+        });
+    });
+    stats
+}
+
+// 高點法, the best payout among the decompositions of a pattern, 0 below 起和
+pub fn best_payout(lists: &[HandList], model: &ScoringModel) -> u64 {
+    lists
+        .iter()
+        .filter_map(|l| model.payout(l))
+        .max()
+        .unwrap_or(0)
+}
+
+// patterns whose score changed, by delta of B against A
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct ScoreChanges {
+    pub by_delta: BTreeMap<i64, (u64, u64)>, // patterns, combinations
+}
+
+#[allow(dead_code)]
+impl ScoreChanges {
+    pub fn new() -> Self {
+        Self {
+            by_delta: BTreeMap::new(),
+        }
+    }
+
+    // true when the score changed
+    pub fn add(&mut self, a: u64, b: u64, combination: u64) -> bool {
+        let delta = b as i64 - a as i64;
+        let v = self.by_delta.entry(delta).or_insert((0, 0));
+        v.0 += 1;
+        v.1 += combination;
+        delta != 0
+    }
+
+    // (patterns, combinations) whose score changed
+    pub fn changed(&self) -> (u64, u64) {
+        self.by_delta
+            .iter()
+            .filter(|(d, _)| **d != 0)
+            .fold((0, 0), |acc, (_, v)| (acc.0 + v.0, acc.1 + v.1))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn list(hands: &[Hand]) -> HandList {
+        let mut list = HandList::new();
+        hands.iter().for_each(|h| list.set(*h as usize, true));
+        list
+    }
+
+    fn models() -> (ScoringModel, ScoringModel) {
+        let a = ScoringModel::additive();
+        let mut values = std::collections::HashMap::new();
+        values.insert(Hand::AllPungs, a.values[Hand::AllPungs as usize] + 2);
+        values.insert(Hand::HalfFlush, 0);
+        let b = a.with_values("b", &values);
+        (a, b)
+    }
+
+    #[test]
+    fn stats_under_two_rule_sets() {
+        let (a, b) = models();
+        let mut hands = HashMap::new();
+        hands.insert(list(&[Hand::AllPungs, Hand::HalfFlush]), (1, 1));
+        hands.insert(list(&[Hand::AllChows]), (1, 3));
+        let (sa, sb) = compare(&hands, &[(HandList::new(), 1.0)], &a, &b);
+
+        assert_eq!(sa.frequency(Hand::AllPungs), 0.25);
+        assert_eq!(sb.frequency(Hand::AllPungs), 0.25);
+        let pungs = a.values[Hand::AllPungs as usize] as f64;
+        let flush = a.values[Hand::HalfFlush as usize] as f64;
+        let chows = a.values[Hand::AllChows as usize] as f64;
+        assert_eq!(sa.average(Hand::AllPungs), pungs + flush);
+        assert_eq!(sb.average(Hand::AllPungs), pungs + 2.0);
+        assert_eq!(sb.share(Hand::HalfFlush), 0.0);
+        assert_eq!(
+            sa.share(Hand::AllChows),
+            3.0 * chows / (pungs + flush + 3.0 * chows)
+        );
+        let total: f64 = (0..HANDVARIANT)
+            .map(|i| sb.share(Hand::try_from(i).unwrap()))
+            .sum();
+        assert!((total - 1.0).abs() < 1e-12);
+        assert_eq!(sa.mean(), (pungs + flush + 3.0 * chows) / 4.0);
+    }
+
+    #[test]
+    fn changed_patterns() {
+        let (a, b) = models();
+        let lists = [
+            list(&[Hand::AllPungs, Hand::HalfFlush]),
+            list(&[Hand::AllPungs]),
+        ];
+        let (pa, pb) = (best_payout(&lists, &a), best_payout(&lists, &b));
+        assert_eq!(pa, a.payout(&lists[0]).unwrap());
+        assert_eq!(pb, b.payout(&lists[0]).unwrap());
+
+        let mut changes = ScoreChanges::new();
+        assert!(changes.add(pa, pb, 10));
+        assert!(!changes.add(3, 3, 5));
+        assert!(changes.add(40, 2, 1));
+        assert_eq!(changes.changed(), (2, 11));
+        assert_eq!(changes.by_delta[&0], (1, 5));
+        assert_eq!(changes.by_delta[&(pb as i64 - pa as i64)], (1, 10));
+        assert_eq!(changes.by_delta[&-38], (1, 1));
+    }
+}
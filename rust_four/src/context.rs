@@ -0,0 +1,271 @@
+use std::{error::Error, fs::File, io::BufReader};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{hand::Hand, handchecker::HandList};
+
+// 和牌狀況, what the tiles alone cannot tell
+#[derive(Clone, Copy, Default, PartialEq, Eq, Hash, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WinContext {
+    pub self_draw: bool,        // 自摸
+    pub last_tile: bool,        // 最後一張, 海底撈月 or 河底撈魚
+    pub kong_replacement: bool, // 嶺上開花
+    pub robbing_kong: bool,     // 搶槓
+    pub first_draw: bool,       // 第一巡自摸, 天和 or 地和
+    pub dealer: bool,           // 莊家
+    pub riichi: bool,           // 立直
+    pub ippatsu: bool,          // 一發
+}
+
+#[allow(dead_code)]
+impl WinContext {
+    pub fn validate(&self) -> Result<(), Box<dyn Error>> {
+        if (self.kong_replacement || self.first_draw) && !self.self_draw {
+            Err("嶺上開花, 天和 and 地和 need a self-drawn tile")?
+        }
+        if self.robbing_kong && self.self_draw {
+            Err("搶槓 takes another player's tile")?
+        }
+        if self.first_draw && self.kong_replacement {
+            Err("天和 and 地和 come before any kong")?
+        }
+        if self.last_tile && (self.kong_replacement || self.robbing_kong || self.first_draw) {
+            Err("the last tile is not a replacement, a robbed kong or the first draw")?
+        }
+        if self.ippatsu && !self.riichi {
+            Err("一發 needs 立直")?
+        }
+        if self.riichi && self.first_draw {
+            Err("天和 and 地和 come before any 立直")?
+        }
+        Ok(())
+    }
+
+    // 狀況役, 門前限定 ones are dropped by `HandChecker::hands` for open hands
+    pub fn hands(&self) -> HandList {
+        let mut checker = HandList::new();
+        checker.set(Hand::SelfDrawn as usize, self.self_draw);
+        checker.set(
+            Hand::LastTileDraw as usize,
+            self.last_tile && self.self_draw,
+        );
+        checker.set(
+            Hand::LastTileClaim as usize,
+            self.last_tile && !self.self_draw,
+        );
+        checker.set(Hand::OutWithReplacementTile as usize, self.kong_replacement);
+        checker.set(Hand::RobbingTheKong as usize, self.robbing_kong);
+        checker.set(
+            Hand::BlessingOfHeaven as usize,
+            self.first_draw && self.dealer,
+        );
+        checker.set(
+            Hand::BlessingOfEarth as usize,
+            self.first_draw && !self.dealer,
+        );
+        checker.set(Hand::Riichi as usize, self.riichi);
+        checker.set(Hand::Ippatsu as usize, self.ippatsu);
+
+        checker
+    }
+
+    // every combination of the flags, valid or not
+    fn all() -> impl Iterator<Item = Self> {
+        (0..1u16 << 8).map(|b| Self {
+            self_draw: b & 1 != 0,
+            last_tile: b & 1 << 1 != 0,
+            kong_replacement: b & 1 << 2 != 0,
+            robbing_kong: b & 1 << 3 != 0,
+            first_draw: b & 1 << 4 != 0,
+            dealer: b & 1 << 5 != 0,
+            riichi: b & 1 << 6 != 0,
+            ippatsu: b & 1 << 7 != 0,
+        })
+    }
+}
+
+// 和牌狀況機率, for statistics runs
+// conditional ones are noted, the last tile is drawn or claimed only when
+// no other circumstance applies
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ContextWeights {
+    pub self_draw: f64,
+    pub last_tile: f64,
+    pub kong_replacement: f64, // given 自摸
+    pub robbing_kong: f64,     // given 榮和
+    pub first_draw: f64,       // given 自摸
+    pub dealer: f64,
+    pub riichi: f64,
+    pub ippatsu: f64, // given 立直
+}
+
+// 不計狀況役
+impl Default for ContextWeights {
+    fn default() -> Self {
+        Self {
+            self_draw: 0.0,
+            last_tile: 0.0,
+            kong_replacement: 0.0,
+            robbing_kong: 0.0,
+            first_draw: 0.0,
+            dealer: 0.25,
+            riichi: 0.0,
+            ippatsu: 0.0,
+        }
+    }
+}
+
+#[allow(dead_code)]
+impl ContextWeights {
+    pub fn from_file(filename: &str) -> Result<Self, Box<dyn Error>> {
+        let reader = BufReader::new(File::open(filename)?);
+        let weights: Self = serde_json::from_reader(reader)?;
+        weights.validate()?;
+        Ok(weights)
+    }
+
+    pub fn validate(&self) -> Result<(), Box<dyn Error>> {
+        let probabilities = [
+            self.self_draw,
+            self.last_tile,
+            self.kong_replacement,
+            self.robbing_kong,
+            self.first_draw,
+            self.dealer,
+            self.riichi,
+            self.ippatsu,
+        ];
+        if !probabilities.iter().all(|p| (0.0..=1.0).contains(p)) {
+            Err("probabilities must be between 0 and 1")?
+        }
+        Ok(())
+    }
+
+    // 0 for contexts that cannot happen
+    pub fn probability(&self, c: &WinContext) -> f64 {
+        if c.validate().is_err() {
+            return 0.0;
+        }
+        let p = |b: bool, q: f64| match b {
+            true => q,
+            false => 1.0 - q,
+        };
+        let last = p(c.last_tile, self.last_tile);
+        let how = match (
+            c.self_draw,
+            c.first_draw,
+            c.kong_replacement,
+            c.robbing_kong,
+        ) {
+            (true, true, _, _) => self.first_draw,
+            (true, false, true, _) => (1.0 - self.first_draw) * self.kong_replacement,
+            (true, false, false, _) => {
+                (1.0 - self.first_draw) * (1.0 - self.kong_replacement) * last
+            }
+            (false, _, _, true) => self.robbing_kong,
+            (false, _, _, false) => (1.0 - self.robbing_kong) * last,
+        };
+        let riichi = match (c.first_draw, c.riichi) {
+            (true, _) => 1.0,
+            (false, true) => self.riichi * p(c.ippatsu, self.ippatsu),
+            (false, false) => 1.0 - self.riichi,
+        };
+
+        p(c.self_draw, self.self_draw) * p(c.dealer, self.dealer) * how * riichi
+    }
+
+    // 狀況役 and their probability, contexts awarding the same hands merged
+    pub fn classes(&self) -> Vec<(HandList, f64)> {
+        let mut classes: Vec<(HandList, f64)> = Vec::new();
+        WinContext::all()
+            .map(|c| (c.hands(), self.probability(&c)))
+            .filter(|(_, w)| *w > 0.0)
+            .for_each(
+                |(list, w)| match classes.iter_mut().find(|(l, _)| *l == list) {
+                    Some((_, v)) => *v += w,
+                    None => classes.push((list, w)),
+                },
+            );
+        classes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn context_hands() {
+        let c = WinContext {
+            self_draw: true,
+            last_tile: true,
+            riichi: true,
+            ippatsu: true,
+            ..Default::default()
+        };
+        assert!(c.validate().is_ok());
+        let list = c.hands();
+        assert!(list[Hand::SelfDrawn as usize]);
+        assert!(list[Hand::LastTileDraw as usize]);
+        assert!(!list[Hand::LastTileClaim as usize]);
+        assert!(list[Hand::Riichi as usize] && list[Hand::Ippatsu as usize]);
+
+        let c = WinContext {
+            first_draw: true,
+            ..c
+        };
+        assert!(c.validate().is_err());
+        let c = WinContext {
+            self_draw: true,
+            first_draw: true,
+            dealer: true,
+            ..Default::default()
+        };
+        assert!(c.hands()[Hand::BlessingOfHeaven as usize]);
+        assert!(!c.hands()[Hand::BlessingOfEarth as usize]);
+        assert!(
+            WinContext {
+                robbing_kong: true,
+                ..Default::default()
+            }
+            .hands()[Hand::RobbingTheKong as usize]
+        );
+    }
+
+    #[test]
+    fn probabilities_sum_to_one() {
+        let weights = ContextWeights {
+            self_draw: 0.4,
+            last_tile: 0.02,
+            kong_replacement: 0.03,
+            robbing_kong: 0.01,
+            first_draw: 0.001,
+            dealer: 0.25,
+            riichi: 0.5,
+            ippatsu: 0.2,
+        };
+        assert!(weights.validate().is_ok());
+        let total: f64 = WinContext::all().map(|c| weights.probability(&c)).sum();
+        assert!((total - 1.0).abs() < 1e-12);
+        let total: f64 = weights.classes().iter().map(|(_, w)| w).sum();
+        assert!((total - 1.0).abs() < 1e-12);
+
+        // 天和 is the dealer's share of first draws
+        let heavenly: f64 = weights
+            .classes()
+            .iter()
+            .filter(|(l, _)| l[Hand::BlessingOfHeaven as usize])
+            .map(|(_, w)| w)
+            .sum();
+        assert!((heavenly - 0.4 * 0.001 * 0.25).abs() < 1e-15);
+    }
+
+    #[test]
+    fn default_weights_award_nothing() {
+        let classes = ContextWeights::default().classes();
+        assert_eq!(classes.len(), 1);
+        assert_eq!(classes[0], (HandList::new(), 1.0));
+    }
+}
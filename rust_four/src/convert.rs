@@ -0,0 +1,28 @@
+use pattern::{HAINUM, VARIANT};
+use rust_common::pattern::{read_legacy, write_patterns};
+use tile::Tile;
+
+mod pattern;
+mod tile;
+
+// convert legacy headerless pattern file into the versioned format
+// usage: convert <legacy.dat> <output.dat> [generator]
+fn main() -> std::io::Result<()> {
+    let args: Vec<String> = std::env::args().collect();
+    if args.len() < 3 {
+        eprintln!("usage: {} <legacy.dat> <output.dat> [generator]", args[0]);
+        std::process::exit(1);
+    }
+    let generator = match args.get(3) {
+        Some(g) => g.clone(),
+        None => format!("legacy {}", args[1]),
+    };
+
+    let data = read_legacy(&args[1], HAINUM, |c| {
+        Tile::try_from(c as char).is_ok_and(|t| !t.is_bonus())
+    })?;
+    write_patterns(&args[2], &generator, VARIANT, HAINUM, &data)?;
+    println!("converted {} records into {}", data.len() / HAINUM, args[2]);
+
+    Ok(())
+}
@@ -0,0 +1,156 @@
+use std::{
+    collections::HashMap,
+    fs::File,
+    io::{BufWriter, Write},
+};
+
+use crate::{
+    hand::{Hand, HANDVARIANT},
+    handchecker::HandList,
+};
+
+// 役種同時出現
+// joint[a][b] is the weight of lists holding both, joint[a][a] the marginal
+#[derive(Clone, PartialEq, Debug)]
+pub struct Cooccurrence {
+    total: f64,
+    joint: Vec<Vec<f64>>,
+}
+
+#[allow(dead_code)]
+impl Cooccurrence {
+    pub fn new() -> Self {
+        Self {
+            total: 0.0,
+            joint: vec![vec![0.0; HANDVARIANT]; HANDVARIANT],
+        }
+    }
+
+    pub fn add(&mut self, list: &HandList, weight: f64) {
+        self.total += weight;
+        let hands: Vec<usize> = (0..HANDVARIANT).filter(|i| list[*i]).collect();
+        hands
+            .iter()
+            .for_each(|a| hands.iter().for_each(|b| self.joint[*a][*b] += weight));
+    }
+
+    pub fn total(&self) -> f64 {
+        self.total
+    }
+
+    // P(A and B)
+    pub fn joint(&self, a: Hand, b: Hand) -> f64 {
+        self.joint[a as usize][b as usize] / self.total
+    }
+
+    // P(B | A), None when A never happens
+    pub fn conditional(&self, a: Hand, b: Hand) -> Option<f64> {
+        match self.joint[a as usize][a as usize] {
+            w if w > 0.0 => Some(self.joint[a as usize][b as usize] / w),
+            _ => None,
+        }
+    }
+
+    // pairs of different hands that happen together, most dependent first
+    pub fn pairs(&self) -> Vec<(Hand, Hand, f64, f64)> {
+        let mut pairs: Vec<(Hand, Hand, f64, f64)> = (0..HANDVARIANT)
+            .flat_map(|a| (0..HANDVARIANT).map(move |b| (a, b)))
+            .filter(|(a, b)| a != b && self.joint[*a][*b] > 0.0)
+            .map(|(a, b)| {
+                let (a, b) = (Hand::try_from(a).unwrap(), Hand::try_from(b).unwrap());
+                (a, b, self.joint(a, b), self.conditional(a, b).unwrap())
+            })
+            .collect();
+        pairs.sort_by(|x, y| y.3.total_cmp(&x.3).then(y.2.total_cmp(&x.2)));
+        pairs
+    }
+
+    // one line per ordered pair: A, B, P(A and B), P(B | A)
+    pub fn write_csv(&self, filename: &str) -> std::io::Result<()> {
+        let mut writer = BufWriter::new(File::create(filename)?);
+        writeln!(writer, "a,b,joint,conditional")?;
+        for a in 0..HANDVARIANT {
+            let a = Hand::try_from(a).unwrap();
+            for b in 0..HANDVARIANT {
+                let b = Hand::try_from(b).unwrap();
+                writeln!(
+                    writer,
+                    "{:?},{:?},{},{}",
+                    a,
+                    b,
+                    self.joint(a, b),
+                    self.conditional(a, b).unwrap_or(0.0)
+                )?;
+            }
+        }
+        writer.flush()
+    }
+}
+
+// 完整役種組合, most frequent first by the chosen weight
+#[allow(dead_code)]
+pub fn top_combinations<K: Fn(&(f64, f64)) -> f64>(
+    combinations: &HashMap<HandList, (f64, f64)>,
+    n: usize,
+    key: K,
+) -> Vec<(HandList, (f64, f64))> {
+    let mut top: Vec<(HandList, (f64, f64))> =
+        combinations.iter().map(|(l, w)| (l.clone(), *w)).collect();
+    top.sort_by(|x, y| key(&y.1).total_cmp(&key(&x.1)));
+    top.truncate(n);
+    top
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn list(hands: &[Hand]) -> HandList {
+        let mut list = HandList::new();
+        hands.iter().for_each(|h| list.set(*h as usize, true));
+        list
+    }
+
+    #[test]
+    fn joint_and_conditional() {
+        let mut c = Cooccurrence::new();
+        c.add(&list(&[Hand::AllPungs, Hand::ThreeConcealedPungs]), 1.0);
+        c.add(&list(&[Hand::AllPungs]), 3.0);
+        c.add(&list(&[Hand::AllChows]), 4.0);
+
+        assert_eq!(c.total(), 8.0);
+        assert_eq!(c.joint(Hand::AllPungs, Hand::AllPungs), 0.5);
+        assert_eq!(c.joint(Hand::AllPungs, Hand::ThreeConcealedPungs), 0.125);
+        assert_eq!(
+            c.conditional(Hand::ThreeConcealedPungs, Hand::AllPungs),
+            Some(1.0)
+        );
+        assert_eq!(
+            c.conditional(Hand::AllPungs, Hand::ThreeConcealedPungs),
+            Some(0.25)
+        );
+        assert_eq!(c.conditional(Hand::FullFlush, Hand::AllPungs), None);
+
+        let pairs = c.pairs();
+        assert_eq!(pairs.len(), 2);
+        assert_eq!(
+            (pairs[0].0, pairs[0].1),
+            (Hand::ThreeConcealedPungs, Hand::AllPungs)
+        );
+    }
+
+    #[test]
+    fn most_frequent_combinations() {
+        let mut combinations = HashMap::new();
+        combinations.insert(list(&[Hand::AllChows]), (10.0, 1.0));
+        combinations.insert(list(&[Hand::AllPungs]), (1.0, 5.0));
+        combinations.insert(list(&[Hand::HalfFlush]), (3.0, 3.0));
+
+        let top = top_combinations(&combinations, 2, |w| w.1);
+        assert_eq!(top[0].0, list(&[Hand::AllPungs]));
+        assert_eq!(top[1].0, list(&[Hand::HalfFlush]));
+        let top = top_combinations(&combinations, 5, |w| w.0);
+        assert_eq!(top.len(), 3);
+        assert_eq!(top[0].0, list(&[Hand::AllChows]));
+    }
+}
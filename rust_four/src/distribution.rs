@@ -0,0 +1,112 @@
+use std::collections::BTreeMap;
+
+// 分數分布, weight of each score or payout
+#[derive(Clone, PartialEq, Debug)]
+pub struct ScoreDistribution {
+    weights: BTreeMap<u64, f64>,
+}
+
+#[allow(dead_code)]
+impl ScoreDistribution {
+    pub fn new() -> Self {
+        Self {
+            weights: BTreeMap::new(),
+        }
+    }
+
+    pub fn add(&mut self, score: u64, weight: f64) {
+        *self.weights.entry(score).or_insert(0.0) += weight;
+    }
+
+    pub fn total(&self) -> f64 {
+        self.weights.values().sum()
+    }
+
+    // 起胡: scores below the minimum do not win, the rest renormalised
+    pub fn with_minimum(&self, minimum: u64) -> Self {
+        let mut weights = self.weights.clone();
+        weights.range_mut(..minimum).for_each(|(_, w)| *w = 0.0);
+        Self { weights }
+    }
+
+    // share of each score seen
+    pub fn histogram(&self) -> Vec<(u64, f64)> {
+        let total = self.total();
+        self.weights.iter().map(|(s, w)| (*s, w / total)).collect()
+    }
+
+    pub fn mean(&self) -> f64 {
+        self.histogram().iter().map(|(s, p)| *s as f64 * p).sum()
+    }
+
+    // P(score ≥ k)
+    pub fn at_least(&self, k: u64) -> f64 {
+        self.histogram()
+            .iter()
+            .filter(|(s, _)| *s >= k)
+            .map(|(_, p)| p)
+            .sum()
+    }
+
+    // the lowest score reaching the share p, None for an empty distribution
+    pub fn percentile(&self, p: f64) -> Option<u64> {
+        let total = self.total();
+        if total <= 0.0 {
+            return None;
+        }
+        let mut cumulative = 0.0;
+        self.weights
+            .iter()
+            .find(|(_, w)| {
+                cumulative += *w;
+                cumulative >= p * total
+            })
+            .or(self.weights.last_key_value())
+            .map(|(s, _)| *s)
+    }
+
+    pub fn median(&self) -> Option<u64> {
+        self.percentile(0.5)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::hand::HANDMAXSCORE;
+
+    fn distribution() -> ScoreDistribution {
+        let mut d = ScoreDistribution::new();
+        d.add(0, 2.0);
+        d.add(1, 3.0);
+        d.add(4, 4.0);
+        // payouts are kept past the fan cap
+        d.add(HANDMAXSCORE as u64 + 5, 1.0);
+        d
+    }
+
+    #[test]
+    fn statistics() {
+        let d = distribution();
+        assert_eq!(d.total(), 10.0);
+        assert!((d.mean() - (3.0 + 16.0 + HANDMAXSCORE as f64 + 5.0) / 10.0).abs() < 1e-12);
+        assert_eq!(d.median(), Some(1));
+        assert_eq!(d.percentile(0.2), Some(0));
+        assert_eq!(d.percentile(0.9), Some(4));
+        assert_eq!(d.percentile(1.0), Some(HANDMAXSCORE as u64 + 5));
+        assert!((d.at_least(1) - 0.8).abs() < 1e-12);
+        assert!((d.at_least(5) - 0.1).abs() < 1e-12);
+        assert_eq!(ScoreDistribution::new().median(), None);
+    }
+
+    #[test]
+    fn minimum_renormalises() {
+        let d = distribution().with_minimum(1);
+        assert_eq!(d.total(), 8.0);
+        assert_eq!(d.histogram()[0], (0, 0.0));
+        assert_eq!(d.histogram()[1], (1, 3.0 / 8.0));
+        assert_eq!(d.median(), Some(4));
+        assert_eq!(d.at_least(0), 1.0);
+    }
+}
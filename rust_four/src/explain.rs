@@ -0,0 +1,596 @@
+use std::fmt;
+
+use arrayvec::ArrayVec;
+use itertools::Itertools;
+use serde::Serialize;
+
+use crate::{
+    context::WinContext,
+    hand::{Hand, HANDVARIANT},
+    handchecker::{HandChecker, HandList},
+    set::{Meld, SETNUM},
+    tile::{Tile, TileColor},
+};
+
+// 役的理由
+// each hand the decomposition satisfies, with the pair and melds behind it,
+// and the rule that removed it when it was not awarded
+#[derive(Clone, PartialEq, Eq, Debug, Serialize)]
+pub struct Reason {
+    pub hand: Hand,
+    pub pair: Option<Tile>,
+    pub melds: ArrayVec<Meld, SETNUM>,
+    pub suppressed: Option<Suppression>,
+    pub open: bool, // 副露, scored by open_score
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize)]
+pub enum Suppression {
+    LimitHand(Hand),  // 絕對滿貫 clears every other hand
+    Superseded(Hand), // higher hand returned early in the same check
+    ClosedOnly,       // found, but 門前限定 and not scored with a revealed meld
+    NotSatisfied,     // the checker did not award it, no rule to name
+}
+
+#[derive(Clone, PartialEq, Eq, Debug, Serialize)]
+pub struct Explanation {
+    pub hands: HandList,
+    pub score: u16,
+    pub reasons: Vec<Reason>,
+}
+
+#[allow(dead_code)]
+impl Explanation {
+    pub fn awarded(&self) -> impl Iterator<Item = &Reason> {
+        self.reasons.iter().filter(|r| r.suppressed.is_none())
+    }
+
+    pub fn suppressed(&self) -> impl Iterator<Item = &Reason> {
+        self.reasons.iter().filter(|r| r.suppressed.is_some())
+    }
+
+    pub fn reason(&self, hand: Hand) -> Option<&Reason> {
+        self.reasons.iter().find(|r| r.hand == hand)
+    }
+}
+
+impl HandChecker {
+    pub fn explain(&self) -> Explanation {
+        let checked = self.checked();
+        let unlimited = checked.hands();
+        let hands = self.hands(&WinContext::default());
+        let limit = (0..HANDVARIANT)
+            .map(|i| Hand::try_from(i).unwrap())
+            .find(|h| h.is_limit() && hands[*h as usize]);
+
+        // the checker's own record names the rule, what it does not explain
+        // is listed as not satisfied
+        let reasons = (0..HANDVARIANT)
+            .map(|i| Hand::try_from(i).unwrap())
+            .filter_map(|hand| {
+                let evidence = self.evidence(hand);
+                let suppressed = match (hands[hand as usize], unlimited[hand as usize]) {
+                    (true, _) if hands.is_open() && hand.open_score().is_none() => {
+                        Some(Suppression::ClosedOnly)
+                    }
+                    (true, _) => None,
+                    _ if evidence.is_none() => return None,
+                    (false, true) => Some(
+                        limit
+                            .map(Suppression::LimitHand)
+                            .unwrap_or(Suppression::NotSatisfied),
+                    ),
+                    (false, false) => Some(
+                        checked
+                            .skipped_by(hand)
+                            .map(Suppression::Superseded)
+                            .unwrap_or(Suppression::NotSatisfied),
+                    ),
+                };
+                let (pair, melds) = evidence.unwrap_or_default();
+                Some(Reason {
+                    hand,
+                    pair: match pair {
+                        true => Some(self.pair()),
+                        false => None,
+                    },
+                    melds,
+                    suppressed,
+                    open: hands.is_open(),
+                })
+            })
+            .collect();
+
+        Explanation {
+            score: hands.score(),
+            hands,
+            reasons,
+        }
+    }
+
+    // hand condition on its own, ignoring early returns and limit hands
+    // pair flag and the melds the condition looks at
+    fn evidence(&self, hand: Hand) -> Option<(bool, ArrayVec<Meld, SETNUM>)> {
+        let melds = self.melds();
+        let pair = self.pair();
+        let all = |f: &dyn Fn(&Meld) -> bool| match melds.iter().all(f) {
+            true => Some(melds.clone()),
+            false => None,
+        };
+        let some = |f: &dyn Fn(&Meld) -> bool| {
+            let found: ArrayVec<Meld, SETNUM> = melds.iter().filter(|m| f(m)).cloned().collect();
+            match found.is_empty() {
+                true => None,
+                false => Some(found),
+            }
+        };
+        let exactly = |n: usize, f: &dyn Fn(&Meld) -> bool| {
+            let found = some(f)?;
+            match found.len() == n {
+                true => Some(found),
+                false => None,
+            }
+        };
+        let find = |n: usize, f: &dyn Fn(&[Meld]) -> bool| -> Option<ArrayVec<Meld, SETNUM>> {
+            melds
+                .iter()
+                .cloned()
+                .combinations(n)
+                .map(|mut c| {
+                    c.sort();
+                    c
+                })
+                .find(|c| f(c))
+                .map(|c| c.into_iter().collect())
+        };
+        let with_pair = |found: Option<ArrayVec<Meld, SETNUM>>, cond: bool| match cond {
+            true => Some((true, found?)),
+            false => None,
+        };
+
+        match hand {
+            Hand::AllChows => Some((false, all(&|m| m.kind.is_chow())?)),
+            Hand::AllPungs => Some((false, all(&|m| m.kind.is_pung())?)),
+            Hand::RedPung => Some((false, some(&|m| m.head == Tile::Red)?)),
+            Hand::GreenPung => Some((false, some(&|m| m.head == Tile::Green)?)),
+            Hand::WhitePung => Some((false, some(&|m| m.head == Tile::White)?)),
+            Hand::WindPung => Some((false, some(&|m| m.head == self.wind())?)),
+            Hand::PureStraight => Some((
+                false,
+                find(3, &|c| {
+                    c.iter().all(|m| m.kind.is_chow())
+                        && c.iter().all(|m| m.head.is_same_color(c[0].head))
+                        && c.iter().map(|m| m.head.number()).eq([1, 4, 7])
+                })?,
+            )),
+            Hand::LittleThreeDragons => {
+                with_pair(exactly(2, &|m| m.head.is_dragon()), pair.is_dragon())
+            }
+            Hand::BigThreeDragons => Some((false, exactly(3, &|m| m.head.is_dragon())?)),
+            Hand::LittleFourWinds => with_pair(exactly(3, &|m| m.head.is_wind()), pair.is_wind()),
+            Hand::BigFourWinds => Some((false, exactly(4, &|m| m.head.is_wind())?)),
+            Hand::AllSimples => with_pair(all(&simple), pair.is_simple()),
+            Hand::OutsideHands => with_pair(all(&|m| !simple(m)), !pair.is_simple()),
+            Hand::TerminalsInAllSets => with_pair(all(&terminal), pair.is_terminal()),
+            Hand::AllTerminalsAndHonors => with_pair(
+                all(&|m| m.kind.is_pung() && !m.head.is_simple()),
+                !pair.is_simple(),
+            ),
+            Hand::AllTerminals => with_pair(
+                all(&|m| m.kind.is_pung() && m.head.is_terminal()),
+                pair.is_terminal(),
+            ),
+            Hand::HalfFlush => {
+                let color = melds
+                    .iter()
+                    .map(|m| m.head)
+                    .chain([pair])
+                    .find(|t| !t.is_honor())?;
+                with_pair(
+                    all(&|m| m.head.is_honor() || m.head.is_same_color(color)),
+                    pair.is_honor() || pair.is_same_color(color),
+                )
+            }
+            Hand::FullFlush => with_pair(all(&|m| m.head.is_same_color(pair)), !pair.is_honor()),
+            Hand::AllHonors => with_pair(all(&|m| m.head.is_honor()), pair.is_honor()),
+            Hand::TwoConcealedPungs => Some((false, find(2, &|c| c.iter().all(concealed_pung))?)),
+            Hand::ThreeConcealedPungs => Some((false, find(3, &|c| c.iter().all(concealed_pung))?)),
+            Hand::FourConcealedPungs => Some((false, all(&concealed_pung)?)),
+            Hand::PureDoubleChow => Some((false, find(2, &same_chows)?)),
+            Hand::TwicePureDoubleChow => Some((
+                false,
+                find(SETNUM, &|c| {
+                    c.iter().all(|m| m.kind.is_chow())
+                        && c[0].head == c[1].head
+                        && c[2].head == c[3].head
+                })?,
+            )),
+            Hand::PureTripleChow => Some((false, find(3, &same_chows)?)),
+            Hand::QuadrupleChow => Some((false, find(SETNUM, &same_chows)?)),
+            Hand::MixedTripleChow => Some((
+                false,
+                find(3, &|c| c.iter().all(|m| m.kind.is_chow()) && mixed(c))?,
+            )),
+            Hand::TriplePung => Some((
+                false,
+                find(3, &|c| c.iter().all(|m| m.kind.is_pung()) && mixed(c))?,
+            )),
+            Hand::PureShiftedPungs => Some((false, find(3, &shifted_pungs)?)),
+            Hand::FourPureShiftedPungs => Some((false, find(SETNUM, &shifted_pungs)?)),
+            // nothing else checked
+            Hand::NoPoint => match self.unlimited_hands().any() {
+                true => None,
+                false => Some((false, ArrayVec::new())),
+            },
+            // 花牌 are set aside before decomposing, 無百搭 depends on the raw tiles
+            Hand::SeatFlower
+            | Hand::SeatSeason
+            | Hand::FourFlowers
+            | Hand::FourSeasons
+            | Hand::EightFlowers
+            | Hand::NoJoker => None,
+            // 和牌狀況 come from the WinContext
+            Hand::SelfDrawn
+            | Hand::LastTileDraw
+            | Hand::LastTileClaim
+            | Hand::OutWithReplacementTile
+            | Hand::RobbingTheKong
+            | Hand::BlessingOfHeaven
+            | Hand::BlessingOfEarth
+            | Hand::Riichi
+            | Hand::Ippatsu => None,
+        }
+    }
+}
+
+// chows by their first tile, 1 and 7 chows touch a terminal
+fn simple(m: &Meld) -> bool {
+    match m.kind.is_chow() {
+        true => !matches!(m.head.number(), 1 | 7),
+        false => m.head.is_simple(),
+    }
+}
+
+fn terminal(m: &Meld) -> bool {
+    match m.kind.is_chow() {
+        true => matches!(m.head.number(), 1 | 7),
+        false => m.head.is_terminal(),
+    }
+}
+
+fn concealed_pung(m: &Meld) -> bool {
+    m.kind.is_pung() && m.kind.is_concealed()
+}
+
+fn same_chows(c: &[Meld]) -> bool {
+    c.iter().all(|m| m.kind.is_chow() && m.head == c[0].head)
+}
+
+fn mixed(c: &[Meld]) -> bool {
+    let mut colors: ArrayVec<TileColor, 3> = c.iter().map(|m| m.head.color()).collect();
+    colors.sort_by_key(|c| *c as u8);
+    colors.as_slice() == [TileColor::Bamboo, TileColor::Character, TileColor::Dot]
+        && c.iter().all(|m| m.head.number() == c[0].head.number())
+}
+
+// sorted by head
+fn shifted_pungs(c: &[Meld]) -> bool {
+    c.iter().all(|m| m.kind.is_pung()) && c.windows(2).all(|w| w[0].head.is_ascending(w[1].head))
+}
+
+impl fmt::Display for Reason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {:?}", self.hand.name(), self.hand)?;
+        match self.suppressed {
+            None if self.open => write!(f, " +{}", self.hand.open_score().unwrap_or(0))?,
+            None => write!(f, " +{}", self.hand.score())?,
+            Some(Suppression::LimitHand(h)) => write!(f, " cleared by {}", h.name())?,
+            Some(Suppression::Superseded(h)) => write!(f, " superseded by {}", h.name())?,
+            Some(Suppression::ClosedOnly) => write!(f, " closed only")?,
+            Some(Suppression::NotSatisfied) => write!(f, " not satisfied")?,
+        }
+        let tiles = self.pair.iter().map(|p| format!("{:?} pair", p)).chain(
+            self.melds
+                .iter()
+                .map(|m| format!("{:?} {:?}", m.head, m.kind)),
+        );
+        write!(f, ": {}", tiles.collect::<Vec<String>>().join(", "))
+    }
+}
+
+impl fmt::Display for Explanation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "score {}", self.score)?;
+        self.reasons.iter().try_for_each(|r| writeln!(f, "  {}", r))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::{
+        set::{MeldKind, SetBuilder},
+        tile::TILEVARIANT,
+    };
+
+    fn checker(pair: Tile, melds: &[(Tile, MeldKind)]) -> HandChecker {
+        melds
+            .iter()
+            .fold(SetBuilder::new().add_pair(pair), |sb, (t, k)| {
+                sb.add_meld(Meld::new(*t, *k)).unwrap()
+            })
+            .build()
+            .unwrap()
+            .to_handchecker(Tile::East)
+    }
+
+    // every concealed decomposition over bamboo and the 1, 2, 9 pungs, 123 and 789
+    // chows of characters and dots, each hand's condition shows up in some suit
+    // pairs only matter by suit and kind, one of each will do
+    #[test]
+    fn every_awarded_hand_has_evidence() {
+        let tile = |i: usize| Tile::try_from((i as u8 + b'A') as char).unwrap();
+        let chow = |t: Tile| [t, tile(t as usize + 1), tile(t as usize + 2)];
+        let mut melds: Vec<(Tile, MeldKind, [Tile; 3])> = Vec::new();
+        (0..TILEVARIANT).map(tile).for_each(|t| {
+            let number = t.number();
+            if t.is_honor() || t.color() == TileColor::Bamboo || matches!(number, 1 | 2 | 9) {
+                melds.push((t, MeldKind::ConcealedPung, [t; 3]));
+            }
+            let chows = match t.color() {
+                TileColor::Bamboo => number <= 7,
+                _ => matches!(number, 1 | 7),
+            };
+            if !t.is_honor() && chows {
+                melds.push((t, MeldKind::ConcealedChow, chow(t)));
+            }
+        });
+
+        let pairs = [
+            Tile::B1,
+            Tile::B5,
+            Tile::C9,
+            Tile::D2,
+            Tile::Red,
+            Tile::East,
+            Tile::North,
+        ];
+        let mut seen = HandList::new();
+        melds
+            .iter()
+            .combinations_with_replacement(SETNUM)
+            .cartesian_product(pairs.iter())
+            .filter(|(ms, p)| {
+                let mut counts = [0; TILEVARIANT];
+                ms.iter()
+                    .flat_map(|m| m.2)
+                    .chain([**p, **p])
+                    .for_each(|t| counts[t as usize] += 1);
+                counts.iter().all(|c| *c <= 4)
+            })
+            .for_each(|(ms, p)| {
+                let melds: Vec<(Tile, MeldKind)> = ms.iter().map(|m| (m.0, m.1)).collect();
+                let c = checker(*p, &melds);
+                let hands = c.hands(&WinContext::default());
+                (0..HANDVARIANT)
+                    .filter(|i| hands[*i])
+                    .map(|i| Hand::try_from(i).unwrap())
+                    .for_each(|h| {
+                        assert!(c.evidence(h).is_some(), "{:?} in {:?}", h, c);
+                        seen.set(h as usize, true);
+                    });
+            });
+        // every hand found from the tiles came up
+        (0..HANDVARIANT)
+            .map(|i| Hand::try_from(i).unwrap())
+            .filter(|h| !h.is_bonus() && !h.is_situational() && *h != Hand::NoJoker)
+            .for_each(|h| assert!(seen[h as usize], "{:?} never awarded", h));
+    }
+
+    #[test]
+    fn awarded_hands_have_melds() {
+        let c = checker(
+            Tile::B5,
+            &[
+                (Tile::B1, MeldKind::ConcealedChow),
+                (Tile::B4, MeldKind::RevealedChow),
+                (Tile::B7, MeldKind::ConcealedChow),
+                (Tile::Red, MeldKind::ConcealedPung),
+            ],
+        );
+        let e = c.explain();
+        assert_eq!(e.hands, c.hands(&WinContext::default()));
+        assert_eq!(
+            e.awarded().map(|r| r.hand).collect::<Vec<Hand>>(),
+            (0..HANDVARIANT)
+                .filter(|i| e.hands[*i])
+                .map(|i| Hand::try_from(i).unwrap())
+                .collect::<Vec<Hand>>()
+        );
+
+        let straight = e.reason(Hand::PureStraight).unwrap();
+        assert_eq!(straight.pair, None);
+        assert_eq!(
+            straight.melds.iter().map(|m| m.head).collect::<Vec<Tile>>(),
+            vec![Tile::B1, Tile::B4, Tile::B7]
+        );
+        let flush = e.reason(Hand::HalfFlush).unwrap();
+        assert_eq!(flush.pair, Some(Tile::B5));
+        assert_eq!(flush.melds.len(), SETNUM);
+        assert_eq!(
+            e.reason(Hand::RedPung).unwrap().melds.as_slice(),
+            [Meld::new(Tile::Red, MeldKind::ConcealedPung)]
+        );
+    }
+
+    #[test]
+    fn early_return_is_named() {
+        // 三暗刻 returns before 二暗刻
+        let c = checker(
+            Tile::B2,
+            &[
+                (Tile::C3, MeldKind::ConcealedPung),
+                (Tile::C5, MeldKind::ConcealedPung),
+                (Tile::D7, MeldKind::ConcealedPung),
+                (Tile::B6, MeldKind::RevealedChow),
+            ],
+        );
+        let e = c.explain();
+        assert!(e.hands[Hand::ThreeConcealedPungs as usize]);
+        let two = e.reason(Hand::TwoConcealedPungs).unwrap();
+        assert_eq!(
+            two.suppressed,
+            Some(Suppression::Superseded(Hand::ThreeConcealedPungs))
+        );
+        assert_eq!(two.melds.len(), 2);
+
+        // 混一色 superseded by 清一色
+        let c = checker(
+            Tile::C2,
+            &[
+                (Tile::C1, MeldKind::ConcealedChow),
+                (Tile::C1, MeldKind::ConcealedChow),
+                (Tile::C5, MeldKind::RevealedPung),
+                (Tile::C7, MeldKind::ConcealedChow),
+            ],
+        );
+        let e = c.explain();
+        assert_eq!(
+            e.reason(Hand::HalfFlush).unwrap().suppressed,
+            Some(Suppression::Superseded(Hand::FullFlush))
+        );
+        // 一般高 is 門前限定, 清一色 drops a step
+        assert_eq!(
+            e.reason(Hand::PureDoubleChow).unwrap().suppressed,
+            Some(Suppression::ClosedOnly)
+        );
+        assert!(e.hands.is_open());
+        assert_eq!(e.score, Hand::FullFlush.open_score().unwrap());
+    }
+
+    #[test]
+    fn limit_hand_clears() {
+        // 字一色 clears 對對和 and 役牌
+        let c = checker(
+            Tile::North,
+            &[
+                (Tile::Red, MeldKind::ConcealedPung),
+                (Tile::Green, MeldKind::RevealedPung),
+                (Tile::East, MeldKind::ConcealedPung),
+                (Tile::West, MeldKind::RevealedPung),
+            ],
+        );
+        let e = c.explain();
+        assert_eq!(e.score, Hand::AllHonors.score());
+        assert_eq!(
+            e.awarded().map(|r| r.hand).collect::<Vec<Hand>>(),
+            vec![Hand::AllHonors]
+        );
+        [
+            Hand::AllPungs,
+            Hand::RedPung,
+            Hand::WindPung,
+            Hand::AllTerminalsAndHonors,
+        ]
+        .iter()
+        .for_each(|h| {
+            assert_eq!(
+                e.reason(*h).unwrap().suppressed,
+                Some(Suppression::LimitHand(Hand::AllHonors))
+            )
+        });
+        // 混一色 never ran, 字一色 returned first
+        assert_eq!(e.reason(Hand::HalfFlush), None);
+    }
+
+    #[test]
+    fn reasons_follow_checker() {
+        // 順子 and 刻子 over every color, terminals and honors
+        let chows = [Tile::B1, Tile::B2, Tile::B4, Tile::B7, Tile::C1, Tile::D1];
+        let pungs = [
+            Tile::B1,
+            Tile::B2,
+            Tile::B3,
+            Tile::B4,
+            Tile::B9,
+            Tile::C1,
+            Tile::C2,
+            Tile::D1,
+            Tile::East,
+            Tile::South,
+            Tile::West,
+            Tile::North,
+            Tile::Red,
+            Tile::Green,
+            Tile::White,
+        ];
+        let melds: Vec<(Tile, MeldKind)> = chows
+            .iter()
+            .map(|t| (*t, MeldKind::ConcealedChow))
+            .chain(pungs.iter().map(|t| (*t, MeldKind::ConcealedPung)))
+            .collect();
+        let reveal = |(t, k): (Tile, MeldKind)| match k.is_chow() {
+            true => (t, MeldKind::RevealedChow),
+            false => (t, MeldKind::RevealedPung),
+        };
+
+        let mut checked = 0;
+        [
+            Tile::B1,
+            Tile::B5,
+            Tile::B9,
+            Tile::C1,
+            Tile::East,
+            Tile::Red,
+        ]
+        .iter()
+        .for_each(|pair| {
+            melds
+                .iter()
+                .cloned()
+                .combinations_with_replacement(SETNUM)
+                // no more than four of a tile
+                .filter(|c| {
+                    let mut count = [0; TILEVARIANT];
+                    count[*pair as usize] += 2;
+                    c.iter().for_each(|(t, k)| match k.is_chow() {
+                        true => (0..3).for_each(|i| count[*t as usize + i] += 1),
+                        false => count[*t as usize] += 3,
+                    });
+                    count.iter().all(|n| *n <= 4)
+                })
+                .flat_map(|c| {
+                    let mut revealed = c.clone();
+                    revealed[0] = reveal(revealed[0]);
+                    [c, revealed]
+                })
+                .filter_map(|c| {
+                    c.iter()
+                        .try_fold(SetBuilder::new().add_pair(*pair), |sb, (t, k)| {
+                            sb.add_meld(Meld::new(*t, *k)).ok()
+                        })?
+                        .build()
+                        .ok()
+                })
+                .for_each(|s| {
+                    let c = s.to_handchecker(Tile::East);
+                    let e = c.explain();
+                    checked += 1;
+                    assert_eq!(e.hands, c.hands(&WinContext::default()));
+                    e.reasons.iter().for_each(|r| {
+                        assert_ne!(r.suppressed, Some(Suppression::NotSatisfied), "{}", e);
+                        assert!(
+                            r.suppressed.is_some()
+                                || r.hand == Hand::NoPoint
+                                || c.evidence(r.hand).is_some(),
+                            "{:?} without evidence in {}",
+                            r.hand,
+                            e
+                        );
+                    });
+                });
+        });
+        assert!(checked > 10000);
+    }
+}
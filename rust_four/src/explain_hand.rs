@@ -0,0 +1,69 @@
+use decompose::allsets;
+use joker::JOKER;
+use set::HAINUM;
+use tile::Tile;
+
+mod context;
+mod decompose;
+mod explain;
+mod hand;
+mod handchecker;
+mod joker;
+mod set;
+mod tile;
+
+// explain every decomposition of a winning hand
+// usage: explain-hand <tiles> [wind] [--json]
+// tiles and wind in pattern file letters, A is 中, D is 東, H is 一索
+// * is 百搭, only the best scoring substitution is explained
+fn main() {
+    let args: Vec<String> = std::env::args().collect();
+    let json = args.iter().any(|a| a == "--json");
+    let args: Vec<&String> = args.iter().filter(|a| *a != "--json").collect();
+    if args.len() < 2 {
+        eprintln!("usage: {} <tiles> [wind] [--json]", args[0]);
+        std::process::exit(1);
+    }
+
+    let raw = args[1].as_bytes();
+    if raw.len() != HAINUM
+        || raw
+            .iter()
+            .any(|r| *r != JOKER && Tile::try_from(*r as char).is_err())
+    {
+        eprintln!("expect {} tiles, A to {} or *", HAINUM, (b'A' + 33) as char);
+        std::process::exit(1);
+    }
+    let wind = match args.get(2).and_then(|w| w.chars().next()) {
+        Some(w) => match Tile::try_from(w) {
+            Ok(t) if t.is_wind() => t,
+            _ => {
+                eprintln!("wind must be one of D, E, F, G");
+                std::process::exit(1);
+            }
+        },
+        None => Tile::East,
+    };
+
+    let mut sets = match joker::jokers(raw) {
+        0 => allsets(raw),
+        _ => joker::best(raw, wind).into_iter().map(|(s, _)| s).collect(),
+    };
+    sets.dedup();
+    if sets.is_empty() {
+        eprintln!("not a winning hand");
+        std::process::exit(1);
+    }
+
+    let explanations: Vec<_> = sets
+        .into_iter()
+        .map(|mut s| {
+            s.set_wind(wind);
+            s.explain()
+        })
+        .collect();
+    match json {
+        true => println!("{}", serde_json::to_string_pretty(&explanations).unwrap()),
+        false => explanations.iter().for_each(|e| print!("{}", e)),
+    }
+}
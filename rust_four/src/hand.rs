@@ -1,41 +1,63 @@
-pub const HANDVARIANT: usize = 31;
+use serde::{Deserialize, Serialize};
+
+pub const HANDVARIANT: usize = 46;
+#[allow(dead_code)]
 pub const HANDMAXSCORE: u16 = 32;
+// bump when the hands found for a decomposition change
+#[allow(dead_code)]
+pub const CHECKERVERSION: u16 = 2;
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Serialize, Deserialize)]
 pub enum Hand {
-    AllChows,              // 平和
-    RedPung,               // 役牌中
-    GreenPung,             // 役牌發
-    WhitePung,             // 役牌白
-    WindPung,              // 役牌自風(東)
-    PureStraight,          // 一氣通貫
-    AllPungs,              // 對對和
-    LittleThreeDragons,    // 小三元
-    BigThreeDragons,       // 大三元
-    LittleFourWinds,       // 小四喜
-    AllSimples,            // 斷幺九
-    OutsideHands,          // 混全帶幺九
-    TerminalsInAllSets,    // 清全帶幺九
-    AllTerminalsAndHonors, // 混老頭
-    HalfFlush,             // 混一色
-    FullFlush,             // 清一色
-    AllHonors,             // 字一色
-    TwoConcealedPungs,     // 二暗刻
-    ThreeConcealedPungs,   // 三暗刻
-    FourConcealedPungs,    // 四暗刻
-    PureDoubleChow,        // 一般高
-    TwicePureDoubleChow,   // 二般高
-    PureTripleChow,        // 三同順
-    MixedTripleChow,       // 三色同順
-    TriplePung,            // 三色同刻
-    PureShiftedPungs,      // 三連刻
-    FourPureShiftedPungs,  // 四連刻
-    BigFourWinds,          // 大四喜
-    AllTerminals,          // 清老頭
-    QuadrupleChow,         // 四同順
-    NoPoint,               // 無役
+    AllChows,               // 平和
+    RedPung,                // 役牌中
+    GreenPung,              // 役牌發
+    WhitePung,              // 役牌白
+    WindPung,               // 役牌自風(東)
+    PureStraight,           // 一氣通貫
+    AllPungs,               // 對對和
+    LittleThreeDragons,     // 小三元
+    BigThreeDragons,        // 大三元
+    LittleFourWinds,        // 小四喜
+    AllSimples,             // 斷幺九
+    OutsideHands,           // 混全帶幺九
+    TerminalsInAllSets,     // 清全帶幺九
+    AllTerminalsAndHonors,  // 混老頭
+    HalfFlush,              // 混一色
+    FullFlush,              // 清一色
+    AllHonors,              // 字一色
+    TwoConcealedPungs,      // 二暗刻
+    ThreeConcealedPungs,    // 三暗刻
+    FourConcealedPungs,     // 四暗刻
+    PureDoubleChow,         // 一般高
+    TwicePureDoubleChow,    // 二般高
+    PureTripleChow,         // 三同順
+    MixedTripleChow,        // 三色同順
+    TriplePung,             // 三色同刻
+    PureShiftedPungs,       // 三連刻
+    FourPureShiftedPungs,   // 四連刻
+    BigFourWinds,           // 大四喜
+    AllTerminals,           // 清老頭
+    QuadrupleChow,          // 四同順
+    SeatFlower,             // 正花
+    SeatSeason,             // 正季
+    FourFlowers,            // 四花, 梅蘭菊竹
+    FourSeasons,            // 四季, 春夏秋冬
+    EightFlowers,           // 八仙過海
+    SelfDrawn,              // 自摸
+    LastTileDraw,           // 海底撈月
+    LastTileClaim,          // 河底撈魚
+    OutWithReplacementTile, // 嶺上開花
+    RobbingTheKong,         // 搶槓
+    BlessingOfHeaven,       // 天和
+    BlessingOfEarth,        // 地和
+    Riichi,                 // 立直
+    Ippatsu,                // 一發
+    NoJoker,                // 無百搭
+    NoPoint,                // 無役
 }
 
+#[allow(dead_code)]
 impl Hand {
     pub fn score(&self) -> u16 {
         match self {
@@ -70,11 +92,99 @@ impl Hand {
             &Self::BigFourWinds => HANDMAXSCORE,
             &Self::AllTerminals => HANDMAXSCORE,
             &Self::QuadrupleChow => HANDMAXSCORE,
+            // 花牌
+            &Self::SeatFlower => 1,
+            &Self::SeatSeason => 1,
+            &Self::FourFlowers => 2,
+            &Self::FourSeasons => 2,
+            &Self::EightFlowers => 8,
+            // 和牌狀況
+            &Self::SelfDrawn => 1,
+            &Self::LastTileDraw => 2,
+            &Self::LastTileClaim => 2,
+            &Self::OutWithReplacementTile => 2,
+            &Self::RobbingTheKong => 2,
+            &Self::BlessingOfHeaven => HANDMAXSCORE,
+            &Self::BlessingOfEarth => HANDMAXSCORE,
+            &Self::Riichi => 1,
+            &Self::Ippatsu => 1,
+            // 百搭
+            &Self::NoJoker => 2,
             // 無役
             &Self::NoPoint => 0,
         }
     }
 
+    // 絕對滿貫
+    pub fn is_limit(&self) -> bool {
+        matches!(
+            self,
+            Self::AllHonors
+                | Self::BigFourWinds
+                | Self::AllTerminals
+                | Self::QuadrupleChow
+                | Self::BlessingOfHeaven
+                | Self::BlessingOfEarth
+        )
+    }
+
+    // 花牌, awarded apart from the decomposition
+    pub fn is_bonus(&self) -> bool {
+        matches!(
+            self,
+            Self::SeatFlower
+                | Self::SeatSeason
+                | Self::FourFlowers
+                | Self::FourSeasons
+                | Self::EightFlowers
+        )
+    }
+
+    // 和牌狀況, given by `WinContext` rather than the tiles
+    pub fn is_situational(&self) -> bool {
+        matches!(
+            self,
+            Self::SelfDrawn
+                | Self::LastTileDraw
+                | Self::LastTileClaim
+                | Self::OutWithReplacementTile
+                | Self::RobbingTheKong
+                | Self::BlessingOfHeaven
+                | Self::BlessingOfEarth
+                | Self::Riichi
+                | Self::Ippatsu
+        )
+    }
+
+    // 食い下がり: 副露時的分數, None 為門前限定
+    // the default open values, `ScoringModel` may give others
+    pub fn open_score(&self) -> Option<u16> {
+        match *self {
+            Self::PureDoubleChow
+            | Self::TwicePureDoubleChow
+            | Self::BlessingOfHeaven
+            | Self::BlessingOfEarth
+            | Self::Riichi
+            | Self::Ippatsu => None,
+            Self::PureStraight => Some(3),
+            Self::OutsideHands => Some(3),
+            Self::TerminalsInAllSets => Some(5),
+            Self::HalfFlush => Some(3),
+            Self::FullFlush => Some(7),
+            Self::MixedTripleChow => Some(2),
+            _ => Some(self.score()),
+        }
+    }
+
+    // 門前限定的狀況役, never awarded with a revealed meld
+    pub fn is_closed_only(&self) -> bool {
+        matches!(
+            self,
+            Self::BlessingOfHeaven | Self::BlessingOfEarth | Self::Riichi | Self::Ippatsu
+        )
+    }
+
+    #[allow(dead_code)]
     pub fn name(&self) -> String {
         match self {
             &Self::AllChows => "平和".to_string(),
@@ -106,6 +216,21 @@ impl Hand {
             &Self::BigFourWinds => "大四喜".to_string(),
             &Self::AllTerminals => "清老頭".to_string(),
             &Self::QuadrupleChow => "四同順".to_string(),
+            &Self::SeatFlower => "正花".to_string(),
+            &Self::SeatSeason => "正季".to_string(),
+            &Self::FourFlowers => "四花".to_string(),
+            &Self::FourSeasons => "四季".to_string(),
+            &Self::EightFlowers => "八仙".to_string(),
+            &Self::SelfDrawn => "自摸".to_string(),
+            &Self::LastTileDraw => "海底".to_string(),
+            &Self::LastTileClaim => "河底".to_string(),
+            &Self::OutWithReplacementTile => "嶺上".to_string(),
+            &Self::RobbingTheKong => "搶槓".to_string(),
+            &Self::BlessingOfHeaven => "天和".to_string(),
+            &Self::BlessingOfEarth => "地和".to_string(),
+            &Self::Riichi => "立直".to_string(),
+            &Self::Ippatsu => "一發".to_string(),
+            &Self::NoJoker => "無百搭".to_string(),
             &Self::NoPoint => "無役".to_string(),
         }
     }
@@ -146,7 +271,22 @@ impl TryFrom<usize> for Hand {
             27 => Ok(Self::BigFourWinds),
             28 => Ok(Self::AllTerminals),
             29 => Ok(Self::QuadrupleChow),
-            30 => Ok(Self::NoPoint),
+            30 => Ok(Self::SeatFlower),
+            31 => Ok(Self::SeatSeason),
+            32 => Ok(Self::FourFlowers),
+            33 => Ok(Self::FourSeasons),
+            34 => Ok(Self::EightFlowers),
+            35 => Ok(Self::SelfDrawn),
+            36 => Ok(Self::LastTileDraw),
+            37 => Ok(Self::LastTileClaim),
+            38 => Ok(Self::OutWithReplacementTile),
+            39 => Ok(Self::RobbingTheKong),
+            40 => Ok(Self::BlessingOfHeaven),
+            41 => Ok(Self::BlessingOfEarth),
+            42 => Ok(Self::Riichi),
+            43 => Ok(Self::Ippatsu),
+            44 => Ok(Self::NoJoker),
+            45 => Ok(Self::NoPoint),
             _ => Err(()),
         }
     }
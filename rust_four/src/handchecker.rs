@@ -1,27 +1,47 @@
 use arrayvec::ArrayVec;
 use bitvec::array::BitArray;
 use bitvec::BitArr;
+use itertools::Itertools;
+use serde::{ser::SerializeStruct, Deserialize, Deserializer, Serialize, Serializer};
 
+use crate::context::WinContext;
 use crate::hand::Hand;
 use crate::hand::HANDMAXSCORE;
 use crate::hand::HANDVARIANT;
-use crate::set::{Meld, Set, SETNUM};
+use crate::set::{Meld, Set, SetData, SETNUM};
 use crate::tile::{Tile, TileColor};
 
+const OPENBIT: usize = 63;
+
+// 役種, 及是否副露
 #[derive(Clone, Hash, PartialEq, Eq, Debug)]
-pub struct HandList(BitArr!(for HANDVARIANT, in u32));
+pub struct HandList(BitArr!(for HANDVARIANT, in u64), bool);
 
+#[allow(dead_code)]
 impl HandList {
     pub fn new() -> HandList {
-        HandList(BitArray::ZERO)
+        HandList(BitArray::ZERO, false)
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.1
+    }
+
+    pub fn set_open(&mut self, open: bool) {
+        self.1 = open;
     }
 
+    // 副露時取 open_score
     pub fn score(&self) -> u16 {
+        let score = |h: Hand| match self.1 {
+            true => h.open_score().unwrap_or(0),
+            false => h.score(),
+        };
         let s: u16 = self
             .iter()
             .enumerate()
             .take(HANDVARIANT)
-            .map(|(i, b)| Hand::try_from(i).unwrap().score() * *b as u16)
+            .map(|(i, b)| score(Hand::try_from(i).unwrap()) * *b as u16)
             .sum();
 
         if s > HANDMAXSCORE {
@@ -30,10 +50,67 @@ impl HandList {
             s
         }
     }
+
+    // 副露 kept in the top bit
+    #[allow(dead_code)]
+    pub fn bits(&self) -> u64 {
+        self.0.as_raw_slice()[0] | (self.1 as u64) << OPENBIT
+    }
+
+    #[allow(dead_code)]
+    pub fn from_bits(bits: u64) -> HandList {
+        HandList(
+            BitArray::new([bits & !(1 << OPENBIT)]),
+            bits >> OPENBIT == 1,
+        )
+    }
+}
+
+// stable text form: list of hand names, open hands as {"hands": [...], "open": true}
+impl Serialize for HandList {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let hands = self
+            .iter()
+            .enumerate()
+            .take(HANDVARIANT)
+            .filter(|(_, b)| **b)
+            .map(|(i, _)| Hand::try_from(i).unwrap());
+        match self.1 {
+            true => {
+                let mut s = serializer.serialize_struct("HandList", 2)?;
+                s.serialize_field("hands", &hands.collect::<Vec<Hand>>())?;
+                s.serialize_field("open", &true)?;
+                s.end()
+            }
+            false => serializer.collect_seq(hands),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum HandListData {
+    Closed(Vec<Hand>),
+    Open { hands: Vec<Hand>, open: bool },
+}
+
+impl<'de> Deserialize<'de> for HandList {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let mut list = HandList::new();
+        let hands = match HandListData::deserialize(deserializer)? {
+            HandListData::Closed(hands) => hands,
+            HandListData::Open { hands, open } => {
+                list.set_open(open);
+                hands
+            }
+        };
+        hands.into_iter().for_each(|h| list.set(h as usize, true));
+        Ok(list)
+    }
 }
 
 impl std::ops::Deref for HandList {
-    type Target = BitArr!(for HANDVARIANT, in u32);
+    type Target = BitArr!(for HANDVARIANT, in u64);
 
     fn deref(&self) -> &Self::Target {
         &self.0
@@ -56,59 +133,274 @@ impl Set {
     }
 }
 
-#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+#[derive(Clone, PartialEq, Eq, Hash, Debug, Serialize, Deserialize)]
+#[serde(try_from = "HandCheckerData")]
 pub struct HandChecker {
     pair: Tile,
     melds: ArrayVec<Meld, SETNUM>,
     wind: Tile, // 自風
 }
 
+#[derive(Deserialize)]
+struct HandCheckerData {
+    pair: Tile,
+    melds: Vec<Meld>,
+    wind: Tile,
+}
+
+impl TryFrom<HandCheckerData> for HandChecker {
+    type Error = String;
+
+    fn try_from(value: HandCheckerData) -> Result<Self, Self::Error> {
+        let set = Set::try_from(SetData {
+            pair: value.pair,
+            melds: value.melds,
+        })?;
+        Ok(set.to_handchecker(value.wind))
+    }
+}
+
+#[allow(dead_code)]
 impl HandChecker {
+    pub fn pair(&self) -> Tile {
+        self.pair
+    }
+
+    pub fn melds(&self) -> &ArrayVec<Meld, SETNUM> {
+        &self.melds
+    }
+
+    pub fn wind(&self) -> Tile {
+        self.wind
+    }
+
+    pub fn set_wind(&mut self, wind: Tile) {
+        self.wind = wind;
+    }
+
     pub fn sort(&mut self) {
         self.melds.sort();
     }
 
-    pub fn hands(&self) -> HandList {
-        let mut checker = HandList::new();
-        // all_chow_pung must be first
-        self.all_chow_pung(&mut checker);
-        self.score_pungs(&mut checker);
-        self.honors(&mut checker);
-        self.straight_simple_terminal(&mut checker);
-        self.pure_mix(&mut checker);
-        self.conceal_pungs(&mut checker);
-        self.same_chows(&mut checker);
-        self.shift_pungs(&mut checker);
-
-        match (
-            checker[Hand::AllHonors as usize],
-            checker[Hand::BigFourWinds as usize],
-            checker[Hand::AllTerminals as usize],
-            checker[Hand::QuadrupleChow as usize],
-        ) {
-            (false, false, false, false) => (),
-            _ => {
-                checker
-                    .iter_mut()
-                    .enumerate()
-                    .filter_map(|(i, c)| match i {
-                        i if i == Hand::AllHonors as usize
-                            || i == Hand::BigFourWinds as usize
-                            || i == Hand::AllTerminals as usize
-                            || i == Hand::QuadrupleChow as usize =>
-                        {
-                            None
-                        }
-                        _ => Some(c),
-                    })
-                    .for_each(|mut c| c.set(false));
-            }
+    pub fn hands(&self, context: &WinContext) -> HandList {
+        limit_hands(self.situational_hands(context))
+    }
+
+    // 花牌 set aside by the winner, scored apart from the decomposition
+    #[allow(dead_code)]
+    pub fn hands_with_bonus(&self, bonus: &[Tile], context: &WinContext) -> HandList {
+        let mut checker = self.situational_hands(context);
+        *checker |= bonus_hands(bonus, self.wind).0;
+        limit_hands(checker)
+    }
+
+    // 狀況役 on top of the tiles, 門前限定 ones only for concealed hands
+    fn situational_hands(&self, context: &WinContext) -> HandList {
+        let mut checker = self.unlimited_hands();
+        *checker |= context.hands().0;
+        if self.melds.iter().any(|m| m.kind.is_revealed()) {
+            checker.set_open(true);
+            (0..HANDVARIANT)
+                .filter(|i| Hand::try_from(*i).unwrap().is_closed_only())
+                .for_each(|i| checker.set(i, false));
         }
-        if checker.any() == false {
-            checker.set(Hand::NoPoint as usize, true);
+        checker
+    }
+
+    // hands before limit hands clear the others
+    pub(crate) fn unlimited_hands(&self) -> HandList {
+        self.checked().hands
+    }
+
+    // the checks in order, with the hands that returned early
+    pub(crate) fn checked(&self) -> Checked {
+        let mut checker = Checked::new();
+        CHECKS.iter().enumerate().for_each(|(i, (check, _))| {
+            checker.check = i;
+            check(self, &mut checker);
+        });
+        checker
+    }
+}
+
+type Check = fn(&HandChecker, &mut Checked);
+
+// checks with the hands each may set, in check order
+// all_chow_pung must be first
+const CHECKS: [(Check, &[Hand]); 8] = [
+    (
+        HandChecker::all_chow_pung,
+        &[Hand::AllChows, Hand::AllPungs],
+    ),
+    (
+        HandChecker::score_pungs,
+        &[
+            Hand::RedPung,
+            Hand::GreenPung,
+            Hand::WhitePung,
+            Hand::WindPung,
+        ],
+    ),
+    (
+        HandChecker::honors,
+        &[
+            Hand::BigFourWinds,
+            Hand::LittleFourWinds,
+            Hand::BigThreeDragons,
+            Hand::LittleThreeDragons,
+        ],
+    ),
+    (
+        HandChecker::straight_simple_terminal,
+        &[
+            Hand::PureStraight,
+            Hand::AllSimples,
+            Hand::AllTerminals,
+            Hand::AllTerminalsAndHonors,
+            Hand::TerminalsInAllSets,
+            Hand::OutsideHands,
+        ],
+    ),
+    (
+        HandChecker::pure_mix,
+        &[
+            Hand::AllHonors,
+            Hand::FullFlush,
+            Hand::HalfFlush,
+            Hand::MixedTripleChow,
+            Hand::TriplePung,
+        ],
+    ),
+    (
+        HandChecker::conceal_pungs,
+        &[
+            Hand::FourConcealedPungs,
+            Hand::ThreeConcealedPungs,
+            Hand::TwoConcealedPungs,
+        ],
+    ),
+    (
+        HandChecker::same_chows,
+        &[
+            Hand::QuadrupleChow,
+            Hand::TwicePureDoubleChow,
+            Hand::PureTripleChow,
+            Hand::PureDoubleChow,
+        ],
+    ),
+    (
+        HandChecker::shift_pungs,
+        &[Hand::FourPureShiftedPungs, Hand::PureShiftedPungs],
+    ),
+];
+
+// hands set so far, and the hand each check returned early with
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub(crate) struct Checked {
+    hands: HandList,
+    check: usize,
+    early: [Option<Hand>; CHECKS.len()],
+}
+
+#[allow(dead_code)]
+impl Checked {
+    fn new() -> Self {
+        Self {
+            hands: HandList::new(),
+            check: 0,
+            early: [None; CHECKS.len()],
         }
+    }
+
+    // set the hand and skip the rest of the check
+    fn early_return(&mut self, hand: Hand) {
+        self.hands.set(hand as usize, true);
+        self.early[self.check] = Some(hand);
+    }
 
+    pub fn hands(&self) -> &HandList {
+        &self.hands
+    }
+
+    // the hand whose check returned before this one was looked at
+    pub fn skipped_by(&self, hand: Hand) -> Option<Hand> {
+        let (i, (_, hands)) = CHECKS
+            .iter()
+            .enumerate()
+            .find(|(_, (_, hands))| hands.contains(&hand))?;
+        let early = self.early[i]?;
+        let order = |h: Hand| hands.iter().position(|x| *x == h);
+        match order(early) < order(hand) {
+            true => Some(early),
+            false => None,
+        }
+    }
+}
+
+impl std::ops::Deref for Checked {
+    type Target = HandList;
+
+    fn deref(&self) -> &Self::Target {
+        &self.hands
+    }
+}
+
+impl std::ops::DerefMut for Checked {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.hands
+    }
+}
+
+// 絕對滿貫 clears every other hand
+fn limit_hands(mut checker: HandList) -> HandList {
+    if let true = checker
+        .iter()
+        .enumerate()
+        .take(HANDVARIANT)
+        .any(|(i, b)| *b && Hand::try_from(i).unwrap().is_limit())
+    {
         checker
+            .iter_mut()
+            .enumerate()
+            .take(HANDVARIANT)
+            .filter(|(i, _)| !Hand::try_from(*i).unwrap().is_limit())
+            .for_each(|(_, mut c)| c.set(false));
+    }
+    if checker.any() == false {
+        checker.set(Hand::NoPoint as usize, true);
+    }
+
+    checker
+}
+
+// 花牌: 正花, 正季, 四花, 四季, 八仙過海 in place of the others
+pub fn bonus_hands(bonus: &[Tile], wind: Tile) -> HandList {
+    let mut checker = HandList::new();
+    let flowers = bonus.iter().filter(|t| t.is_flower()).unique().count();
+    let seasons = bonus.iter().filter(|t| t.is_season()).unique().count();
+    if flowers == 4 && seasons == 4 {
+        checker.set(Hand::EightFlowers as usize, true);
+        return checker;
+    }
+
+    let seat = |f: fn(&Tile) -> bool| bonus.iter().any(|t| f(t) && t.is_seat_bonus(wind));
+    checker.set(Hand::SeatFlower as usize, seat(Tile::is_flower));
+    checker.set(Hand::SeatSeason as usize, seat(Tile::is_season));
+    checker.set(Hand::FourFlowers as usize, flowers == 4);
+    checker.set(Hand::FourSeasons as usize, seasons == 4);
+
+    checker
+}
+
+impl HandList {
+    // add hands awarded apart from the decomposition, 花牌 or 無百搭,
+    // same as `hands_with_bonus`
+    #[allow(dead_code)]
+    pub fn with_bonus(&self, bonus: &HandList) -> HandList {
+        let mut checker = self.clone();
+        checker.set(Hand::NoPoint as usize, false);
+        *checker |= bonus.0;
+        limit_hands(checker)
     }
 }
 
@@ -184,12 +476,11 @@ macro_rules! comb2_fn {
 
 impl HandChecker {
     // 平和、對對和
-    fn all_chow_pung(&self, checker: &mut HandList) {
+    fn all_chow_pung(&self, checker: &mut Checked) {
         // 平和
         match self.melds.iter().all(|m| m.kind.is_chow()) {
             true => {
-                checker.set(Hand::AllChows as usize, true);
-                return;
+                return checker.early_return(Hand::AllChows);
             }
             false => (),
         }
@@ -204,7 +495,7 @@ impl HandChecker {
     }
 
     // 役牌
-    fn score_pungs(&self, checker: &mut HandList) {
+    fn score_pungs(&self, checker: &mut Checked) {
         match self.melds.iter().any(|m| m.head == Tile::Red) {
             true => checker.set(Hand::RedPung as usize, true),
             false => (),
@@ -227,18 +518,16 @@ impl HandChecker {
     }
 
     // 字牌類
-    fn honors(&self, checker: &mut HandList) {
+    fn honors(&self, checker: &mut Checked) {
         // 四喜和
         let wind_melds = self.melds.iter().filter(|m| m.head.is_wind()).count();
         match wind_melds {
             4 => {
-                checker.set(Hand::BigFourWinds as usize, true);
-                return;
+                return checker.early_return(Hand::BigFourWinds);
             }
             3 => match self.pair.is_wind() {
                 true => {
-                    checker.set(Hand::LittleFourWinds as usize, true);
-                    return;
+                    return checker.early_return(Hand::LittleFourWinds);
                 }
                 false => (),
             },
@@ -262,7 +551,7 @@ impl HandChecker {
     }
 
     // 一氣、幺九類
-    fn straight_simple_terminal(&self, checker: &mut HandList) {
+    fn straight_simple_terminal(&self, checker: &mut Checked) {
         // 一氣
         let is_straight = |m1: Meld, m2: Meld, m3: Meld| -> bool {
             match (m1.kind.is_chow(), m2.kind.is_chow(), m3.kind.is_chow()) {
@@ -277,7 +566,7 @@ impl HandChecker {
 
         comb3_fn!(self, is_straight, checker, Hand::PureStraight);
         if checker[Hand::PureStraight as usize] == true {
-            return;
+            return checker.early_return(Hand::PureStraight);
         }
 
         // 斷幺
@@ -291,8 +580,7 @@ impl HandChecker {
         });
 
         if let (true, true) = (pair_simple, melds_simple) {
-            checker.set(Hand::AllSimples as usize, true);
-            return;
+            return checker.early_return(Hand::AllSimples);
         }
 
         // 清老、混老、清全、混全
@@ -302,15 +590,15 @@ impl HandChecker {
                     self.pair.is_terminal(),
                     self.melds.iter().all(|m| m.head.is_terminal()),
                 ) {
-                    checker.set(Hand::AllTerminals as usize, true);
-                    return;
+                    return checker.early_return(Hand::AllTerminals);
                 }
 
                 if let (true, true) = (
                     !self.pair.is_simple(),
                     self.melds.iter().all(|m| !m.head.is_simple()),
                 ) {
-                    checker.set(Hand::AllTerminalsAndHonors as usize, true);
+                    // 混全帶 is not looked at for 對對和
+                    checker.early_return(Hand::AllTerminalsAndHonors);
                 };
             }
             false => {
@@ -324,8 +612,7 @@ impl HandChecker {
                 });
 
                 if let (true, true) = (pair_terminal, melds_terminal) {
-                    checker.set(Hand::TerminalsInAllSets as usize, true);
-                    return;
+                    return checker.early_return(Hand::TerminalsInAllSets);
                 }
 
                 let pair_honor_or_terminal = !pair_simple;
@@ -345,19 +632,17 @@ impl HandChecker {
     }
 
     // 一色類、三色類
-    fn pure_mix(&self, checker: &mut HandList) {
+    fn pure_mix(&self, checker: &mut Checked) {
         // 字一色
         let pair_honor = self.pair.is_honor();
         let melds_honor = self.melds.iter().all(|m| m.head.is_honor());
         if let (true, true) = (pair_honor, melds_honor) {
-            checker.set(Hand::AllHonors as usize, true);
-            return;
+            return checker.early_return(Hand::AllHonors);
         }
 
         // 清一色
         if let true = self.melds.iter().all(|m| m.head.is_same_color(self.pair)) {
-            checker.set(Hand::FullFlush as usize, true);
-            return;
+            return checker.early_return(Hand::FullFlush);
         }
 
         // 混一色
@@ -387,8 +672,7 @@ impl HandChecker {
                     .iter()
                     .all(|m| m.head.is_same_color(self.pair) || m.head.is_honor())
                 {
-                    checker.set(Hand::HalfFlush as usize, true);
-                    return;
+                    return checker.early_return(Hand::HalfFlush);
                 }
             }
         }
@@ -409,7 +693,7 @@ impl HandChecker {
 
         comb3_fn!(self, is_mixed_triple_chow, checker, Hand::MixedTripleChow);
         if checker[Hand::MixedTripleChow as usize] == true {
-            return;
+            return checker.early_return(Hand::MixedTripleChow);
         }
 
         // 三色同刻
@@ -430,12 +714,11 @@ impl HandChecker {
     }
 
     // 暗刻類
-    fn conceal_pungs(&self, checker: &mut HandList) {
+    fn conceal_pungs(&self, checker: &mut Checked) {
         // 四暗刻
         if let true = checker[Hand::AllPungs as usize] {
             if let true = self.melds.iter().all(|m| m.kind.is_concealed()) {
-                checker.set(Hand::FourConcealedPungs as usize, true);
-                return;
+                return checker.early_return(Hand::FourConcealedPungs);
             }
         }
 
@@ -461,7 +744,7 @@ impl HandChecker {
             Hand::ThreeConcealedPungs
         );
         if checker[Hand::ThreeConcealedPungs as usize] == true {
-            return;
+            return checker.early_return(Hand::ThreeConcealedPungs);
         }
 
         // 二暗刻
@@ -478,7 +761,7 @@ impl HandChecker {
         comb2_fn!(self, is_two_conceal_pung, checker, Hand::TwoConcealedPungs);
     }
     // 同順類
-    fn same_chows(&self, checker: &mut HandList) {
+    fn same_chows(&self, checker: &mut Checked) {
         let is_same_chow = |m1: Meld, m2: Meld| -> bool {
             match (m1.kind.is_chow(), m2.kind.is_chow()) {
                 (true, true) => m1.head == m2.head,
@@ -489,8 +772,7 @@ impl HandChecker {
         if let true = checker[Hand::AllChows as usize] {
             // 四同順
             if let true = self.melds.windows(2).all(|w| w[0].head == w[1].head) {
-                checker.set(Hand::QuadrupleChow as usize, true);
-                return;
+                return checker.early_return(Hand::QuadrupleChow);
             }
 
             // 二般高
@@ -504,8 +786,7 @@ impl HandChecker {
             ) {
                 (false, false, false) => {}
                 _ => {
-                    checker.set(Hand::TwicePureDoubleChow as usize, true);
-                    return;
+                    return checker.early_return(Hand::TwicePureDoubleChow);
                 }
             }
         }
@@ -515,14 +796,14 @@ impl HandChecker {
 
         comb3_fn!(self, is_triple_chow, checker, Hand::PureTripleChow);
         if checker[Hand::PureTripleChow as usize] == true {
-            return;
+            return checker.early_return(Hand::PureTripleChow);
         }
 
         comb2_fn!(self, is_same_chow, checker, Hand::PureDoubleChow);
     }
 
     // 連刻類
-    fn shift_pungs(&self, checker: &mut HandList) {
+    fn shift_pungs(&self, checker: &mut Checked) {
         // 四連刻
         if checker[Hand::AllPungs as usize]
             && self
@@ -539,8 +820,7 @@ impl HandChecker {
                 .map(|t| Tile::try_from((t + b'A') as char).unwrap())
                 .collect();
             if tmp.windows(2).all(|w| w[0].is_ascending(w[1])) {
-                checker.set(Hand::FourPureShiftedPungs as usize, true);
-                return;
+                return checker.early_return(Hand::FourPureShiftedPungs);
             }
         }
 
@@ -587,4 +867,202 @@ mod tests {
 
         assert_eq!(hl.score(), 16);
     }
+
+    #[test]
+    fn bonus_hands_for_seat() {
+        let hl = bonus_hands(&[Tile::F2, Tile::S2, Tile::S4], Tile::South);
+        assert!(hl[Hand::SeatFlower as usize] && hl[Hand::SeatSeason as usize]);
+        assert_eq!(hl.score(), 2);
+        assert!(bonus_hands(&[Tile::F2], Tile::East).not_any());
+
+        let hl = bonus_hands(&[Tile::F1, Tile::F2, Tile::F3, Tile::F4], Tile::West);
+        assert!(hl[Hand::FourFlowers as usize] && hl[Hand::SeatFlower as usize]);
+
+        let all: Vec<Tile> = (34..42).map(|i| Tile::try_from(i).unwrap()).collect();
+        let hl = bonus_hands(&all, Tile::East);
+        assert_eq!(hl.count_ones(), 1);
+        assert!(hl[Hand::EightFlowers as usize]);
+    }
+
+    #[test]
+    fn open_hands_reduce_scores() {
+        use crate::set::{MeldKind, SetBuilder};
+
+        // 11s 123s 123s 456s 789s, 一般高 and 一氣 in 清一色
+        let checker = |kind: MeldKind| {
+            SetBuilder::new()
+                .add_pair(Tile::B1)
+                .add_meld(Meld::new(Tile::B1, MeldKind::ConcealedChow))
+                .unwrap()
+                .add_meld(Meld::new(Tile::B1, MeldKind::ConcealedChow))
+                .unwrap()
+                .add_meld(Meld::new(Tile::B4, MeldKind::ConcealedChow))
+                .unwrap()
+                .add_meld(Meld::new(Tile::B7, kind))
+                .unwrap()
+                .build()
+                .unwrap()
+                .to_handchecker(Tile::East)
+                .hands(&WinContext::default())
+        };
+        let closed = checker(MeldKind::ConcealedChow);
+        let open = checker(MeldKind::RevealedChow);
+        assert!(!closed.is_open() && open.is_open());
+        // 一般高 is still found, open scoring drops it
+        assert!(closed[Hand::PureDoubleChow as usize]);
+        assert!(open[Hand::PureDoubleChow as usize]);
+        assert!(open[Hand::PureStraight as usize] && open[Hand::FullFlush as usize]);
+        assert_eq!(open.score(), closed.score() - 1 - 1 - 1);
+
+        // 副露 survives bits and text
+        assert_eq!(HandList::from_bits(open.bits()), open);
+        let json = serde_json::to_string(&open).unwrap();
+        assert_eq!(serde_json::from_str::<HandList>(&json).unwrap(), open);
+        let json = serde_json::to_string(&closed).unwrap();
+        assert!(json.starts_with('['));
+    }
+
+    #[test]
+    fn bonus_with_limit_and_no_point() {
+        let mut no_point = HandList::new();
+        no_point.set(Hand::NoPoint as usize, true);
+        let bonus = bonus_hands(&[Tile::F1], Tile::East);
+        let hl = no_point.with_bonus(&bonus);
+        assert!(!hl[Hand::NoPoint as usize] && hl[Hand::SeatFlower as usize]);
+        assert_eq!(no_point.with_bonus(&HandList::new()), no_point);
+
+        let mut limit = HandList::new();
+        limit.set(Hand::AllHonors as usize, true);
+        assert_eq!(limit.with_bonus(&bonus), limit);
+    }
+
+    #[test]
+    fn context_hands_for_open_and_closed() {
+        use crate::set::{MeldKind, SetBuilder};
+
+        let checker = |kind: MeldKind| {
+            SetBuilder::new()
+                .add_pair(Tile::D9)
+                .add_meld(Meld::new(Tile::B1, MeldKind::ConcealedChow))
+                .unwrap()
+                .add_meld(Meld::new(Tile::B4, MeldKind::ConcealedChow))
+                .unwrap()
+                .add_meld(Meld::new(Tile::C2, MeldKind::ConcealedChow))
+                .unwrap()
+                .add_meld(Meld::new(Tile::D2, kind))
+                .unwrap()
+                .build()
+                .unwrap()
+                .to_handchecker(Tile::East)
+        };
+        let riichi = WinContext {
+            self_draw: true,
+            riichi: true,
+            ..Default::default()
+        };
+
+        let hl = checker(MeldKind::ConcealedChow).hands(&riichi);
+        assert!(hl[Hand::SelfDrawn as usize] && hl[Hand::Riichi as usize]);
+        let hl = checker(MeldKind::RevealedChow).hands(&riichi);
+        assert!(hl[Hand::SelfDrawn as usize] && !hl[Hand::Riichi as usize]);
+
+        // 天和 is a limit hand
+        let heavenly = WinContext {
+            self_draw: true,
+            first_draw: true,
+            dealer: true,
+            ..Default::default()
+        };
+        let hl = checker(MeldKind::ConcealedChow).hands(&heavenly);
+        assert_eq!(hl.count_ones(), 1);
+        assert!(hl[Hand::BlessingOfHeaven as usize]);
+        assert_eq!(
+            checker(MeldKind::ConcealedChow).hands(&WinContext::default()),
+            checker(MeldKind::ConcealedChow).unlimited_hands()
+        );
+    }
+
+    #[test]
+    fn checks_set_their_own_hands() {
+        use crate::set::{MeldKind, SetBuilder};
+
+        let c = |pair: Tile, melds: &[(Tile, MeldKind)]| {
+            melds
+                .iter()
+                .fold(SetBuilder::new().add_pair(pair), |sb, (t, k)| {
+                    sb.add_meld(Meld::new(*t, *k)).unwrap()
+                })
+                .build()
+                .unwrap()
+                .to_handchecker(Tile::East)
+        };
+        [
+            c(
+                Tile::B5,
+                &[
+                    (Tile::B1, MeldKind::ConcealedChow),
+                    (Tile::B4, MeldKind::ConcealedChow),
+                    (Tile::B7, MeldKind::ConcealedChow),
+                    (Tile::B1, MeldKind::ConcealedChow),
+                ],
+            ),
+            c(
+                Tile::North,
+                &[
+                    (Tile::East, MeldKind::ConcealedPung),
+                    (Tile::South, MeldKind::ConcealedPung),
+                    (Tile::West, MeldKind::ConcealedPung),
+                    (Tile::Red, MeldKind::ConcealedPung),
+                ],
+            ),
+            c(
+                Tile::C9,
+                &[
+                    (Tile::B2, MeldKind::ConcealedPung),
+                    (Tile::B3, MeldKind::ConcealedPung),
+                    (Tile::B4, MeldKind::RevealedPung),
+                    (Tile::C2, MeldKind::ConcealedPung),
+                ],
+            ),
+        ]
+        .iter()
+        .for_each(|checker| {
+            let checked = checker.checked();
+            let mut hands = HandList::new();
+            CHECKS.iter().enumerate().for_each(|(i, (check, own))| {
+                let before = hands.clone();
+                let mut step = Checked::new();
+                step.hands = before.clone();
+                step.check = i;
+                check(checker, &mut step);
+                (0..HANDVARIANT)
+                    .filter(|h| step.hands[*h] && !before[*h])
+                    .for_each(|h| assert!(own.contains(&Hand::try_from(h).unwrap())));
+                assert!(step.early[i].is_none_or(|h| own.contains(&h)));
+                hands = step.hands;
+            });
+            assert_eq!(&hands, checked.hands());
+        });
+        // 四喜 returns before 三元, 三暗刻 is looked at after 四暗刻
+        let checked = c(
+            Tile::Red,
+            &[
+                (Tile::East, MeldKind::ConcealedPung),
+                (Tile::South, MeldKind::ConcealedPung),
+                (Tile::West, MeldKind::ConcealedPung),
+                (Tile::North, MeldKind::ConcealedPung),
+            ],
+        )
+        .checked();
+        assert_eq!(
+            checked.skipped_by(Hand::LittleThreeDragons),
+            Some(Hand::BigFourWinds)
+        );
+        assert_eq!(
+            checked.skipped_by(Hand::ThreeConcealedPungs),
+            Some(Hand::FourConcealedPungs)
+        );
+        assert_eq!(checked.skipped_by(Hand::BigFourWinds), None);
+        assert_eq!(checked.skipped_by(Hand::RedPung), None);
+    }
 }
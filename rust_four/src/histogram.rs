@@ -0,0 +1,167 @@
+use std::{
+    collections::HashMap,
+    fs::File,
+    io::{Error, ErrorKind, Read, Write},
+};
+
+use crate::{
+    hand::{CHECKERVERSION, HANDVARIANT},
+    handchecker::HandList,
+    pattern::{HAINUM, VARIANT},
+    tile::TILEVARIANT,
+};
+
+// 役種組合統計檔
+// header: magic, version, variant, tile count, hand count, checker version,
+// pattern checksum, tiles left in the wall, record count, followed by
+// records of HandList bits, patterns and combinations
+pub const MAGIC: [u8; 4] = *b"MJHG";
+pub const VERSION: u16 = 1;
+
+const FIXEDHEADERLEN: usize = 4 + 2 + 1 + 1 + 1 + 2 + 8 + TILEVARIANT + 8;
+const RECORDLEN: usize = 8 + 8 + 8;
+
+// agari's HandList to (patterns, combinations) before any score is applied
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Histogram {
+    pub source: u64, // checksum of pattern file
+    pub available: [u64; TILEVARIANT],
+    pub hands: HashMap<HandList, (u64, u64)>,
+}
+
+#[allow(dead_code)]
+impl Histogram {
+    pub fn encode(&self) -> Vec<u8> {
+        let mut buffer = Vec::with_capacity(FIXEDHEADERLEN + self.hands.len() * RECORDLEN);
+        buffer.extend_from_slice(&MAGIC);
+        buffer.extend_from_slice(&VERSION.to_le_bytes());
+        buffer.push(VARIANT.into());
+        buffer.push(HAINUM as u8);
+        buffer.push(HANDVARIANT as u8);
+        buffer.extend_from_slice(&CHECKERVERSION.to_le_bytes());
+        buffer.extend_from_slice(&self.source.to_le_bytes());
+        self.available.iter().for_each(|a| buffer.push(*a as u8));
+        buffer.extend_from_slice(&(self.hands.len() as u64).to_le_bytes());
+        // sorted, the same histogram always gives the same file
+        let mut records: Vec<(u64, (u64, u64))> =
+            self.hands.iter().map(|(l, v)| (l.bits(), *v)).collect();
+        records.sort();
+        records
+            .into_iter()
+            .for_each(|(bits, (pattern, combination))| {
+                buffer.extend_from_slice(&bits.to_le_bytes());
+                buffer.extend_from_slice(&pattern.to_le_bytes());
+                buffer.extend_from_slice(&combination.to_le_bytes());
+            });
+        buffer
+    }
+
+    pub fn decode(buffer: &[u8]) -> std::io::Result<Self> {
+        let invalid = |msg: &str| Error::new(ErrorKind::InvalidData, msg.to_string());
+
+        if buffer.len() < FIXEDHEADERLEN || buffer[0..4] != MAGIC {
+            Err(invalid("not a hand histogram"))?
+        }
+        if u16::from_le_bytes([buffer[4], buffer[5]]) != VERSION {
+            Err(invalid("unsupported hand histogram version"))?
+        }
+        if buffer[6] != u8::from(VARIANT) || buffer[7] as usize != HAINUM {
+            Err(invalid("hand histogram built for another variant"))?
+        }
+        if buffer[8] as usize != HANDVARIANT
+            || u16::from_le_bytes([buffer[9], buffer[10]]) != CHECKERVERSION
+        {
+            Err(invalid("hand histogram built by another hand checker"))?
+        }
+        let read_u64 = |at: usize| u64::from_le_bytes(buffer[at..at + 8].try_into().unwrap());
+        let source = read_u64(11);
+        let mut available = [0; TILEVARIANT];
+        available
+            .iter_mut()
+            .zip(buffer[19..19 + TILEVARIANT].iter())
+            .for_each(|(a, b)| *a = *b as u64);
+        let n = read_u64(19 + TILEVARIANT) as usize;
+        // the record count comes from the file, the size must not overflow
+        let size = n
+            .checked_mul(RECORDLEN)
+            .and_then(|d| d.checked_add(FIXEDHEADERLEN))
+            .ok_or_else(|| invalid("impossible hand histogram size"))?;
+        if buffer.len() != size {
+            Err(invalid("truncated hand histogram"))?
+        }
+
+        let hands = buffer[FIXEDHEADERLEN..]
+            .chunks(RECORDLEN)
+            .map(|c| {
+                let read = |at: usize| u64::from_le_bytes(c[at..at + 8].try_into().unwrap());
+                (HandList::from_bits(read(0)), (read(8), read(16)))
+            })
+            .collect();
+
+        Ok(Self {
+            source,
+            available,
+            hands,
+        })
+    }
+
+    pub fn write(&self, filename: &str) -> std::io::Result<()> {
+        let mut file = File::create(filename)?;
+        file.write_all(&self.encode())
+    }
+
+    pub fn read(filename: &str) -> std::io::Result<Self> {
+        let mut reader = File::open(filename)?;
+        let mut buffer = Vec::new();
+        reader.read_to_end(&mut buffer)?;
+        Self::decode(&buffer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hand::Hand;
+
+    fn histogram() -> Histogram {
+        let mut hands = HashMap::new();
+        let mut list = HandList::new();
+        list.set(Hand::AllPungs as usize, true);
+        hands.insert(list.clone(), (3, 1200));
+        list.set(Hand::NoPoint as usize, true);
+        hands.insert(list, (1, 6));
+        let mut available = [4; TILEVARIANT];
+        available[0] = 2;
+        Histogram {
+            source: 42,
+            available,
+            hands,
+        }
+    }
+
+    #[test]
+    fn histogram_round_trip() {
+        let h = histogram();
+        let buffer = h.encode();
+        assert_eq!(buffer.len(), FIXEDHEADERLEN + 2 * RECORDLEN);
+        assert_eq!(Histogram::decode(&buffer).unwrap(), h);
+    }
+
+    #[test]
+    fn rejects_other_checker() {
+        let mut buffer = histogram().encode();
+        buffer[9] ^= 1;
+        assert!(Histogram::decode(&buffer).is_err());
+        let buffer = histogram().encode();
+        assert!(Histogram::decode(&buffer[..buffer.len() - 1]).is_err());
+        assert!(Histogram::decode(b"MJLT").is_err());
+    }
+
+    #[test]
+    fn rejects_huge_record_count() {
+        let mut buffer = histogram().encode();
+        let at = 19 + TILEVARIANT;
+        buffer[at..at + 8].copy_from_slice(&(u64::MAX / 2).to_le_bytes());
+        assert!(Histogram::decode(&buffer).is_err());
+    }
+}
@@ -0,0 +1,99 @@
+use arrayvec::ArrayVec;
+use itertools::Itertools;
+
+use crate::{
+    context::WinContext,
+    decompose::allsets,
+    hand::Hand,
+    handchecker::{HandChecker, HandList},
+    set::HAINUM,
+    tile::{Tile, TILEVARIANT},
+};
+
+// 百搭, stands in for any tile in a pattern record
+// only hand explanation here, joker patterns and their weight are listed by rust_custom
+pub const JOKER: u8 = b'*';
+
+#[allow(dead_code)]
+pub fn jokers(raw: &[u8]) -> usize {
+    raw.iter().filter(|r| **r == JOKER).count()
+}
+
+// every way the jokers stand in for tiles, at most four copies of a tile
+pub fn substitutions(raw: &[u8]) -> Vec<ArrayVec<u8, HAINUM>> {
+    let tiles: Vec<u8> = raw.iter().cloned().filter(|r| *r != JOKER).collect();
+    let mut counts = [0usize; TILEVARIANT];
+    tiles
+        .iter()
+        .map(|r| Tile::try_from(*r as char).unwrap())
+        .filter(|t| !t.is_bonus())
+        .for_each(|t| counts[t as usize] += 1);
+
+    (0..TILEVARIANT)
+        .combinations_with_replacement(raw.len() - tiles.len())
+        .filter(|sub| {
+            let mut added = [0usize; TILEVARIANT];
+            sub.iter().for_each(|i| added[*i] += 1);
+            (0..TILEVARIANT).all(|i| counts[i] + added[i] <= 4)
+        })
+        .map(|sub| {
+            let mut hai: Vec<u8> = tiles
+                .iter()
+                .cloned()
+                .chain(sub.into_iter().map(|i| i as u8 + b'A'))
+                .collect();
+            hai.sort();
+            hai.into_iter().collect()
+        })
+        .collect()
+}
+
+// the best scoring substitution, None when no substitution wins
+// 無百搭 goes to hands without jokers
+#[allow(dead_code)]
+pub fn best(raw: &[u8], wind: Tile) -> Option<(HandChecker, HandList)> {
+    let mut no_joker = HandList::new();
+    no_joker.set(Hand::NoJoker as usize, jokers(raw) == 0);
+
+    let mut sets: Vec<HandChecker> = substitutions(raw).iter().flat_map(|s| allsets(s)).collect();
+    sets.sort();
+    sets.dedup();
+    sets.into_iter()
+        .map(|mut s| {
+            s.set_wind(wind);
+            let list = s.hands(&WinContext::default()).with_bonus(&no_joker);
+            (s, list)
+        })
+        .max_by_key(|(s, list)| (list.score(), std::cmp::Reverse(s.clone())))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn joker_fills_pair_and_meld() {
+        // 中中中 發發發 白白白 111s 5s + 百搭 is 大三元 with 55s
+        let raw = *b"*AAABBBCCCHHHL";
+        assert_eq!(substitutions(&raw).len(), TILEVARIANT);
+        let (checker, list) = best(&raw, Tile::East).unwrap();
+        assert_eq!(checker.pair(), Tile::B5);
+        assert!(list[Hand::BigThreeDragons as usize]);
+        assert!(!list[Hand::NoJoker as usize]);
+
+        // without the joker the same shape earns 無百搭
+        let raw = *b"AAABBBCCCHHHLL";
+        let (_, list) = best(&raw, Tile::East).unwrap();
+        assert!(list[Hand::NoJoker as usize]);
+    }
+
+    #[test]
+    fn best_substitution_is_chosen() {
+        // 123456789s 111m + two jokers as any pair keeps 一氣通貫
+        let raw = *b"**HIJKLMNOPQQQ";
+        let (_, list) = best(&raw, Tile::East).unwrap();
+        assert!(list[Hand::PureStraight as usize]);
+
+        assert!(best(b"**ADGHKNQTWZ]`", Tile::East).is_none());
+    }
+}
@@ -1,17 +1,15 @@
-use std::fs::File;
-use std::io::Write;
-
 use arrayvec::ArrayVec;
 use itertools::Itertools;
 
-use set::{Meld, MeldKind, Set, SetBuilder};
+use pattern::VARIANT;
+use rust_common::pattern::write_patterns;
+use set::{Meld, MeldKind, Set, SetBuilder, HAINUM, SETNUM};
 use tile::{Tile, TILEVARIANT};
 
+mod pattern;
 mod set;
 mod tile;
 
-const HAINUM: usize = 14;
-const SETNUM: usize = HAINUM / 3;
 const SETKINDVARIANT: usize = 2_usize.pow(SETNUM as u32);
 const TILESELECTNUM: usize = TILEVARIANT * 4 - 7 * 3;
 
@@ -83,8 +81,12 @@ fn main() {
 
     let tmp: Vec<u8> = possible_sets.into_iter().flatten().collect();
     let filename = "patterns_rust_four.dat";
-    let mut file = File::create(filename).expect("not able to open file");
-    file.write_all(&tmp)
+    let generator = format!(
+        "{} list {}",
+        env!("CARGO_PKG_NAME"),
+        env!("CARGO_PKG_VERSION")
+    );
+    write_patterns(filename, &generator, VARIANT, pattern::HAINUM, &tmp)
         .expect("cannot write all into the file");
 }
 
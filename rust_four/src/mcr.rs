@@ -0,0 +1,1425 @@
+use std::error::Error;
+
+use arrayvec::ArrayVec;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    decompose::allsets,
+    set::{Meld, MeldKind, HAINUM, SETNUM},
+    tile::{Tile, TileColor, TILEVARIANT},
+};
+
+// 國標麻將
+// 81 fan, 不重複 by exclusion lists and 套算一次 for chow and pung combinations
+pub const FANVARIANT: usize = 81;
+pub const MINIMUM: u16 = 8; // 起和番, flowers excluded
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Serialize, Deserialize)]
+pub enum Fan {
+    // 88
+    BigFourWinds,      // 大四喜
+    BigThreeDragons,   // 大三元
+    AllGreen,          // 綠一色
+    NineGates,         // 九蓮寶燈
+    FourKongs,         // 四槓
+    SevenShiftedPairs, // 連七對
+    ThirteenOrphans,   // 十三么
+    // 64
+    AllTerminals,       // 清么九
+    LittleFourWinds,    // 小四喜
+    LittleThreeDragons, // 小三元
+    AllHonors,          // 字一色
+    FourConcealedPungs, // 四暗刻
+    PureTerminalChows,  // 一色雙龍會
+    // 48
+    QuadrupleChow,        // 一色四同順
+    FourPureShiftedPungs, // 一色四節高
+    // 32
+    FourPureShiftedChows,  // 一色四步高
+    ThreeKongs,            // 三槓
+    AllTerminalsAndHonors, // 混么九
+    // 24
+    SevenPairs,                   // 七對
+    GreaterHonorsAndKnittedTiles, // 七星不靠
+    AllEvenPungs,                 // 全雙刻
+    FullFlush,                    // 清一色
+    PureTripleChow,               // 一色三同順
+    PureShiftedPungs,             // 一色三節高
+    UpperTiles,                   // 全大
+    MiddleTiles,                  // 全中
+    LowerTiles,                   // 全小
+    // 16
+    PureStraight,             // 清龍
+    ThreeSuitedTerminalChows, // 三色雙龍會
+    PureShiftedChows,         // 一色三步高
+    AllFives,                 // 全帶五
+    TriplePung,               // 三同刻
+    ThreeConcealedPungs,      // 三暗刻
+    // 12
+    LesserHonorsAndKnittedTiles, // 全不靠
+    KnittedStraight,             // 組合龍
+    UpperFour,                   // 大於五
+    LowerFour,                   // 小於五
+    BigThreeWinds,               // 三風刻
+    // 8
+    MixedStraight,          // 花龍
+    ReversibleTiles,        // 推不倒
+    MixedTripleChow,        // 三色三同順
+    MixedShiftedPungs,      // 三色三節高
+    ChickenHand,            // 無番和
+    LastTileDraw,           // 妙手回春
+    LastTileClaim,          // 海底撈月
+    OutWithReplacementTile, // 槓上開花
+    RobbingTheKong,         // 搶槓和
+    // 6
+    AllPungs,          // 碰碰和
+    HalfFlush,         // 混一色
+    MixedShiftedChows, // 三色三步高
+    AllTypes,          // 五門齊
+    MeldedHand,        // 全求人
+    TwoConcealedKongs, // 雙暗槓
+    TwoDragonPungs,    // 雙箭刻
+    // 4
+    OutsideHand,        // 全帶么
+    FullyConcealedHand, // 不求人
+    TwoMeldedKongs,     // 雙明槓
+    LastTile,           // 和絕張
+    // 2
+    DragonPung,        // 箭刻
+    PrevalentWind,     // 圈風刻
+    SeatWind,          // 門風刻
+    ConcealedHand,     // 門前清
+    AllChows,          // 平和
+    TileHog,           // 四歸一
+    DoublePung,        // 雙同刻
+    TwoConcealedPungs, // 雙暗刻
+    ConcealedKong,     // 暗槓
+    AllSimples,        // 斷么
+    // 1
+    PureDoubleChow,          // 一般高
+    MixedDoubleChow,         // 喜相逢
+    ShortStraight,           // 連六
+    TwoTerminalChows,        // 老少副
+    PungOfTerminalsOrHonors, // 么九刻
+    MeldedKong,              // 明槓
+    OneVoidedSuit,           // 缺一門
+    NoHonors,                // 無字
+    EdgeWait,                // 邊張
+    ClosedWait,              // 坎張
+    SingleWait,              // 單釣將
+    SelfDrawn,               // 自摸
+    FlowerTiles,             // 花牌
+}
+
+use Fan::*;
+
+// highest first, exclusions are applied in this order
+pub const FANS: [Fan; FANVARIANT] = [
+    BigFourWinds,
+    BigThreeDragons,
+    AllGreen,
+    NineGates,
+    FourKongs,
+    SevenShiftedPairs,
+    ThirteenOrphans,
+    AllTerminals,
+    LittleFourWinds,
+    LittleThreeDragons,
+    AllHonors,
+    FourConcealedPungs,
+    PureTerminalChows,
+    QuadrupleChow,
+    FourPureShiftedPungs,
+    FourPureShiftedChows,
+    ThreeKongs,
+    AllTerminalsAndHonors,
+    SevenPairs,
+    GreaterHonorsAndKnittedTiles,
+    AllEvenPungs,
+    FullFlush,
+    PureTripleChow,
+    PureShiftedPungs,
+    UpperTiles,
+    MiddleTiles,
+    LowerTiles,
+    PureStraight,
+    ThreeSuitedTerminalChows,
+    PureShiftedChows,
+    AllFives,
+    TriplePung,
+    ThreeConcealedPungs,
+    LesserHonorsAndKnittedTiles,
+    KnittedStraight,
+    UpperFour,
+    LowerFour,
+    BigThreeWinds,
+    MixedStraight,
+    ReversibleTiles,
+    MixedTripleChow,
+    MixedShiftedPungs,
+    ChickenHand,
+    LastTileDraw,
+    LastTileClaim,
+    OutWithReplacementTile,
+    RobbingTheKong,
+    AllPungs,
+    HalfFlush,
+    MixedShiftedChows,
+    AllTypes,
+    MeldedHand,
+    TwoConcealedKongs,
+    TwoDragonPungs,
+    OutsideHand,
+    FullyConcealedHand,
+    TwoMeldedKongs,
+    LastTile,
+    DragonPung,
+    PrevalentWind,
+    SeatWind,
+    ConcealedHand,
+    AllChows,
+    TileHog,
+    DoublePung,
+    TwoConcealedPungs,
+    ConcealedKong,
+    AllSimples,
+    PureDoubleChow,
+    MixedDoubleChow,
+    ShortStraight,
+    TwoTerminalChows,
+    PungOfTerminalsOrHonors,
+    MeldedKong,
+    OneVoidedSuit,
+    NoHonors,
+    EdgeWait,
+    ClosedWait,
+    SingleWait,
+    SelfDrawn,
+    FlowerTiles,
+];
+
+impl Fan {
+    pub fn value(&self) -> u8 {
+        match self {
+            BigFourWinds | BigThreeDragons | AllGreen | NineGates | FourKongs
+            | SevenShiftedPairs | ThirteenOrphans => 88,
+            AllTerminals | LittleFourWinds | LittleThreeDragons | AllHonors
+            | FourConcealedPungs | PureTerminalChows => 64,
+            QuadrupleChow | FourPureShiftedPungs => 48,
+            FourPureShiftedChows | ThreeKongs | AllTerminalsAndHonors => 32,
+            SevenPairs
+            | GreaterHonorsAndKnittedTiles
+            | AllEvenPungs
+            | FullFlush
+            | PureTripleChow
+            | PureShiftedPungs
+            | UpperTiles
+            | MiddleTiles
+            | LowerTiles => 24,
+            PureStraight
+            | ThreeSuitedTerminalChows
+            | PureShiftedChows
+            | AllFives
+            | TriplePung
+            | ThreeConcealedPungs => 16,
+            LesserHonorsAndKnittedTiles
+            | KnittedStraight
+            | UpperFour
+            | LowerFour
+            | BigThreeWinds => 12,
+            MixedStraight
+            | ReversibleTiles
+            | MixedTripleChow
+            | MixedShiftedPungs
+            | ChickenHand
+            | LastTileDraw
+            | LastTileClaim
+            | OutWithReplacementTile
+            | RobbingTheKong => 8,
+            AllPungs | HalfFlush | MixedShiftedChows | AllTypes | MeldedHand
+            | TwoConcealedKongs | TwoDragonPungs => 6,
+            OutsideHand | FullyConcealedHand | TwoMeldedKongs | LastTile => 4,
+            DragonPung | PrevalentWind | SeatWind | ConcealedHand | AllChows | TileHog
+            | DoublePung | TwoConcealedPungs | ConcealedKong | AllSimples => 2,
+            PureDoubleChow
+            | MixedDoubleChow
+            | ShortStraight
+            | TwoTerminalChows
+            | PungOfTerminalsOrHonors
+            | MeldedKong
+            | OneVoidedSuit
+            | NoHonors
+            | EdgeWait
+            | ClosedWait
+            | SingleWait
+            | SelfDrawn
+            | FlowerTiles => 1,
+        }
+    }
+
+    // 不計, fans already implied by this one
+    pub fn excludes(&self) -> &'static [Fan] {
+        match self {
+            BigFourWinds => &[
+                BigThreeWinds,
+                LittleFourWinds,
+                AllPungs,
+                PrevalentWind,
+                SeatWind,
+                PungOfTerminalsOrHonors,
+            ],
+            BigThreeDragons => &[LittleThreeDragons, TwoDragonPungs, DragonPung],
+            AllGreen => &[HalfFlush, OneVoidedSuit],
+            NineGates => &[
+                FullFlush,
+                ConcealedHand,
+                FullyConcealedHand,
+                PungOfTerminalsOrHonors,
+                OneVoidedSuit,
+                NoHonors,
+            ],
+            FourKongs => &[SingleWait],
+            SevenShiftedPairs => &[
+                SevenPairs,
+                FullFlush,
+                ConcealedHand,
+                FullyConcealedHand,
+                SingleWait,
+                OneVoidedSuit,
+                NoHonors,
+            ],
+            ThirteenOrphans => &[
+                AllTerminalsAndHonors,
+                AllTypes,
+                ConcealedHand,
+                FullyConcealedHand,
+                SingleWait,
+            ],
+            AllTerminals => &[
+                AllTerminalsAndHonors,
+                AllPungs,
+                OutsideHand,
+                DoublePung,
+                PungOfTerminalsOrHonors,
+                NoHonors,
+            ],
+            LittleFourWinds => &[BigThreeWinds, PungOfTerminalsOrHonors],
+            LittleThreeDragons => &[TwoDragonPungs, DragonPung],
+            AllHonors => &[
+                AllTerminalsAndHonors,
+                AllPungs,
+                OutsideHand,
+                PungOfTerminalsOrHonors,
+            ],
+            FourConcealedPungs => &[
+                ThreeConcealedPungs,
+                AllPungs,
+                ConcealedHand,
+                TwoConcealedPungs,
+            ],
+            PureTerminalChows => &[
+                SevenPairs,
+                FullFlush,
+                AllChows,
+                PureDoubleChow,
+                TwoTerminalChows,
+                OneVoidedSuit,
+                NoHonors,
+            ],
+            QuadrupleChow => &[PureTripleChow, PureShiftedPungs, TileHog, PureDoubleChow],
+            FourPureShiftedPungs => &[PureTripleChow, PureShiftedPungs, AllPungs],
+            FourPureShiftedChows => &[PureShiftedChows, ShortStraight, TwoTerminalChows],
+            AllTerminalsAndHonors => &[AllPungs, OutsideHand, PungOfTerminalsOrHonors],
+            SevenPairs => &[ConcealedHand, FullyConcealedHand, SingleWait],
+            GreaterHonorsAndKnittedTiles => &[
+                LesserHonorsAndKnittedTiles,
+                AllTypes,
+                ConcealedHand,
+                FullyConcealedHand,
+                SingleWait,
+            ],
+            AllEvenPungs => &[AllPungs, AllSimples, NoHonors],
+            FullFlush => &[OneVoidedSuit, NoHonors],
+            PureTripleChow => &[PureShiftedPungs, PureDoubleChow],
+            PureShiftedPungs => &[PureTripleChow],
+            UpperTiles => &[UpperFour, NoHonors],
+            MiddleTiles => &[AllSimples, NoHonors],
+            LowerTiles => &[LowerFour, NoHonors],
+            ThreeSuitedTerminalChows => &[AllChows, MixedDoubleChow, TwoTerminalChows, NoHonors],
+            AllFives => &[AllSimples, NoHonors],
+            ThreeConcealedPungs => &[TwoConcealedPungs],
+            LesserHonorsAndKnittedTiles => {
+                &[AllTypes, ConcealedHand, FullyConcealedHand, SingleWait]
+            }
+            UpperFour | LowerFour => &[NoHonors],
+            ReversibleTiles => &[OneVoidedSuit],
+            LastTileDraw | OutWithReplacementTile => &[SelfDrawn],
+            RobbingTheKong => &[LastTile],
+            HalfFlush => &[OneVoidedSuit],
+            MeldedHand => &[SingleWait],
+            TwoConcealedKongs => &[ConcealedKong, TwoConcealedPungs],
+            TwoDragonPungs => &[DragonPung],
+            FullyConcealedHand => &[SelfDrawn],
+            TwoMeldedKongs => &[MeldedKong],
+            AllChows | AllSimples => &[NoHonors],
+            _ => &[],
+        }
+    }
+}
+
+// fan counts, 四歸一 and 么九刻 may count more than once
+#[derive(Clone, PartialEq, Eq, Debug)]
+struct FanTable([u8; FANVARIANT]);
+
+impl FanTable {
+    fn new() -> Self {
+        FanTable([0; FANVARIANT])
+    }
+
+    fn add(&mut self, fan: Fan, n: u8) {
+        self.0[fan as usize] += n;
+    }
+
+    fn has(&self, fan: Fan) -> bool {
+        self.0[fan as usize] > 0
+    }
+
+    // 不重複原則, higher fans first so excluded fans exclude nothing
+    fn exclude(&mut self) {
+        FANS.iter().for_each(|f| {
+            if self.has(*f) {
+                f.excludes().iter().for_each(|e| self.0[*e as usize] = 0);
+            }
+        });
+    }
+
+    fn total(&self) -> u16 {
+        FANS.iter()
+            .filter(|f| **f != FlowerTiles)
+            .map(|f| f.value() as u16 * self.0[*f as usize] as u16)
+            .sum()
+    }
+
+    fn list(&self) -> Vec<(Fan, u8)> {
+        FANS.iter()
+            .filter(|f| self.has(**f))
+            .map(|f| (*f, self.0[*f as usize]))
+            .collect()
+    }
+}
+
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum Form {
+    Standard {
+        pair: Tile,
+        melds: ArrayVec<Meld, SETNUM>,
+    },
+    SevenPairs,
+    ThirteenOrphans,
+    HonorsAndKnitted, // 全不靠、七星不靠
+    KnittedStraight {
+        pair: Tile,
+        meld: Meld,
+    }, // 組合龍 with one meld and a pair
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct McrWin {
+    pub concealed: Vec<Tile>, // 手牌, winning tile included
+    #[serde(default)]
+    pub called: Vec<Meld>, // 吃碰槓 and 暗槓
+    pub winning: Tile,
+    #[serde(default)]
+    pub self_drawn: bool,
+    pub seat: Tile, // 門風
+    #[serde(default = "east")]
+    pub prevalent: Tile, // 圈風
+    #[serde(default)]
+    pub flowers: u8,
+    #[serde(default)]
+    pub last_tile: bool, // 和絕張
+    #[serde(default)]
+    pub last_draw: bool, // 妙手回春、海底撈月
+    #[serde(default)]
+    pub replacement: bool, // 槓上開花
+    #[serde(default)]
+    pub robbing: bool, // 搶槓和
+}
+
+fn east() -> Tile {
+    Tile::East
+}
+
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub struct McrScore {
+    pub form: Form,
+    pub fans: Vec<(Fan, u8)>,
+    pub total: u16, // flowers included
+}
+
+// winning tile placed in a standard or 組合龍 form
+struct Placement {
+    wait: Option<Fan>,       // 邊張、坎張、單釣將
+    ron_pung: Option<usize>, // pung completed by a discard is not concealed
+}
+
+fn tile(i: usize) -> Tile {
+    Tile::try_from(i).unwrap()
+}
+
+fn meld_tiles(m: &Meld) -> ArrayVec<Tile, 4> {
+    match (m.kind.is_chow(), m.kind.is_kong()) {
+        (true, _) => (0..3).map(|i| tile(m.head as usize + i)).collect(),
+        (false, false) => (0..3).map(|_| m.head).collect(),
+        (false, true) => (0..4).map(|_| m.head).collect(),
+    }
+}
+
+// 147, 258, 369 over the three suits, six ways
+fn knitted() -> Vec<[Tile; 9]> {
+    let suits = [Tile::B1, Tile::C1, Tile::D1];
+    [
+        [0, 1, 2],
+        [0, 2, 1],
+        [1, 0, 2],
+        [1, 2, 0],
+        [2, 0, 1],
+        [2, 1, 0],
+    ]
+    .iter()
+    .map(|p| {
+        let mut k = [Tile::B1; 9];
+        (0..9).for_each(|i| k[i] = tile(suits[p[i / 3]] as usize + i / 3 + 3 * (i % 3)));
+        k
+    })
+    .collect()
+}
+
+fn suit_index(t: Tile) -> Option<usize> {
+    match t.color() {
+        TileColor::Bamboo => Some(0),
+        TileColor::Character => Some(1),
+        TileColor::Dot => Some(2),
+        TileColor::Honor | TileColor::Bonus => None,
+    }
+}
+
+// chow combinations by head
+fn two_chows(a: Tile, b: Tile) -> Option<Fan> {
+    match (a.is_same_color(b), a.number(), b.number()) {
+        (true, x, y) if x == y => Some(PureDoubleChow),
+        (true, x, y) if x.abs_diff(y) == 3 => Some(ShortStraight),
+        (true, x, y) if x.abs_diff(y) == 6 => Some(TwoTerminalChows),
+        (false, x, y) if x == y => Some(MixedDoubleChow),
+        _ => None,
+    }
+}
+
+fn three_chows(c: &[Tile]) -> Option<Fan> {
+    let mut numbers: ArrayVec<usize, 3> = c.iter().map(|t| t.number()).collect();
+    numbers.sort();
+    let same = c.iter().all(|t| t.is_same_color(c[0]));
+    let mixed = !c[0].is_same_color(c[1]) && !c[0].is_same_color(c[2]) && !c[1].is_same_color(c[2]);
+    let step = numbers[1] - numbers[0];
+    let shifted = step == numbers[2] - numbers[1];
+    match (same, mixed, numbers.as_slice(), step) {
+        (true, _, _, 0) if shifted => Some(PureTripleChow),
+        (true, _, [1, 4, 7], _) => Some(PureStraight),
+        (true, _, _, 1 | 2) if shifted => Some(PureShiftedChows),
+        (_, true, _, 0) if shifted => Some(MixedTripleChow),
+        (_, true, [1, 4, 7], _) => Some(MixedStraight),
+        (_, true, _, 1) if shifted => Some(MixedShiftedChows),
+        _ => None,
+    }
+}
+
+fn four_chows(c: &[Tile]) -> Option<Fan> {
+    let mut c: ArrayVec<Tile, SETNUM> = c.iter().cloned().collect();
+    c.sort();
+    let step = c[1] as usize - c[0] as usize;
+    match (
+        c.iter().all(|t| t.is_same_color(c[0])),
+        c.windows(2).all(|w| w[1] as usize - w[0] as usize == step),
+        step,
+    ) {
+        (true, true, 0) => Some(QuadrupleChow),
+        (true, true, 1 | 2) => Some(FourPureShiftedChows),
+        _ => None,
+    }
+}
+
+fn two_pungs(a: Tile, b: Tile) -> Option<Fan> {
+    match !a.is_same_color(b) && a.number() == b.number() {
+        true => Some(DoublePung),
+        false => None,
+    }
+}
+
+fn three_pungs(c: &[Tile]) -> Option<Fan> {
+    let mut c: ArrayVec<Tile, 3> = c.iter().cloned().collect();
+    c.sort_by_key(|t| t.number());
+    let same = c.iter().all(|t| t.is_same_color(c[0]));
+    let mixed = !c[0].is_same_color(c[1]) && !c[0].is_same_color(c[2]) && !c[1].is_same_color(c[2]);
+    let consecutive = c.windows(2).all(|w| w[1].number() == w[0].number() + 1);
+    match (same, mixed, consecutive) {
+        (true, _, true) => Some(PureShiftedPungs),
+        (_, true, true) => Some(MixedShiftedPungs),
+        (_, true, false) if c.iter().all(|t| t.number() == c[0].number()) => Some(TriplePung),
+        _ => None,
+    }
+}
+
+fn four_pungs(c: &[Tile]) -> Option<Fan> {
+    let mut c: ArrayVec<Tile, SETNUM> = c.iter().cloned().collect();
+    c.sort();
+    match c.iter().all(|t| t.is_same_color(c[0]))
+        && c.windows(2).all(|w| w[1] as usize == w[0] as usize + 1)
+    {
+        true => Some(FourPureShiftedPungs),
+        false => None,
+    }
+}
+
+type Combine<'a> = (
+    &'a dyn Fn(&[Tile]) -> Option<Fan>,
+    &'a dyn Fn(&[Tile]) -> Option<Fan>,
+    &'a dyn Fn(Tile, Tile) -> Option<Fan>,
+);
+
+// 套算一次原則
+// a four-meld fan stands alone, a three-meld fan takes one more pair fan with
+// the last meld, otherwise at most one pair fan less than the melds involved
+fn combine(heads: &[Tile], (four, three, two): Combine) -> Vec<Fan> {
+    let n = heads.len();
+    if n == 4 {
+        if let Some(f) = four(heads) {
+            return vec![f];
+        }
+    }
+
+    let best = |fans: Vec<Vec<Fan>>| {
+        fans.into_iter()
+            .max_by_key(|f| f.iter().map(|f| f.value() as u16).sum::<u16>())
+    };
+
+    let triples: Vec<Vec<Fan>> = (0..n)
+        .flat_map(|i| (i + 1..n).flat_map(move |j| (j + 1..n).map(move |k| [i, j, k])))
+        .filter_map(|[i, j, k]| {
+            let f = three(&[heads[i], heads[j], heads[k]])?;
+            let rest = (0..n).find(|x| ![i, j, k].contains(x));
+            let extra = rest.and_then(|r| {
+                [i, j, k]
+                    .iter()
+                    .filter_map(|x| two(heads[*x], heads[r]))
+                    .max_by_key(|f| f.value())
+            });
+            Some(std::iter::once(f).chain(extra).collect())
+        })
+        .collect();
+    if let Some(fans) = best(triples) {
+        return fans;
+    }
+
+    let pairs: Vec<(usize, usize, Fan)> = (0..n)
+        .flat_map(|i| (i + 1..n).map(move |j| (i, j)))
+        .filter_map(|(i, j)| Some((i, j, two(heads[i], heads[j])?)))
+        .collect();
+    let related = (0..n)
+        .filter(|x| pairs.iter().any(|(i, j, _)| i == x || j == x))
+        .count();
+    let mut fans: Vec<Fan> = pairs.into_iter().map(|(_, _, f)| f).collect();
+    fans.sort_by_key(|f| (std::cmp::Reverse(f.value()), *f as usize));
+    fans.truncate(related.saturating_sub(1));
+    fans
+}
+
+#[allow(dead_code)]
+impl McrWin {
+    pub fn new(concealed: &[Tile], called: &[Meld], winning: Tile, seat: Tile) -> Self {
+        Self {
+            concealed: concealed.to_vec(),
+            called: called.to_vec(),
+            winning,
+            self_drawn: false,
+            seat,
+            prevalent: Tile::East,
+            flowers: 0,
+            last_tile: false,
+            last_draw: false,
+            replacement: false,
+            robbing: false,
+        }
+    }
+
+    pub fn is_concealed(&self) -> bool {
+        self.called
+            .iter()
+            .all(|m| m.kind == MeldKind::ConcealedKong)
+    }
+
+    // every tile in hand, kongs with four
+    fn tiles(&self) -> Vec<Tile> {
+        self.concealed
+            .iter()
+            .cloned()
+            .chain(self.called.iter().flat_map(meld_tiles))
+            .collect()
+    }
+
+    fn validate(&self) -> Result<(), Box<dyn Error>> {
+        if self.called.len() > SETNUM {
+            Err("Too many called melds")?
+        }
+        if self.concealed.len() != HAINUM - 3 * self.called.len() {
+            Err("Wrong number of concealed tiles")?
+        }
+        if !self.concealed.contains(&self.winning) {
+            Err("Winning tile not in hand")?
+        }
+        if !self.seat.is_wind() || !self.prevalent.is_wind() {
+            Err("Seat and prevalent must be winds")?
+        }
+        if self
+            .called
+            .iter()
+            .any(|m| m.kind.is_chow() && (m.head.is_honor() || m.head.number() > 7))
+        {
+            Err("Invalid chow")?
+        }
+        if self
+            .called
+            .iter()
+            .any(|m| m.kind.is_concealed() && !m.kind.is_kong())
+        {
+            Err("Called melds are revealed or concealed kongs")?
+        }
+        if self.tiles().iter().any(|t| t.is_bonus()) {
+            Err("Flowers are counted in `flowers`, not in the hand")?
+        }
+        let mut counts = [0u8; TILEVARIANT];
+        self.tiles().iter().for_each(|t| counts[*t as usize] += 1);
+        if counts.iter().any(|c| *c > 4) {
+            Err("More than four copies of a tile")?
+        }
+        if self.flowers > 8 {
+            Err("At most eight flowers")?
+        }
+        Ok(())
+    }
+
+    // every way the concealed tiles and called melds complete a hand
+    fn forms(&self, concealed: &[Tile]) -> Vec<Form> {
+        let mut counts = [0u8; TILEVARIANT];
+        concealed.iter().for_each(|t| counts[*t as usize] += 1);
+        let mut forms = Vec::new();
+
+        if self.called.is_empty() {
+            // 十三么
+            let orphans = (0..TILEVARIANT).filter(|i| !tile(*i).is_simple());
+            if orphans.clone().all(|i| counts[i] >= 1)
+                && orphans.map(|i| counts[i] as usize).sum::<usize>() == HAINUM
+            {
+                forms.push(Form::ThirteenOrphans);
+            }
+            // 七對, four of a kind as two pairs
+            if counts.iter().all(|c| c % 2 == 0) {
+                forms.push(Form::SevenPairs);
+            }
+            // 全不靠
+            if counts.iter().all(|c| *c <= 1)
+                && knitted().iter().any(|k| {
+                    (0..TILEVARIANT)
+                        .filter(|i| counts[*i] == 1)
+                        .all(|i| tile(i).is_honor() || k.contains(&tile(i)))
+                })
+            {
+                forms.push(Form::HonorsAndKnitted);
+            }
+        }
+
+        // 組合龍, nine knitted tiles with one meld and a pair
+        if self.called.len() <= 1 {
+            for k in knitted()
+                .iter()
+                .filter(|k| k.iter().all(|t| counts[*t as usize] > 0))
+            {
+                let mut rest = counts;
+                k.iter().for_each(|t| rest[*t as usize] -= 1);
+                for p in (0..TILEVARIANT).filter(|i| rest[*i] >= 2) {
+                    let mut rest = rest;
+                    rest[p] -= 2;
+                    let meld = match self.called.first() {
+                        Some(m) => Some(*m),
+                        None => {
+                            let i = (0..TILEVARIANT).find(|i| rest[*i] > 0).unwrap();
+                            match (rest[i], rest.get(i + 1), rest.get(i + 2)) {
+                                (3, _, _) => Some(Meld::new(tile(i), MeldKind::ConcealedPung)),
+                                (1, Some(1), Some(1))
+                                    if !tile(i).is_honor() && tile(i).number() <= 7 =>
+                                {
+                                    Some(Meld::new(tile(i), MeldKind::ConcealedChow))
+                                }
+                                _ => None,
+                            }
+                        }
+                    };
+                    if let Some(meld) = meld {
+                        forms.push(Form::KnittedStraight {
+                            pair: tile(p),
+                            meld,
+                        });
+                    }
+                }
+            }
+        }
+
+        // 基本和型, called melds put back as tiles for allsets
+        let raw: Vec<u8> = concealed
+            .iter()
+            .cloned()
+            .chain(
+                self.called
+                    .iter()
+                    .flat_map(|m| meld_tiles(m).into_iter().take(3)),
+            )
+            .map(|t| t as u8 + b'A')
+            .collect();
+        if raw.len() == HAINUM {
+            let mut sets = allsets(&raw);
+            sets.dedup();
+            for s in sets.into_iter() {
+                let mut rest: Vec<Meld> = s.melds().to_vec();
+                let found = self.called.iter().all(|c| {
+                    match rest
+                        .iter()
+                        .position(|m| m.head == c.head && m.kind.is_chow() == c.kind.is_chow())
+                    {
+                        Some(i) => {
+                            rest.remove(i);
+                            true
+                        }
+                        None => false,
+                    }
+                });
+                if found {
+                    forms.push(Form::Standard {
+                        pair: s.pair(),
+                        melds: rest
+                            .into_iter()
+                            .chain(self.called.iter().cloned())
+                            .collect(),
+                    });
+                }
+            }
+        }
+        forms
+    }
+
+    // number of tiles the hand was waiting on
+    fn waits(&self) -> usize {
+        let mut hand = self.concealed.clone();
+        let i = hand.iter().position(|t| *t == self.winning).unwrap();
+        hand.remove(i);
+        let tiles = self.tiles();
+
+        (0..TILEVARIANT)
+            .map(tile)
+            .filter(|t| {
+                tiles.iter().filter(|x| *x == t).count() - ((*t == self.winning) as usize) < 4
+            })
+            .filter(|t| {
+                let mut h = hand.clone();
+                h.push(*t);
+                !self.forms(&h).is_empty()
+            })
+            .count()
+    }
+
+    pub fn score(&self) -> Result<McrScore, Box<dyn Error>> {
+        self.validate()?;
+        let forms = self.forms(&self.concealed);
+        if forms.is_empty() {
+            Err("Not a winning hand")?
+        }
+        let unique = self.waits() == 1;
+
+        forms
+            .into_iter()
+            .flat_map(|form| {
+                self.placements(&form)
+                    .into_iter()
+                    .map(|p| {
+                        let mut table = self.fans(&form, &p, unique);
+                        table.exclude();
+                        if table.total() == 0 {
+                            table.add(ChickenHand, 1);
+                        }
+                        McrScore {
+                            form: form.clone(),
+                            fans: table.list(),
+                            total: table.total() + self.flowers as u16,
+                        }
+                    })
+                    .collect::<Vec<McrScore>>()
+            })
+            .max_by_key(|s| s.total)
+            .filter(|s| s.total - self.flowers as u16 >= MINIMUM)
+            .ok_or_else(|| format!("Fewer than {} fan", MINIMUM).into())
+    }
+
+    fn placements(&self, form: &Form) -> Vec<Placement> {
+        let (pair, melds): (Tile, Vec<Meld>) = match form {
+            Form::Standard { pair, melds } => (*pair, melds.to_vec()),
+            Form::KnittedStraight { pair, meld } => (*pair, vec![*meld]),
+            _ => {
+                return vec![Placement {
+                    wait: None,
+                    ron_pung: None,
+                }]
+            }
+        };
+
+        let mut placements = vec![Placement {
+            wait: None,
+            ron_pung: None,
+        }];
+        if pair == self.winning {
+            placements.push(Placement {
+                wait: Some(SingleWait),
+                ron_pung: None,
+            });
+        }
+        melds
+            .iter()
+            .enumerate()
+            .filter(|(_, m)| matches!(m.kind, MeldKind::ConcealedChow | MeldKind::ConcealedPung))
+            .for_each(|(i, m)| match (m.kind.is_chow(), m.head == self.winning) {
+                (false, true) => placements.push(Placement {
+                    wait: None,
+                    ron_pung: match self.self_drawn {
+                        true => None,
+                        false => Some(i),
+                    },
+                }),
+                (true, _)
+                    if self.winning.is_same_color(m.head)
+                        && (m.head as usize..m.head as usize + 3)
+                            .contains(&(self.winning as usize)) =>
+                {
+                    let wait = match (self.winning as usize - m.head as usize, m.head.number()) {
+                        (1, _) => Some(ClosedWait),
+                        (0, 7) | (2, 1) => Some(EdgeWait),
+                        _ => None,
+                    };
+                    placements.push(Placement {
+                        wait,
+                        ron_pung: None,
+                    });
+                }
+                _ => (),
+            });
+
+        // a standard hand always holds the winning tile in its pair or a meld,
+        // 組合龍 may hold it among the knitted tiles
+        match form {
+            Form::Standard { .. } => placements.into_iter().skip(1).collect(),
+            _ => placements,
+        }
+    }
+
+    fn fans(&self, form: &Form, p: &Placement, unique: bool) -> FanTable {
+        let mut table = FanTable::new();
+        let tiles = self.tiles();
+
+        match form {
+            Form::Standard { pair, melds } => self.meld_fans(&mut table, *pair, melds, p),
+            Form::KnittedStraight { pair, meld } => {
+                table.add(KnittedStraight, 1);
+                if meld.kind.is_chow() && !pair.is_honor() {
+                    table.add(AllChows, 1);
+                }
+                self.pung_fans(&mut table, *pair, &[*meld], p);
+            }
+            Form::SevenPairs => {
+                let mut pairs: Vec<Tile> = self.concealed.clone();
+                pairs.sort();
+                pairs.dedup();
+                match pairs.len() == 7 && pairs.windows(2).all(|w| w[0].is_ascending(w[1])) {
+                    true => table.add(SevenShiftedPairs, 1),
+                    false => table.add(SevenPairs, 1),
+                }
+            }
+            Form::ThirteenOrphans => table.add(ThirteenOrphans, 1),
+            Form::HonorsAndKnitted => {
+                match tiles.iter().filter(|t| t.is_honor()).count() {
+                    7 => table.add(GreaterHonorsAndKnittedTiles, 1),
+                    _ => table.add(LesserHonorsAndKnittedTiles, 1),
+                }
+                if knitted()
+                    .iter()
+                    .any(|k| k.iter().all(|t| tiles.contains(t)))
+                {
+                    table.add(KnittedStraight, 1);
+                }
+            }
+        }
+
+        self.tile_fans(&mut table, &tiles);
+
+        // 門前清、不求人、全求人
+        match (self.is_concealed(), self.self_drawn) {
+            (true, true) => table.add(FullyConcealedHand, 1),
+            (true, false) => table.add(ConcealedHand, 1),
+            _ => (),
+        }
+        if self.called.len() == SETNUM
+            && self.called.iter().all(|m| m.kind.is_revealed())
+            && !self.self_drawn
+        {
+            table.add(MeldedHand, 1);
+        }
+        if self.self_drawn {
+            table.add(SelfDrawn, 1);
+        }
+        match (self.last_draw, self.self_drawn) {
+            (true, true) => table.add(LastTileDraw, 1),
+            (true, false) => table.add(LastTileClaim, 1),
+            _ => (),
+        }
+        if self.replacement && self.self_drawn {
+            table.add(OutWithReplacementTile, 1);
+        }
+        if self.robbing && !self.self_drawn {
+            table.add(RobbingTheKong, 1);
+        }
+        if self.last_tile {
+            table.add(LastTile, 1);
+        }
+        if let (true, Some(w)) = (unique, p.wait) {
+            table.add(w, 1);
+        }
+        table.add(FlowerTiles, self.flowers);
+        table
+    }
+
+    // fans read from tiles alone
+    fn tile_fans(&self, table: &mut FanTable, tiles: &[Tile]) {
+        let numbers: Vec<usize> = tiles
+            .iter()
+            .filter(|t| !t.is_honor())
+            .map(|t| t.number())
+            .collect();
+        let honors = tiles.iter().any(|t| t.is_honor());
+        let all_numbers = |f: &dyn Fn(usize) -> bool| !honors && numbers.iter().all(|n| f(*n));
+
+        if tiles.iter().all(|t| {
+            matches!(
+                t,
+                Tile::B2 | Tile::B3 | Tile::B4 | Tile::B6 | Tile::B8 | Tile::Green
+            )
+        }) {
+            table.add(AllGreen, 1);
+        }
+        if tiles.iter().all(|t| {
+            matches!(
+                t,
+                Tile::D1
+                    | Tile::D2
+                    | Tile::D3
+                    | Tile::D4
+                    | Tile::D5
+                    | Tile::D8
+                    | Tile::D9
+                    | Tile::B2
+                    | Tile::B4
+                    | Tile::B5
+                    | Tile::B6
+                    | Tile::B8
+                    | Tile::B9
+                    | Tile::White
+            )
+        }) {
+            table.add(ReversibleTiles, 1);
+        }
+
+        // 九蓮寶燈, 1112345678999 before the winning tile
+        if self.called.is_empty() && !honors && tiles.iter().all(|t| t.is_same_color(tiles[0])) {
+            let mut shape = [0u8; 9];
+            tiles.iter().for_each(|t| shape[t.number() - 1] += 1);
+            shape[self.winning.number() - 1] -= 1;
+            if shape == [3, 1, 1, 1, 1, 1, 1, 1, 3] {
+                table.add(NineGates, 1);
+            }
+        }
+
+        match (tiles.iter().all(|t| t.is_terminal()), honors) {
+            _ if tiles.iter().all(|t| t.is_honor()) => table.add(AllHonors, 1),
+            (true, _) => table.add(AllTerminals, 1),
+            _ if tiles.iter().all(|t| !t.is_simple()) => table.add(AllTerminalsAndHonors, 1),
+            _ => (),
+        }
+
+        let mut suits = [false; 3];
+        tiles
+            .iter()
+            .filter_map(|t| suit_index(*t))
+            .for_each(|s| suits[s] = true);
+        match (suits.iter().filter(|s| **s).count(), honors) {
+            (1, false) => table.add(FullFlush, 1),
+            (1, true) => table.add(HalfFlush, 1),
+            (2, _) => table.add(OneVoidedSuit, 1),
+            _ => (),
+        }
+        if !honors {
+            table.add(NoHonors, 1);
+        }
+        if tiles.iter().all(|t| t.is_simple()) {
+            table.add(AllSimples, 1);
+        }
+        if suits.iter().all(|s| *s)
+            && tiles.iter().any(|t| t.is_wind())
+            && tiles.iter().any(|t| t.is_dragon())
+        {
+            table.add(AllTypes, 1);
+        }
+
+        if all_numbers(&|n| n >= 7) {
+            table.add(UpperTiles, 1);
+        } else if all_numbers(&|n| (4..=6).contains(&n)) {
+            table.add(MiddleTiles, 1);
+        } else if all_numbers(&|n| n <= 3) {
+            table.add(LowerTiles, 1);
+        } else if all_numbers(&|n| n >= 6) {
+            table.add(UpperFour, 1);
+        } else if all_numbers(&|n| n <= 4) {
+            table.add(LowerFour, 1);
+        }
+
+        // 四歸一, kongs do not count
+        let hogs = (0..TILEVARIANT)
+            .map(tile)
+            .filter(|t| tiles.iter().filter(|x| *x == t).count() == 4)
+            .filter(|t| !self.called.iter().any(|m| m.kind.is_kong() && m.head == *t))
+            .count();
+        table.add(TileHog, hogs as u8);
+    }
+
+    fn meld_fans(&self, table: &mut FanTable, pair: Tile, melds: &[Meld], p: &Placement) {
+        let chows: Vec<Tile> = melds
+            .iter()
+            .filter(|m| m.kind.is_chow())
+            .map(|m| m.head)
+            .collect();
+        let pungs: Vec<Tile> = melds
+            .iter()
+            .filter(|m| m.kind.is_pung() && !m.head.is_honor())
+            .map(|m| m.head)
+            .collect();
+
+        // 雙龍會
+        let mut sorted = chows.clone();
+        sorted.sort();
+        let terminal_chows = |s: usize| {
+            let b = [Tile::B1, Tile::C1, Tile::D1][s] as usize;
+            (
+                chows.iter().filter(|t| **t as usize == b).count(),
+                chows.iter().filter(|t| **t as usize == b + 6).count(),
+            )
+        };
+        let dragon_pair = (0..3).find(|s| {
+            terminal_chows(*s) == (2, 2)
+                && pair as usize == [Tile::B5, Tile::C5, Tile::D5][*s] as usize
+        });
+        let suited_pair = suit_index(pair).filter(|s| {
+            pair.number() == 5
+                && (0..3)
+                    .filter(|x| x != s)
+                    .all(|x| terminal_chows(x) == (1, 1))
+        });
+        match (dragon_pair, suited_pair) {
+            (Some(_), _) => table.add(PureTerminalChows, 1),
+            (_, Some(_)) => table.add(ThreeSuitedTerminalChows, 1),
+            _ => combine(&chows, (&four_chows, &three_chows, &two_chows))
+                .into_iter()
+                .for_each(|f| table.add(f, 1)),
+        }
+        combine(&pungs, (&four_pungs, &three_pungs, &two_pungs))
+            .into_iter()
+            .for_each(|f| table.add(f, 1));
+
+        if chows.len() == SETNUM && !pair.is_honor() {
+            table.add(AllChows, 1);
+        }
+        if melds.iter().all(|m| m.kind.is_pung()) {
+            table.add(AllPungs, 1);
+        }
+        if !pair.is_simple()
+            && melds.iter().all(|m| match m.kind.is_chow() {
+                true => matches!(m.head.number(), 1 | 7),
+                false => !m.head.is_simple(),
+            })
+        {
+            table.add(OutsideHand, 1);
+        }
+        if pair.number() == 5
+            && !pair.is_honor()
+            && melds.iter().all(|m| {
+                !m.head.is_honor()
+                    && match m.kind.is_chow() {
+                        true => (3..=5).contains(&m.head.number()),
+                        false => m.head.number() == 5,
+                    }
+            })
+        {
+            table.add(AllFives, 1);
+        }
+        if !pair.is_honor()
+            && pair.number().is_multiple_of(2)
+            && melds.iter().all(|m| {
+                m.kind.is_pung() && !m.head.is_honor() && m.head.number().is_multiple_of(2)
+            })
+        {
+            table.add(AllEvenPungs, 1);
+        }
+
+        self.pung_fans(table, pair, melds, p);
+    }
+
+    // 刻子、槓子 and honors
+    fn pung_fans(&self, table: &mut FanTable, pair: Tile, melds: &[Meld], p: &Placement) {
+        let pungs: Vec<(usize, &Meld)> = melds
+            .iter()
+            .enumerate()
+            .filter(|(_, m)| m.kind.is_pung())
+            .collect();
+
+        let dragons = pungs.iter().filter(|(_, m)| m.head.is_dragon()).count();
+        match (dragons, pair.is_dragon()) {
+            (3, _) => table.add(BigThreeDragons, 1),
+            (2, true) => table.add(LittleThreeDragons, 1),
+            (2, false) => table.add(TwoDragonPungs, 1),
+            (1, _) => table.add(DragonPung, 1),
+            _ => (),
+        }
+        let winds = pungs.iter().filter(|(_, m)| m.head.is_wind()).count();
+        match (winds, pair.is_wind()) {
+            (4, _) => table.add(BigFourWinds, 1),
+            (3, true) => table.add(LittleFourWinds, 1),
+            (3, false) => table.add(BigThreeWinds, 1),
+            _ => (),
+        }
+        if pungs.iter().any(|(_, m)| m.head == self.prevalent) {
+            table.add(PrevalentWind, 1);
+        }
+        if pungs.iter().any(|(_, m)| m.head == self.seat) {
+            table.add(SeatWind, 1);
+        }
+        // 么九刻, winds already counted as 風刻 are left out
+        let terminal_pungs = pungs
+            .iter()
+            .filter(|(_, m)| match m.head.is_wind() {
+                true => winds < 3 && m.head != self.prevalent && m.head != self.seat,
+                false => m.head.is_terminal(),
+            })
+            .count();
+        table.add(PungOfTerminalsOrHonors, terminal_pungs as u8);
+
+        let concealed = pungs
+            .iter()
+            .filter(|(i, m)| m.kind.is_concealed() && p.ron_pung != Some(*i))
+            .count();
+        match concealed {
+            4 => table.add(FourConcealedPungs, 1),
+            3 => table.add(ThreeConcealedPungs, 1),
+            2 => table.add(TwoConcealedPungs, 1),
+            _ => (),
+        }
+
+        let kongs = pungs.iter().filter(|(_, m)| m.kind.is_kong()).count();
+        let concealed_kongs = pungs
+            .iter()
+            .filter(|(_, m)| m.kind == MeldKind::ConcealedKong)
+            .count();
+        match (kongs, concealed_kongs) {
+            (4, _) => table.add(FourKongs, 1),
+            (3, _) => table.add(ThreeKongs, 1),
+            (2, 2) => table.add(TwoConcealedKongs, 1),
+            (2, 0) => table.add(TwoMeldedKongs, 1),
+            (2, 1) => {
+                table.add(ConcealedKong, 1);
+                table.add(MeldedKong, 1);
+            }
+            (1, 1) => table.add(ConcealedKong, 1),
+            (1, 0) => table.add(MeldedKong, 1),
+            _ => (),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::tile::parse_tiles;
+
+    fn win(concealed: &str, winning: &str, seat: Tile) -> McrWin {
+        McrWin::new(
+            &parse_tiles(concealed).unwrap(),
+            &[],
+            parse_tiles(winning).unwrap()[0],
+            seat,
+        )
+    }
+
+    fn fans(s: &McrScore) -> Vec<Fan> {
+        s.fans.iter().map(|(f, _)| *f).collect()
+    }
+
+    #[test]
+    fn knitted_tiles() {
+        let k = knitted();
+        assert_eq!(k.len(), 6);
+        assert_eq!(
+            k[0],
+            [
+                Tile::B1,
+                Tile::B4,
+                Tile::B7,
+                Tile::C2,
+                Tile::C5,
+                Tile::C8,
+                Tile::D3,
+                Tile::D6,
+                Tile::D9
+            ]
+        );
+    }
+
+    #[test]
+    fn chicken_hand() {
+        let called = [
+            Meld::new(Tile::C2, MeldKind::RevealedChow),
+            Meld::new(Tile::D6, MeldKind::RevealedChow),
+        ];
+        let mut w = McrWin::new(
+            &parse_tiles("345777s44z").unwrap(),
+            &called,
+            Tile::B3,
+            Tile::South,
+        );
+        let s = w.score().unwrap();
+        assert_eq!(fans(&s), vec![ChickenHand]);
+        assert_eq!(s.total, 8);
+
+        w.flowers = 2;
+        assert_eq!(w.score().unwrap().total, 10);
+
+        // 斷么 alone is not enough, flowers do not help
+        w.concealed = parse_tiles("345777s55m").unwrap();
+        assert!(w.score().is_err());
+    }
+
+    #[test]
+    fn special_forms() {
+        let mut w = win("19m19p19s12345677z", "7z", Tile::East);
+        w.self_drawn = true;
+        let s = w.score().unwrap();
+        assert_eq!(s.form, Form::ThirteenOrphans);
+        assert_eq!(fans(&s), vec![ThirteenOrphans, SelfDrawn]);
+        assert_eq!(s.total, 89);
+
+        let s = win("11223344556677p", "7p", Tile::East).score().unwrap();
+        assert_eq!(fans(&s), vec![SevenShiftedPairs]);
+        assert_eq!(s.total, 88);
+
+        let s = win("147m258p3s1234567z", "3s", Tile::East).score().unwrap();
+        assert_eq!(s.form, Form::HonorsAndKnitted);
+        assert_eq!(fans(&s), vec![GreaterHonorsAndKnittedTiles]);
+
+        // 組合龍 with 234s and 55m, a single wait
+        let s = win("147m258p369s234s55m", "5m", Tile::East)
+            .score()
+            .unwrap();
+        assert_eq!(
+            fans(&s),
+            vec![KnittedStraight, ConcealedHand, AllChows, SingleWait]
+        );
+        assert_eq!(s.total, 17);
+    }
+
+    #[test]
+    fn non_repeat() {
+        // 大四喜 drops 碰碰和 and the wind pungs, 四暗刻 drops 門前清 only,
+        // 不求人 stays and covers 自摸
+        let mut w = win("111222333444z55m", "5m", Tile::East);
+        w.self_drawn = true;
+        let s = w.score().unwrap();
+        assert_eq!(
+            fans(&s),
+            vec![
+                BigFourWinds,
+                FourConcealedPungs,
+                HalfFlush,
+                FullyConcealedHand,
+                SingleWait
+            ]
+        );
+        assert_eq!(s.total, 163);
+
+        // 清龍 with 喜相逢 counted once, 坎張 as the only wait
+        let s = win("123456789s123m99p", "5s", Tile::East).score().unwrap();
+        assert_eq!(
+            fans(&s),
+            vec![
+                PureStraight,
+                ConcealedHand,
+                AllChows,
+                MixedDoubleChow,
+                ClosedWait
+            ]
+        );
+        assert_eq!(s.total, 22);
+
+        // 三同刻 covers 雙同刻, the discard completes a chow so pungs stay concealed
+        let s = win("222m222p222s456s88p", "4s", Tile::East)
+            .score()
+            .unwrap();
+        assert_eq!(
+            fans(&s),
+            vec![TriplePung, ThreeConcealedPungs, ConcealedHand, AllSimples]
+        );
+        assert_eq!(s.total, 36);
+    }
+
+    #[test]
+    fn chow_combinations() {
+        let c = |s: &str| parse_tiles(s).unwrap();
+        let f = (
+            &four_chows as &dyn Fn(&[Tile]) -> Option<Fan>,
+            &three_chows as &dyn Fn(&[Tile]) -> Option<Fan>,
+            &two_chows as &dyn Fn(Tile, Tile) -> Option<Fan>,
+        );
+        assert_eq!(combine(&c("1357s"), f), vec![FourPureShiftedChows]);
+        assert_eq!(
+            combine(&c("11s1m"), f),
+            vec![PureDoubleChow, MixedDoubleChow]
+        );
+        // 一般高 twice and 連六 once, one fewer than the four chows
+        assert_eq!(
+            combine(&c("1144s"), f),
+            vec![PureDoubleChow, PureDoubleChow, ShortStraight]
+        );
+        assert_eq!(
+            combine(&c("147s1m"), f),
+            vec![PureStraight, MixedDoubleChow]
+        );
+    }
+
+    #[test]
+    fn rejects_bad_hands() {
+        assert!(win("123456789s123m9p", "9p", Tile::East).score().is_err());
+        assert!(win("123456789s123m99p", "1p", Tile::East).score().is_err());
+        assert!(win("123456789s123m99p", "5s", Tile::Red).score().is_err());
+        assert!(win("11111345678999s", "9s", Tile::East).score().is_err());
+    }
+}
@@ -0,0 +1,28 @@
+use std::io::Read;
+
+use mcr::McrWin;
+
+mod context;
+mod decompose;
+mod hand;
+mod handchecker;
+mod mcr;
+mod set;
+mod tile;
+
+// score a Chinese Official hand given as JSON, from a file or stdin
+// usage: mcr [hand.json]
+// {"concealed":["B3",...],"called":[{"head":"C2","kind":"RevealedChow"}],
+//  "winning":"B3","seat":"South","prevalent":"East","self_drawn":false,"flowers":1}
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let mut input = String::new();
+    match std::env::args().nth(1) {
+        Some(filename) => std::fs::File::open(filename)?.read_to_string(&mut input)?,
+        None => std::io::stdin().read_to_string(&mut input)?,
+    };
+
+    let win: McrWin = serde_json::from_str(&input)?;
+    println!("{}", serde_json::to_string_pretty(&win.score()?)?);
+
+    Ok(())
+}
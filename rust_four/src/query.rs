@@ -0,0 +1,348 @@
+use std::{error::Error, fs::File, io::BufReader};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    context::WinContext,
+    decompose::{allsets, comb_available},
+    hand::{Hand, HANDVARIANT},
+    handchecker::{HandChecker, HandList},
+    set::{Meld, MeldKind, Set},
+    tile::{available, Tile, TileColor, TILEVARIANT},
+};
+
+// 牌的分類, a tile or a whole group
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum TileClass {
+    Tile(Tile),
+    Honor,
+    Wind,
+    Dragon,
+    Simple,
+    Terminal,
+    Bamboo,
+    Character,
+    Dot,
+}
+
+impl TileClass {
+    pub fn matches(&self, t: Tile) -> bool {
+        match self {
+            Self::Tile(c) => *c == t,
+            Self::Honor => t.is_honor(),
+            Self::Wind => t.is_wind(),
+            Self::Dragon => t.is_dragon(),
+            Self::Simple => t.is_simple(),
+            Self::Terminal => t.is_terminal(),
+            Self::Bamboo => t.color() == TileColor::Bamboo,
+            Self::Character => t.color() == TileColor::Character,
+            Self::Dot => t.color() == TileColor::Dot,
+        }
+    }
+}
+
+// 查詢條件, written as JSON like {"All": [{"Pair": "Dragon"}, {"RevealedMelds": 2}]}
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum Predicate {
+    All(Vec<Predicate>),
+    Any(Vec<Predicate>),
+    Not(Box<Predicate>),
+    Pair(TileClass),
+    Contains(TileClass),  // some tile of the pattern
+    Only(TileClass),      // every tile of the pattern
+    MeldWith(TileClass),  // some meld holding such a tile
+    Chows(usize),         // exactly n chows
+    Pungs(usize),         // exactly n pungs or kongs
+    RevealedMelds(usize), // exactly n revealed melds
+    Open(bool),           // any revealed meld
+    Hand(Hand),           // the hand is awarded
+    Score { min: u16, max: u16 },
+}
+
+// one decomposition of a pattern and its hands
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Record<'a> {
+    pub raw: &'a [u8],
+    pub checker: HandChecker,
+    pub list: HandList,
+    pub combination: u64,
+}
+
+fn meld_tiles(m: &Meld) -> Vec<Tile> {
+    match m.kind.is_chow() {
+        true => (0..3)
+            .map(|i| Tile::try_from(m.head as usize + i).unwrap())
+            .collect(),
+        false => vec![m.head],
+    }
+}
+
+#[allow(dead_code)]
+impl Predicate {
+    pub fn matches(&self, r: &Record) -> bool {
+        let melds = r.checker.melds();
+        match self {
+            Self::All(p) => p.iter().all(|p| p.matches(r)),
+            Self::Any(p) => p.iter().any(|p| p.matches(r)),
+            Self::Not(p) => !p.matches(r),
+            Self::Pair(c) => c.matches(r.checker.pair()),
+            Self::Contains(c) => r.raw.iter().any(|t| c.matches(tile(*t))),
+            Self::Only(c) => r.raw.iter().all(|t| c.matches(tile(*t))),
+            Self::MeldWith(c) => melds
+                .iter()
+                .any(|m| meld_tiles(m).into_iter().any(|t| c.matches(t))),
+            Self::Chows(n) => melds.iter().filter(|m| m.kind.is_chow()).count() == *n,
+            Self::Pungs(n) => melds.iter().filter(|m| m.kind.is_pung()).count() == *n,
+            Self::RevealedMelds(n) => melds.iter().filter(|m| m.kind.is_revealed()).count() == *n,
+            Self::Open(b) => melds.iter().any(|m| m.kind.is_revealed()) == *b,
+            Self::Hand(h) => r.list[*h as usize],
+            Self::Score { min, max } => (*min..=*max).contains(&r.list.score()),
+        }
+    }
+}
+
+fn tile(raw: u8) -> Tile {
+    Tile::try_from(raw as char).unwrap()
+}
+
+fn reveal(kind: MeldKind) -> MeldKind {
+    match kind {
+        MeldKind::ConcealedChow => MeldKind::RevealedChow,
+        MeldKind::ConcealedPung => MeldKind::RevealedPung,
+        MeldKind::ConcealedKong => MeldKind::RevealedKong,
+        k => k,
+    }
+}
+
+// every choice of revealed melds, patterns are read as all concealed
+pub fn openings(checker: &HandChecker) -> Vec<HandChecker> {
+    let melds = checker.melds();
+    let mut result: Vec<HandChecker> = (0..1usize << melds.len())
+        .map(|mask| {
+            let set = Set {
+                pair: checker.pair(),
+                melds: melds
+                    .iter()
+                    .enumerate()
+                    .map(|(i, m)| match mask & (1 << i) {
+                        0 => *m,
+                        _ => Meld::new(m.head, reveal(m.kind)),
+                    })
+                    .collect(),
+            };
+            let mut c = set.to_handchecker(checker.wind());
+            c.sort();
+            c
+        })
+        .collect();
+    result.sort();
+    result.dedup();
+    result
+}
+
+// 每種役的和牌形、組合數、總分, same table as agari
+#[derive(Clone, PartialEq, Debug)]
+pub struct HandTable {
+    rows: Vec<(u64, u64, u64)>,
+    records: u64,
+}
+
+#[allow(dead_code)]
+impl HandTable {
+    pub fn new() -> Self {
+        Self {
+            rows: vec![(0, 0, 0); HANDVARIANT],
+            records: 0,
+        }
+    }
+
+    pub fn add(&mut self, list: &HandList, combination: u64) {
+        self.records += 1;
+        let score = list.score() as u64;
+        (0..HANDVARIANT).filter(|i| list[*i]).for_each(|i| {
+            self.rows[i].0 += 1;
+            self.rows[i].1 += combination;
+            self.rows[i].2 += combination * score;
+        });
+    }
+
+    // matching decompositions
+    pub fn records(&self) -> u64 {
+        self.records
+    }
+
+    // 役種、和牌形、組合數、平均分數, 役牌 merged as in agari
+    pub fn rows(&self) -> Vec<(Hand, u64, u64, f64)> {
+        let mut rows = self.rows.clone();
+        for i in 1..=3 {
+            rows[4].0 += rows[i].0;
+            rows[4].1 += rows[i].1;
+            rows[4].2 += rows[i].2;
+        }
+        rows.into_iter()
+            .enumerate()
+            .filter(|(i, _)| !matches!(i, 1..=3))
+            .map(|(i, (p, c, s))| (Hand::try_from(i).unwrap(), p, c, s as f64 / c as f64))
+            .collect()
+    }
+}
+
+// 查詢, the filter and whether to try every choice of revealed melds
+// visible tiles in pattern file letters are out of the wall
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub struct Query {
+    pub filter: Predicate,
+    #[serde(default)]
+    pub reveal: bool,
+    #[serde(default)]
+    pub context: WinContext,
+    #[serde(default)]
+    pub visible: String,
+}
+
+#[allow(dead_code)]
+impl Query {
+    pub fn from_file(filename: &str) -> Result<Self, Box<dyn Error>> {
+        let reader = BufReader::new(File::open(filename)?);
+        let query: Self = serde_json::from_reader(reader)?;
+        query.context.validate()?;
+        available(query.visible.as_bytes())?;
+        Ok(query)
+    }
+
+    // records of one pattern passing the filter, with every tile in the wall
+    pub fn records<'a>(&self, raw: &'a [u8]) -> Vec<Record<'a>> {
+        self.records_available(raw, &[4; TILEVARIANT])
+    }
+
+    // none when the pattern needs more copies than are left
+    pub fn records_available<'a>(
+        &self,
+        raw: &'a [u8],
+        available: &[u64; TILEVARIANT],
+    ) -> Vec<Record<'a>> {
+        let combination = comb_available(raw, available);
+        if combination == 0 {
+            return Vec::new();
+        }
+        let mut sets = allsets(raw);
+        sets.dedup();
+        sets.iter()
+            .flat_map(|s| match self.reveal {
+                true => openings(s),
+                false => vec![s.clone()],
+            })
+            .map(|checker| Record {
+                raw,
+                list: checker.hands(&self.context),
+                checker,
+                combination,
+            })
+            .filter(|r| self.filter.matches(r))
+            .collect()
+    }
+
+    pub fn run<T: AsRef<[u8]>>(&self, patterns: &[T]) -> Result<HandTable, Box<dyn Error>> {
+        let available = available(self.visible.as_bytes())?;
+        let mut table = HandTable::new();
+        patterns.iter().for_each(|raw| {
+            self.records_available(raw.as_ref(), &available)
+                .iter()
+                .for_each(|r| table.add(&r.list, r.combination))
+        });
+        Ok(table)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::decompose::comb;
+
+    const FLUSH: &[u8; 14] = b"LLLMMMNNNOOOPP";
+    const DRAGONS: &[u8; 14] = b"AAABBBHHHIIJJJ";
+
+    fn query(filter: Predicate) -> Query {
+        Query {
+            filter,
+            reveal: false,
+            context: WinContext::default(),
+            visible: String::new(),
+        }
+    }
+
+    #[test]
+    fn filters_records() {
+        let all = query(Predicate::All(vec![]));
+        assert!(!all.records(FLUSH).is_empty());
+
+        let bamboo_flush = query(Predicate::All(vec![
+            Predicate::Hand(Hand::FullFlush),
+            Predicate::Only(TileClass::Bamboo),
+        ]));
+        let dot_flush = query(Predicate::All(vec![
+            Predicate::Hand(Hand::FullFlush),
+            Predicate::Only(TileClass::Dot),
+        ]));
+        let (b, d) = (
+            bamboo_flush.records(FLUSH).len(),
+            dot_flush.records(FLUSH).len(),
+        );
+        assert_eq!(b, all.records(FLUSH).len());
+        assert_eq!(d, 0);
+
+        let dragon_pair = query(Predicate::Pair(TileClass::Dragon));
+        assert!(dragon_pair.records(FLUSH).is_empty());
+        assert!(
+            query(Predicate::Not(Box::new(Predicate::Pair(TileClass::Dragon))))
+                .records(FLUSH)
+                .len()
+                == all.records(FLUSH).len()
+        );
+        assert_eq!(
+            query(Predicate::MeldWith(TileClass::Dragon))
+                .records(DRAGONS)
+                .len(),
+            all.records(DRAGONS).len()
+        );
+    }
+
+    #[test]
+    fn revealed_melds() {
+        let mut q = query(Predicate::RevealedMelds(2));
+        assert!(q.records(DRAGONS).is_empty());
+        q.reveal = true;
+        let records = q.records(DRAGONS);
+        // 4 pungs, C(4, 2) ways to reveal two of them
+        assert_eq!(records.len(), 6);
+        assert!(records
+            .iter()
+            .all(|r| !r.list[Hand::FourConcealedPungs as usize]));
+        assert!(records
+            .iter()
+            .all(|r| r.list[Hand::TwoConcealedPungs as usize]));
+
+        let checker = &allsets(DRAGONS)[0];
+        assert_eq!(openings(checker).len(), 16);
+    }
+
+    #[test]
+    fn table_of_subset() {
+        let q = query(Predicate::Hand(Hand::AllPungs));
+        let table = q.run(&[DRAGONS, FLUSH]).unwrap();
+        let rows = table.rows();
+        let all_pungs = rows.iter().find(|r| r.0 == Hand::AllPungs).unwrap();
+        assert_eq!(all_pungs.1, table.records());
+        assert_eq!(all_pungs.2, comb(DRAGONS) + comb(FLUSH));
+        assert!(rows.iter().all(|r| r.1 <= table.records()));
+
+        // two 中 seen leave one, too few for the pung
+        let mut q = q;
+        q.visible = "AA".to_string();
+        let table = q.run(&[DRAGONS, FLUSH]).unwrap();
+        assert_eq!(table.records(), 1);
+        let rows = table.rows();
+        let all_pungs = rows.iter().find(|r| r.0 == Hand::AllPungs).unwrap();
+        assert_eq!(all_pungs.2, comb(FLUSH));
+    }
+}
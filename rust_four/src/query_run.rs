@@ -0,0 +1,54 @@
+use std::{error::Error, time::Instant};
+
+use rust_common::pattern::read_patterns;
+
+use crate::{
+    pattern::{HAINUM, VARIANT},
+    query::Query,
+};
+
+mod context;
+mod decompose;
+mod hand;
+mod handchecker;
+mod pattern;
+mod query;
+mod set;
+mod tile;
+
+// per-hand table for the decompositions matching a query, four-player patterns only
+// usage: query query.json
+// {"filter":{"All":[{"Pair":"Dragon"},{"RevealedMelds":2}]},"reveal":true,"visible":"AAH"}
+fn main() -> Result<(), Box<dyn Error>> {
+    let filename = std::env::args().nth(1).ok_or("usage: query query.json")?;
+    let query = Query::from_file(&filename)?;
+
+    let start = Instant::now();
+    let (header, raw_hai_sets) = read_patterns::<HAINUM>("patterns_general_four.dat", VARIANT)?;
+    assert_eq!(raw_hai_sets.len() as u64, header.records);
+    let table = query.run(&raw_hai_sets)?;
+    println!(
+        "time query {} patterns in {:.2} s",
+        raw_hai_sets.len(),
+        start.elapsed().as_secs_f32()
+    );
+    println!("{} matching decompositions", table.records());
+    println!();
+
+    println!("{:4}{:8}{:21}{:4}", "役種", "和牌形", "組合數", "平均分數");
+    table
+        .rows()
+        .into_iter()
+        .filter(|(_, pattern, _, _)| *pattern > 0)
+        .for_each(|(hand, pattern, combination, score)| {
+            println!(
+                "{:<4}{:>8}{:>24} {:.5}",
+                hand.name(),
+                pattern,
+                combination,
+                score
+            );
+        });
+
+    Ok(())
+}
@@ -0,0 +1,311 @@
+use std::{collections::HashMap, error::Error};
+
+use rust_common::html::{HtmlReport, Metadata};
+
+use crate::{
+    context::ContextWeights,
+    cooccurrence::{top_combinations, Cooccurrence},
+    decompose::bonus_comb,
+    distribution::ScoreDistribution,
+    hand::{Hand, HANDVARIANT},
+    handchecker::HandList,
+    scoring::ScoringModel,
+    tile::Tile,
+};
+
+// remove `name value` from the arguments
+pub fn take_option(args: &mut Vec<String>, name: &str) -> Result<Option<String>, Box<dyn Error>> {
+    match args.iter().position(|a| a == name) {
+        Some(i) => {
+            let value = args
+                .get(i + 1)
+                .ok_or(format!("{} needs a value", name))?
+                .clone();
+            args.drain(i..i + 2);
+            Ok(Some(value))
+        }
+        None => Ok(None),
+    }
+}
+
+// 花牌 and 和牌狀況 hands with their weight, 花牌 out of 4^8 times the probability
+// patterns are all concealed, so 門前限定 狀況役 always apply
+pub fn classes(weights: &ContextWeights) -> Vec<(HandList, f64)> {
+    let contexts = weights.classes();
+    bonus_comb(Tile::East)
+        .into_iter()
+        .flat_map(|(b, weight)| {
+            contexts.iter().map(move |(c, p)| {
+                let mut list = b.clone();
+                *list |= **c;
+                (list, weight as f64 * p)
+            })
+        })
+        .collect()
+}
+
+// hand names for the HTML report, the key when a name is shared like 役牌
+pub fn labels() -> Vec<(String, String)> {
+    let hands: Vec<Hand> = (0..HANDVARIANT)
+        .map(|i| Hand::try_from(i).unwrap())
+        .collect();
+    hands
+        .iter()
+        .map(|h| {
+            let key = format!("{:?}", h);
+            match hands.iter().filter(|o| o.name() == h.name()).count() {
+                1 => (h.name(), key),
+                _ => (key.clone(), key),
+            }
+        })
+        .collect()
+}
+
+// agari's report from HandList to (patterns, combinations)
+// scores come from the additive model, open lists by `Hand::open_score` as in `HandList::score`
+// weights add 狀況役, minimum is 起胡, html writes the charts to a file as well
+// csv is the directory for the cooccurrence tables, not written without it
+#[allow(dead_code)]
+pub fn report(
+    hands: &HashMap<HandList, (u64, u64)>,
+    weights: &ContextWeights,
+    minimum: u16,
+    scores: &ScoringModel,
+    html: Option<(&str, Metadata)>,
+    csv: Option<&str>,
+) -> Result<(), Box<dyn Error>> {
+    println!("calculate combinations and total scores");
+    // 役種、和牌形、組合數、總分
+    // 花牌 multiply the combinations by their weight out of 4^8,
+    // 和牌狀況 by their probability
+    let mut result: Vec<(Hand, u64, f64, f64)> =
+        vec![(Hand::try_from(0).unwrap(), 0, 0.0, 0.0); HANDVARIANT];
+    result
+        .iter_mut()
+        .enumerate()
+        .for_each(|(i, (h, _, _, _))| *h = Hand::try_from(i).unwrap());
+
+    // 各計分模型: 達起和番的組合數、總支付
+    let models = [
+        scores.clone(),
+        ScoringModel::hong_kong(),
+        ScoringModel::hong_kong_doubling(),
+    ];
+    let mut payouts: Vec<Vec<(f64, f64)>> = vec![vec![(0.0, 0.0); HANDVARIANT]; models.len()];
+    let classes = classes(weights);
+    // 完整組合與共現, by combinations and by patterns, a pattern split by class weight
+    let norm: f64 = classes.iter().map(|(_, w)| w).sum();
+    let mut full: HashMap<HandList, (f64, f64)> = HashMap::new();
+    let mut by_combination = Cooccurrence::new();
+    let mut by_pattern = Cooccurrence::new();
+    // 分數分布, every hand before 起胡
+    let mut scores_by_combination = ScoreDistribution::new();
+    let mut scores_by_pattern = ScoreDistribution::new();
+    let mut page = html
+        .as_ref()
+        .map(|(_, meta)| HtmlReport::new(meta.clone(), labels()));
+
+    hands.iter().for_each(|(handlist, (pattern, combination))| {
+        // a pattern counts once for every hand some 花牌 draw or 和牌狀況 gives it
+        let mut seen = HandList::new();
+        classes.iter().for_each(|(b, weight)| {
+            let list = handlist.with_bonus(b);
+            let occurance = *pattern as f64 * weight / norm;
+            let combination = *combination as f64 * weight;
+            let score = scores.payout(&list).unwrap_or(0);
+            scores_by_combination.add(score, combination);
+            scores_by_pattern.add(score, occurance);
+            if score < minimum as u64 {
+                return;
+            }
+            if let Some(page) = page.as_mut() {
+                let held: Vec<usize> = (0..HANDVARIANT).filter(|i| list[*i]).collect();
+                page.add(&held, score, combination);
+            }
+            let score = score as f64;
+            by_combination.add(&list, combination);
+            by_pattern.add(&list, occurance);
+            let v = full.entry(list.clone()).or_insert((0.0, 0.0));
+            v.0 += occurance;
+            v.1 += combination;
+            let paid: Vec<Option<u64>> = models.iter().map(|m| m.payout(&list)).collect();
+            *seen |= *list;
+            list.into_iter()
+                .enumerate()
+                .filter(|(_, h)| *h)
+                .for_each(|(i, _)| {
+                    result[i].2 += combination;
+                    result[i].3 += combination * score;
+                    paid.iter()
+                        .enumerate()
+                        .filter_map(|(m, p)| Some((m, (*p)?)))
+                        .for_each(|(m, p)| {
+                            payouts[m][i].0 += combination;
+                            payouts[m][i].1 += combination * p as f64;
+                        });
+                });
+        });
+        seen.into_iter()
+            .enumerate()
+            .filter(|(_, h)| *h)
+            .for_each(|(i, _)| result[i].1 += *pattern);
+    });
+
+    // 役牌特殊處理
+    result[4].1 += result[1].1;
+    result[4].2 += result[1].2;
+    result[4].3 += result[1].3;
+    result[4].1 += result[2].1;
+    result[4].2 += result[2].2;
+    result[4].3 += result[2].3;
+    result[4].1 += result[3].1;
+    result[4].2 += result[3].2;
+    result[4].3 += result[3].3;
+    payouts.iter_mut().for_each(|p| {
+        for i in 1..=3 {
+            p[4].0 += p[i].0;
+            p[4].1 += p[i].1;
+        }
+    });
+
+    println!("end of process");
+    println!();
+
+    println!("{:4}{:8}{:21}{:4}", "役種", "和牌形", "組合數", "平均分數");
+    result
+        .iter()
+        .cloned()
+        .enumerate()
+        .filter_map(|(i, r)| match i {
+            1..=3 => None,
+            _ => Some(r),
+        })
+        .for_each(|(hand, pattern, combination, score)| {
+            println!(
+                "{:<4}{:>8}{:>24.0} {:>.*}",
+                hand.name(),
+                pattern,
+                combination,
+                5,
+                (score / combination).to_string()
+            );
+        });
+
+    // 平均支付只計達起和番的組合, 和了率 is their share of the hand's combinations
+    println!();
+    print!("{:4}", "役種");
+    models
+        .iter()
+        .for_each(|m| print!("{:>20}{:>8}", m.name, "和了率"));
+    println!();
+    result
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| !matches!(i, 1..=3))
+        .for_each(|(i, (hand, _, combination, _))| {
+            print!("{:<4}", hand.name());
+            payouts
+                .iter()
+                .for_each(|p| print!("{:>20.5}{:>10.4}", p[i].1 / p[i].0, p[i].0 / *combination));
+            println!();
+        });
+
+    // 分數分布, 起胡 renormalises to the winning hands
+    println!();
+    let kept = scores_by_combination.at_least(minimum as u64);
+    let scores_by_combination = scores_by_combination.with_minimum(minimum as u64);
+    let scores_by_pattern = scores_by_pattern.with_minimum(minimum as u64);
+    if minimum > 0 {
+        println!("起胡 {}: {:.5} of the combinations win", minimum, kept);
+    }
+    println!(
+        "{:>4}{:>12}{:>12}{:>12}",
+        "分數", "組合比例", "P(≥k)", "和牌形比例"
+    );
+    scores_by_combination
+        .histogram()
+        .into_iter()
+        .zip(scores_by_pattern.histogram())
+        .filter(|((k, _), _)| *k >= minimum as u64)
+        .for_each(|((k, p), (_, q))| {
+            println!(
+                "{:>6}{:>16.6}{:>12.6}{:>17.6}",
+                k,
+                p,
+                scores_by_combination.at_least(k),
+                q
+            )
+        });
+    [
+        ("組合", &scores_by_combination),
+        ("和牌形", &scores_by_pattern),
+    ]
+    .iter()
+    .for_each(|(name, d)| {
+        let percentiles: Vec<String> = [0.1, 0.25, 0.5, 0.75, 0.9, 0.99]
+            .iter()
+            .map(|p| format!("P{}={}", p * 100.0, d.percentile(*p).unwrap_or(0)))
+            .collect();
+        println!(
+            "{}: 平均 {:.5} 中位數 {} {}",
+            name,
+            d.mean(),
+            d.median().unwrap_or(0),
+            percentiles.join(" ")
+        );
+    });
+
+    // 最常見的完整役種組合
+    println!();
+    println!("{:>10}{:>10}{:>6}  役種", "組合比例", "和牌形比例", "分數");
+    top_combinations(&full, 20, |w| w.1)
+        .into_iter()
+        .for_each(|(list, (pattern, combination))| {
+            let names: Vec<String> = (0..HANDVARIANT)
+                .filter(|i| list[*i])
+                .map(|i| format!("{:?}", Hand::try_from(i).unwrap()))
+                .collect();
+            println!(
+                "{:>14.5}{:>15.5}{:>8}  {}",
+                combination / by_combination.total(),
+                pattern / by_pattern.total(),
+                scores.payout(&list).unwrap_or(0),
+                names.join(" ")
+            );
+        });
+
+    // 共現: P(A∧B), P(B|A), the pairs that almost always stack first
+    println!();
+    println!(
+        "{:24}{:24}{:>12}{:>12}{:>12}",
+        "A", "B", "P(A∧B)", "P(B|A)", "P(B|A) 形"
+    );
+    by_combination
+        .pairs()
+        .into_iter()
+        .take(30)
+        .for_each(|(a, b, joint, conditional)| {
+            println!(
+                "{:24}{:24}{:>12.5}{:>12.5}{:>12.5}",
+                format!("{:?}", a),
+                format!("{:?}", b),
+                joint,
+                conditional,
+                by_pattern.conditional(a, b).unwrap_or(0.0)
+            );
+        });
+    if let Some(dir) = csv {
+        let dir = std::path::Path::new(dir);
+        let combination = dir.join("cooccurrence_combination_four.csv");
+        let pattern = dir.join("cooccurrence_pattern_four.csv");
+        by_combination.write_csv(&combination.to_string_lossy())?;
+        by_pattern.write_csv(&pattern.to_string_lossy())?;
+        println!("cooccurrence written to {}", dir.display());
+    }
+    if let (Some((filename, _)), Some(page)) = (html, page) {
+        page.write(filename)?;
+        println!("report written to {}", filename);
+    }
+
+    Ok(())
+}
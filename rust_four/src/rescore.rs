@@ -0,0 +1,77 @@
+use std::{error::Error, time::Instant};
+
+use rust_common::html::Metadata;
+
+use crate::{
+    context::ContextWeights,
+    histogram::Histogram,
+    pattern::VARIANT,
+    report::{report, take_option},
+    scoring::ScoringModel,
+};
+
+mod context;
+mod cooccurrence;
+mod decompose;
+mod distribution;
+mod hand;
+mod handchecker;
+mod histogram;
+mod pattern;
+mod report;
+mod scoring;
+mod set;
+mod tile;
+
+// agari's report from a histogram saved by `agari --save`, no patterns read
+// usage: rescore histogram.dat [--rules rules.json] [--context context.json] [--minimum k]
+//                [--html report.html] [--csv dir]
+// rules.json replaces the score of some hands, {"AllPungs": 4, "HalfFlush": 3}
+fn main() -> Result<(), Box<dyn Error>> {
+    let mut args: Vec<String> = std::env::args().skip(1).collect();
+    let scores = match take_option(&mut args, "--rules")? {
+        Some(f) => ScoringModel::additive().with_values_file(&f)?,
+        None => ScoringModel::additive(),
+    };
+    let context = take_option(&mut args, "--context")?;
+    let weights = match context.as_deref() {
+        Some(f) => ContextWeights::from_file(f)?,
+        None => ContextWeights::default(),
+    };
+    let minimum: u16 = match take_option(&mut args, "--minimum")? {
+        Some(k) => k.parse()?,
+        None => 0,
+    };
+    let html = take_option(&mut args, "--html")?;
+    let csv = take_option(&mut args, "--csv")?;
+    let filename = args.first().ok_or(
+        "usage: rescore histogram.dat [--rules rules.json] [--context context.json] [--minimum k] [--html report.html] [--csv dir]",
+    )?;
+
+    let start = Instant::now();
+    let histogram = Histogram::read(filename)?;
+    println!(
+        "time read {} hand combinations in {:.2} ms",
+        histogram.hands.len(),
+        start.elapsed().as_secs_f64() * 1000.0
+    );
+
+    let meta = Metadata {
+        variant: VARIANT.name(),
+        rules: scores.name.clone(),
+        checksum: histogram.source,
+        extra: vec![
+            ("起胡".to_string(), minimum.to_string()),
+            ("context".to_string(), context.unwrap_or("none".to_string())),
+            ("histogram".to_string(), filename.clone()),
+        ],
+    };
+    report(
+        &histogram.hands,
+        &weights,
+        minimum,
+        &scores,
+        html.as_deref().map(|f| (f, meta)),
+        csv.as_deref(),
+    )
+}
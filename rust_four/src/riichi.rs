@@ -0,0 +1,1104 @@
+use std::error::Error;
+
+use arrayvec::ArrayVec;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    set::{Meld, MeldKind, HAINUM, SETNUM},
+    tile::{Tile, TileColor, TILEVARIANT},
+};
+
+// 立直麻將
+// han and fu on top of Tile and Meld, scored on the best decomposition
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum Yaku {
+    Riichi,         // 立直
+    DoubleRiichi,   // 兩立直
+    Ippatsu,        // 一發
+    MenzenTsumo,    // 門前清自摸和
+    Pinfu,          // 平和
+    Iipeikou,       // 一盃口
+    Ryanpeikou,     // 二盃口
+    Tanyao,         // 斷幺九
+    Haku,           // 役牌白
+    Hatsu,          // 役牌發
+    Chun,           // 役牌中
+    SeatWind,       // 自風
+    RoundWind,      // 場風
+    Haitei,         // 海底摸月
+    Houtei,         // 河底撈魚
+    Rinshan,        // 嶺上開花
+    Chankan,        // 搶槓
+    Chanta,         // 混全帶幺九
+    Junchan,        // 純全帶幺九
+    Ittsu,          // 一氣通貫
+    SanshokuDoujun, // 三色同順
+    SanshokuDoukou, // 三色同刻
+    Sankantsu,      // 三槓子
+    Toitoi,         // 對對和
+    Sanankou,       // 三暗刻
+    Shousangen,     // 小三元
+    Honroutou,      // 混老頭
+    Chiitoitsu,     // 七對子
+    Honitsu,        // 混一色
+    Chinitsu,       // 清一色
+}
+
+impl Yaku {
+    // 0 when the yaku needs a closed hand
+    pub fn han(&self, closed: bool) -> u8 {
+        match (self, closed) {
+            (&Self::Riichi, true) => 1,
+            (&Self::DoubleRiichi, true) => 2,
+            (&Self::Ippatsu, true) => 1,
+            (&Self::MenzenTsumo, true) => 1,
+            (&Self::Pinfu, true) => 1,
+            (&Self::Iipeikou, true) => 1,
+            (&Self::Ryanpeikou, true) => 3,
+            (&Self::Chiitoitsu, true) => 2,
+            (
+                &Self::Riichi
+                | &Self::DoubleRiichi
+                | &Self::Ippatsu
+                | &Self::MenzenTsumo
+                | &Self::Pinfu
+                | &Self::Iipeikou
+                | &Self::Ryanpeikou
+                | &Self::Chiitoitsu,
+                false,
+            ) => 0,
+            (&Self::Tanyao, _) => 1,
+            (&Self::Haku | &Self::Hatsu | &Self::Chun, _) => 1,
+            (&Self::SeatWind | &Self::RoundWind, _) => 1,
+            (&Self::Haitei | &Self::Houtei | &Self::Rinshan | &Self::Chankan, _) => 1,
+            // 食い下がり
+            (&Self::Chanta, true) => 2,
+            (&Self::Chanta, false) => 1,
+            (&Self::Junchan, true) => 3,
+            (&Self::Junchan, false) => 2,
+            (&Self::Ittsu, true) => 2,
+            (&Self::Ittsu, false) => 1,
+            (&Self::SanshokuDoujun, true) => 2,
+            (&Self::SanshokuDoujun, false) => 1,
+            (&Self::SanshokuDoukou, _) => 2,
+            (&Self::Sankantsu, _) => 2,
+            (&Self::Toitoi, _) => 2,
+            (&Self::Sanankou, _) => 2,
+            (&Self::Shousangen, _) => 2,
+            (&Self::Honroutou, _) => 2,
+            (&Self::Honitsu, true) => 3,
+            (&Self::Honitsu, false) => 2,
+            (&Self::Chinitsu, true) => 6,
+            (&Self::Chinitsu, false) => 5,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum Yakuman {
+    Kokushi,       // 國士無雙
+    Kokushi13,     // 國士無雙十三面
+    Suuankou,      // 四暗刻
+    SuuankouTanki, // 四暗刻單騎
+    Daisangen,     // 大三元
+    Shousuushii,   // 小四喜
+    Daisuushii,    // 大四喜
+    Tsuuiisou,     // 字一色
+    Chinroutou,    // 清老頭
+    Ryuuiisou,     // 綠一色
+    Chuuren,       // 九蓮寶燈
+    JunseiChuuren, // 純正九蓮寶燈
+    Suukantsu,     // 四槓子
+    Tenhou,        // 天和
+    Chiihou,       // 地和
+}
+
+impl Yakuman {
+    // 雙倍役滿
+    pub fn multiplier(&self) -> u8 {
+        match self {
+            &Self::Kokushi13 | &Self::SuuankouTanki | &Self::Daisuushii | &Self::JunseiChuuren => 2,
+            _ => 1,
+        }
+    }
+}
+
+// 聽牌形
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum Wait {
+    Ryanmen, // 兩面
+    Kanchan, // 嵌張
+    Penchan, // 邊張
+    Shanpon, // 雙碰
+    Tanki,   // 單騎
+}
+
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum Form {
+    Standard {
+        pair: Tile,
+        melds: ArrayVec<Meld, SETNUM>,
+        wait: Wait,
+    },
+    SevenPairs,
+    ThirteenOrphans,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum Limit {
+    None,
+    Mangan,       // 滿貫
+    Haneman,      // 跳滿
+    Baiman,       // 倍滿
+    Sanbaiman,    // 三倍滿
+    KazoeYakuman, // 累計役滿
+    Yakuman(u8),  // 役滿, times
+}
+
+// rounded up to 100 per payer
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum Payment {
+    Ron(u32),
+    DealerTsumo(u32), // each
+    Tsumo { dealer: u32, others: u32 },
+}
+
+impl Payment {
+    pub fn total(&self) -> u32 {
+        match *self {
+            Self::Ron(p) => p,
+            Self::DealerTsumo(p) => p * 3,
+            Self::Tsumo { dealer, others } => dealer + others * 2,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct RiichiWin {
+    pub concealed: Vec<Tile>, // 手牌, winning tile included
+    #[serde(default)]
+    pub called: Vec<Meld>, // 副露 and 暗槓
+    pub winning: Tile,
+    #[serde(default)]
+    pub tsumo: bool,
+    pub seat: Tile, // 自風, East is dealer
+    #[serde(default = "east")]
+    pub round: Tile, // 場風
+    #[serde(default)]
+    pub riichi: bool,
+    #[serde(default)]
+    pub double_riichi: bool,
+    #[serde(default)]
+    pub ippatsu: bool,
+    #[serde(default)]
+    pub last_tile: bool, // 海底、河底
+    #[serde(default)]
+    pub rinshan: bool,
+    #[serde(default)]
+    pub chankan: bool,
+    #[serde(default)]
+    pub blessing: bool, // 天和、地和, first uninterrupted draw
+    #[serde(default)]
+    pub dora: Vec<Tile>, // indicators
+    #[serde(default)]
+    pub ura: Vec<Tile>, // indicators, riichi only
+    #[serde(default)]
+    pub red: u8, // 赤五
+}
+
+fn east() -> Tile {
+    Tile::East
+}
+
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub struct RiichiScore {
+    pub form: Form,
+    pub yaku: Vec<(Yaku, u8)>,
+    pub yakuman: Vec<Yakuman>,
+    pub dora: u8,
+    pub ura: u8,
+    pub red: u8,
+    pub han: u8,
+    pub fu: u8,
+    pub limit: Limit,
+    pub payment: Payment,
+}
+
+// decomposed hand with the winning tile placed
+struct Shape {
+    pair: Tile,
+    melds: ArrayVec<Meld, SETNUM>,
+    wait: Wait,
+    ron_pung: Option<usize>, // 明刻 by ron, not concealed for fu and 暗刻
+}
+
+fn tile(i: usize) -> Tile {
+    Tile::try_from(i).unwrap()
+}
+
+fn meld_tiles(m: &Meld) -> ArrayVec<Tile, 4> {
+    match (m.kind.is_chow(), m.kind.is_kong()) {
+        (true, _) => (0..3).map(|i| tile(m.head as usize + i)).collect(),
+        (false, false) => (0..3).map(|_| m.head).collect(),
+        (false, true) => (0..4).map(|_| m.head).collect(),
+    }
+}
+
+// 寶牌指示牌 to 寶牌
+pub fn dora_of(indicator: Tile) -> Tile {
+    match indicator {
+        Tile::White => Tile::Green,
+        Tile::Green => Tile::Red,
+        Tile::Red => Tile::White,
+        Tile::North => Tile::East,
+        t if t.is_wind() => tile(t as usize + 1),
+        t if t.number() == 9 => tile(t as usize - 8),
+        t => tile(t as usize + 1),
+    }
+}
+
+// 面子 from concealed tiles, lowest tile first
+fn melds_of(counts: &mut [u8; TILEVARIANT], found: &mut Vec<Meld>, out: &mut Vec<Vec<Meld>>) {
+    let i = match counts.iter().position(|c| *c > 0) {
+        Some(i) => i,
+        None => {
+            out.push(found.clone());
+            return;
+        }
+    };
+    let t = tile(i);
+
+    if counts[i] >= 3 {
+        counts[i] -= 3;
+        found.push(Meld::new(t, MeldKind::ConcealedPung));
+        melds_of(counts, found, out);
+        found.pop();
+        counts[i] += 3;
+    }
+    if !t.is_honor() && t.number() <= 7 && counts[i + 1] > 0 && counts[i + 2] > 0 {
+        (i..i + 3).for_each(|j| counts[j] -= 1);
+        found.push(Meld::new(t, MeldKind::ConcealedChow));
+        melds_of(counts, found, out);
+        found.pop();
+        (i..i + 3).for_each(|j| counts[j] += 1);
+    }
+}
+
+fn is_simple_meld(m: &Meld) -> bool {
+    match m.kind.is_chow() {
+        true => (2..=6).contains(&m.head.number()),
+        false => m.head.is_simple(),
+    }
+}
+
+fn has_terminal(m: &Meld) -> bool {
+    match m.kind.is_chow() {
+        true => matches!(m.head.number(), 1 | 7),
+        false => m.head.is_terminal(),
+    }
+}
+
+fn is_mixed(c: &[&Meld]) -> bool {
+    let mut colors: ArrayVec<u8, 3> = c.iter().map(|m| m.head.color() as u8).collect();
+    colors.sort();
+    colors.as_slice()
+        == [
+            TileColor::Bamboo as u8,
+            TileColor::Character as u8,
+            TileColor::Dot as u8,
+        ]
+        && c.iter().all(|m| m.head.number() == c[0].head.number())
+}
+
+fn flush(tiles: &[Tile]) -> Option<Yaku> {
+    let color = tiles.iter().find(|t| !t.is_honor())?;
+    match (
+        tiles
+            .iter()
+            .all(|t| t.is_honor() || t.is_same_color(*color)),
+        tiles.iter().any(|t| t.is_honor()),
+    ) {
+        (true, true) => Some(Yaku::Honitsu),
+        (true, false) => Some(Yaku::Chinitsu),
+        _ => None,
+    }
+}
+
+// 符 rounded to 10 for everything but 七對子
+fn base_points(han: u8, fu: u8) -> (Limit, u32) {
+    match han {
+        13.. => (Limit::KazoeYakuman, 8000),
+        11..=12 => (Limit::Sanbaiman, 6000),
+        8..=10 => (Limit::Baiman, 4000),
+        6..=7 => (Limit::Haneman, 3000),
+        5 => (Limit::Mangan, 2000),
+        _ => match fu as u32 * 2u32.pow(han as u32 + 2) {
+            b if b >= 2000 => (Limit::Mangan, 2000),
+            b => (Limit::None, b),
+        },
+    }
+}
+
+fn round_up(p: u32) -> u32 {
+    p.div_ceil(100) * 100
+}
+
+#[allow(dead_code)]
+impl RiichiWin {
+    pub fn new(concealed: &[Tile], called: &[Meld], winning: Tile, seat: Tile) -> Self {
+        Self {
+            concealed: concealed.to_vec(),
+            called: called.to_vec(),
+            winning,
+            tsumo: false,
+            seat,
+            round: Tile::East,
+            riichi: false,
+            double_riichi: false,
+            ippatsu: false,
+            last_tile: false,
+            rinshan: false,
+            chankan: false,
+            blessing: false,
+            dora: Vec::new(),
+            ura: Vec::new(),
+            red: 0,
+        }
+    }
+
+    pub fn is_dealer(&self) -> bool {
+        self.seat == Tile::East
+    }
+
+    pub fn is_closed(&self) -> bool {
+        self.called
+            .iter()
+            .all(|m| m.kind == MeldKind::ConcealedKong)
+    }
+
+    // every tile in hand, kongs with four
+    fn tiles(&self) -> Vec<Tile> {
+        self.concealed
+            .iter()
+            .cloned()
+            .chain(self.called.iter().flat_map(meld_tiles))
+            .collect()
+    }
+
+    fn validate(&self) -> Result<[u8; TILEVARIANT], Box<dyn Error>> {
+        if self.called.len() > SETNUM {
+            Err("Too many called melds")?
+        }
+        if self.concealed.len() != HAINUM - 3 * self.called.len() {
+            Err("Wrong number of concealed tiles")?
+        }
+        if !self.concealed.contains(&self.winning) {
+            Err("Winning tile not in hand")?
+        }
+        if !self.seat.is_wind() || !self.round.is_wind() {
+            Err("Seat and round must be winds")?
+        }
+        if self
+            .called
+            .iter()
+            .any(|m| m.kind.is_chow() && (m.head.is_honor() || m.head.number() > 7))
+        {
+            Err("Invalid chow")?
+        }
+        if self
+            .called
+            .iter()
+            .any(|m| m.kind.is_concealed() && !m.kind.is_kong())
+        {
+            Err("Called melds are revealed or concealed kongs")?
+        }
+
+        if self
+            .tiles()
+            .iter()
+            .chain(self.dora.iter())
+            .chain(self.ura.iter())
+            .any(|t| t.is_bonus())
+        {
+            Err("No bonus tiles in riichi")?
+        }
+        let mut all = [0u8; TILEVARIANT];
+        self.tiles().iter().for_each(|t| all[*t as usize] += 1);
+        if all.iter().any(|c| *c > 4) {
+            Err("More than four copies of a tile")?
+        }
+        if self.red as usize
+            > [Tile::B5, Tile::C5, Tile::D5]
+                .iter()
+                .map(|t| all[*t as usize] as usize)
+                .sum()
+        {
+            Err("More red fives than fives")?
+        }
+
+        let mut counts = [0u8; TILEVARIANT];
+        self.concealed.iter().for_each(|t| counts[*t as usize] += 1);
+        Ok(counts)
+    }
+
+    pub fn score(&self) -> Result<RiichiScore, Box<dyn Error>> {
+        let counts = self.validate()?;
+        let closed = self.is_closed();
+
+        let mut scores = Vec::new();
+        if self.called.is_empty() {
+            if let Some(s) = self.thirteen_orphans(&counts) {
+                scores.push(s);
+            }
+            if let Some(s) = self.seven_pairs(&counts) {
+                scores.push(s);
+            }
+        }
+        let shapes = self.shapes(&counts);
+        let winning = !scores.is_empty() || !shapes.is_empty();
+        shapes.into_iter().for_each(|s| {
+            let (yaku, yakuman) = self.standard_yaku(&s, closed);
+            let fu = self.fu(&s, closed, yaku.contains(&Yaku::Pinfu));
+            let form = Form::Standard {
+                pair: s.pair,
+                melds: s.melds,
+                wait: s.wait,
+            };
+            scores.push(self.finish(form, yaku, yakuman, fu));
+        });
+
+        match winning {
+            false => Err("Not a winning hand")?,
+            true => Ok(scores
+                .into_iter()
+                .flatten()
+                .max_by_key(|s| (s.payment.total(), s.han, s.fu))
+                .ok_or("No yaku")?),
+        }
+    }
+
+    // every decomposition, with every place the winning tile can take
+    fn shapes(&self, counts: &[u8; TILEVARIANT]) -> Vec<Shape> {
+        let mut shapes = Vec::new();
+        for p in (0..TILEVARIANT).filter(|i| counts[*i] >= 2) {
+            let mut rest = *counts;
+            rest[p] -= 2;
+            let mut decompositions = Vec::new();
+            melds_of(&mut rest, &mut Vec::new(), &mut decompositions);
+
+            for concealed in decompositions.into_iter() {
+                let pair = tile(p);
+                let melds: ArrayVec<Meld, SETNUM> = concealed
+                    .iter()
+                    .chain(self.called.iter())
+                    .cloned()
+                    .collect();
+                let shape = |wait, ron_pung| Shape {
+                    pair,
+                    melds: melds.clone(),
+                    wait,
+                    ron_pung,
+                };
+
+                if pair == self.winning {
+                    shapes.push(shape(Wait::Tanki, None));
+                }
+                concealed.iter().enumerate().for_each(|(i, m)| {
+                    match (m.kind.is_chow(), m.head == self.winning) {
+                        (false, true) => shapes.push(shape(
+                            Wait::Shanpon,
+                            match self.tsumo {
+                                true => None,
+                                false => Some(i),
+                            },
+                        )),
+                        (true, _)
+                            if self.winning.is_same_color(m.head)
+                                && (m.head as usize..m.head as usize + 3)
+                                    .contains(&(self.winning as usize)) =>
+                        {
+                            let wait =
+                                match (self.winning as usize - m.head as usize, m.head.number()) {
+                                    (1, _) => Wait::Kanchan,
+                                    (0, 7) | (2, 1) => Wait::Penchan,
+                                    _ => Wait::Ryanmen,
+                                };
+                            shapes.push(shape(wait, None));
+                        }
+                        _ => (),
+                    }
+                });
+            }
+        }
+        shapes
+    }
+
+    // 立直、自摸 and other yaku from how the hand was won
+    fn situational(&self, closed: bool) -> Vec<Yaku> {
+        let mut yaku = Vec::new();
+        match (closed, self.double_riichi, self.riichi) {
+            (true, true, _) => yaku.push(Yaku::DoubleRiichi),
+            (true, false, true) => yaku.push(Yaku::Riichi),
+            _ => (),
+        }
+        if closed && (self.riichi || self.double_riichi) && self.ippatsu {
+            yaku.push(Yaku::Ippatsu);
+        }
+        if closed && self.tsumo {
+            yaku.push(Yaku::MenzenTsumo);
+        }
+        match (self.last_tile, self.tsumo) {
+            (true, true) => yaku.push(Yaku::Haitei),
+            (true, false) => yaku.push(Yaku::Houtei),
+            _ => (),
+        }
+        if self.rinshan && self.tsumo {
+            yaku.push(Yaku::Rinshan);
+        }
+        if self.chankan && !self.tsumo {
+            yaku.push(Yaku::Chankan);
+        }
+        yaku
+    }
+
+    // 天和、地和, and yakuman read from tile counts alone
+    fn tile_yakuman(&self, closed: bool) -> Vec<Yakuman> {
+        let mut yakuman = Vec::new();
+        let tiles = self.tiles();
+
+        if self.blessing && self.tsumo && closed {
+            match self.is_dealer() {
+                true => yakuman.push(Yakuman::Tenhou),
+                false => yakuman.push(Yakuman::Chiihou),
+            }
+        }
+        if tiles.iter().all(|t| t.is_honor()) {
+            yakuman.push(Yakuman::Tsuuiisou);
+        }
+        if tiles.iter().all(|t| t.is_terminal()) {
+            yakuman.push(Yakuman::Chinroutou);
+        }
+        if tiles.iter().all(|t| {
+            matches!(
+                t,
+                Tile::B2 | Tile::B3 | Tile::B4 | Tile::B6 | Tile::B8 | Tile::Green
+            )
+        }) {
+            yakuman.push(Yakuman::Ryuuiisou);
+        }
+
+        // 九蓮寶燈, 1112345678999 and one more
+        let color = tiles[0];
+        if closed && !color.is_honor() && tiles.iter().all(|t| t.is_same_color(color)) {
+            let mut numbers = [0u8; 9];
+            tiles.iter().for_each(|t| numbers[t.number() - 1] += 1);
+            let shape = [3, 1, 1, 1, 1, 1, 1, 1, 3];
+            if numbers.iter().zip(shape.iter()).all(|(n, s)| n >= s) {
+                numbers[self.winning.number() - 1] -= 1;
+                match numbers == shape {
+                    true => yakuman.push(Yakuman::JunseiChuuren),
+                    false => yakuman.push(Yakuman::Chuuren),
+                }
+            }
+        }
+        yakuman
+    }
+
+    fn standard_yaku(&self, s: &Shape, closed: bool) -> (Vec<Yaku>, Vec<Yakuman>) {
+        let mut yaku = self.situational(closed);
+        let mut yakuman = self.tile_yakuman(closed);
+        let melds = &s.melds;
+        let chows: Vec<&Meld> = melds.iter().filter(|m| m.kind.is_chow()).collect();
+        let pungs: Vec<&Meld> = melds.iter().filter(|m| m.kind.is_pung()).collect();
+        let yakuhai = |t: Tile| t.is_dragon() || t == self.seat || t == self.round;
+
+        // 平和
+        if closed && chows.len() == SETNUM && s.wait == Wait::Ryanmen && !yakuhai(s.pair) {
+            yaku.push(Yaku::Pinfu);
+        }
+
+        // 斷幺九
+        if s.pair.is_simple() && melds.iter().all(is_simple_meld) {
+            yaku.push(Yaku::Tanyao);
+        }
+
+        // 一盃口、二盃口
+        if closed {
+            let mut heads: Vec<Tile> = chows.iter().map(|m| m.head).collect();
+            heads.sort();
+            match (
+                heads.len() == SETNUM && heads[0] == heads[1] && heads[2] == heads[3],
+                heads.windows(2).any(|w| w[0] == w[1]),
+            ) {
+                (true, _) => yaku.push(Yaku::Ryanpeikou),
+                (false, true) => yaku.push(Yaku::Iipeikou),
+                _ => (),
+            }
+        }
+
+        // 役牌
+        pungs.iter().for_each(|m| match m.head {
+            Tile::White => yaku.push(Yaku::Haku),
+            Tile::Green => yaku.push(Yaku::Hatsu),
+            Tile::Red => yaku.push(Yaku::Chun),
+            t => {
+                if t == self.seat {
+                    yaku.push(Yaku::SeatWind);
+                }
+                if t == self.round {
+                    yaku.push(Yaku::RoundWind);
+                }
+            }
+        });
+
+        // 混全帶、純全帶、混老頭
+        let honors = s.pair.is_honor() || melds.iter().any(|m| m.head.is_honor());
+        if !s.pair.is_simple() && melds.iter().all(|m| m.head.is_honor() || has_terminal(m)) {
+            match (chows.is_empty(), honors) {
+                (false, true) => yaku.push(Yaku::Chanta),
+                (false, false) => yaku.push(Yaku::Junchan),
+                (true, _) => yaku.push(Yaku::Honroutou),
+            }
+        }
+        if yakuman.contains(&Yakuman::Chinroutou) || yakuman.contains(&Yakuman::Tsuuiisou) {
+            yaku.retain(|y| *y != Yaku::Honroutou);
+        }
+
+        // 一氣通貫
+        if chows.iter().any(|a| {
+            a.head.number() == 1
+                && [3, 6]
+                    .iter()
+                    .all(|d| chows.iter().any(|b| b.head as usize == a.head as usize + d))
+        }) {
+            yaku.push(Yaku::Ittsu);
+        }
+
+        // 三色同順、三色同刻
+        let triples = |ms: &Vec<&Meld>| {
+            (0..ms.len()).any(|i| {
+                (i + 1..ms.len())
+                    .any(|j| (j + 1..ms.len()).any(|k| is_mixed(&[ms[i], ms[j], ms[k]])))
+            })
+        };
+        if triples(&chows) {
+            yaku.push(Yaku::SanshokuDoujun);
+        }
+        if triples(&pungs) {
+            yaku.push(Yaku::SanshokuDoukou);
+        }
+
+        // 對對和、暗刻、槓子
+        if pungs.len() == SETNUM {
+            yaku.push(Yaku::Toitoi);
+        }
+        let ankou = melds
+            .iter()
+            .enumerate()
+            .filter(|(i, m)| m.kind.is_pung() && m.kind.is_concealed() && s.ron_pung != Some(*i))
+            .count();
+        match (ankou, s.wait) {
+            (4, Wait::Tanki) => yakuman.push(Yakuman::SuuankouTanki),
+            (4, _) => yakuman.push(Yakuman::Suuankou),
+            (3, _) => yaku.push(Yaku::Sanankou),
+            _ => (),
+        }
+        match melds.iter().filter(|m| m.kind.is_kong()).count() {
+            4 => yakuman.push(Yakuman::Suukantsu),
+            3 => yaku.push(Yaku::Sankantsu),
+            _ => (),
+        }
+
+        // 三元、四喜
+        let dragons = pungs.iter().filter(|m| m.head.is_dragon()).count();
+        match (dragons, s.pair.is_dragon()) {
+            (3, _) => yakuman.push(Yakuman::Daisangen),
+            (2, true) => yaku.push(Yaku::Shousangen),
+            _ => (),
+        }
+        let winds = pungs.iter().filter(|m| m.head.is_wind()).count();
+        match (winds, s.pair.is_wind()) {
+            (4, _) => yakuman.push(Yakuman::Daisuushii),
+            (3, true) => yakuman.push(Yakuman::Shousuushii),
+            _ => (),
+        }
+
+        // 混一色、清一色
+        let tiles: Vec<Tile> = melds
+            .iter()
+            .map(|m| m.head)
+            .chain(std::iter::once(s.pair))
+            .collect();
+        if let Some(y) = flush(&tiles) {
+            yaku.push(y);
+        }
+
+        (yaku, yakuman)
+    }
+
+    fn fu(&self, s: &Shape, closed: bool, pinfu: bool) -> u8 {
+        // 平和自摸
+        if pinfu && self.tsumo {
+            return 20;
+        }
+
+        let mut fu = 20;
+        match (closed, self.tsumo) {
+            (true, false) => fu += 10, // 門前加符
+            (_, true) => fu += 2,
+            _ => (),
+        }
+        fu += s
+            .melds
+            .iter()
+            .enumerate()
+            .filter(|(_, m)| m.kind.is_pung())
+            .map(|(i, m)| {
+                let concealed = m.kind.is_concealed() && s.ron_pung != Some(i);
+                let f = match (m.head.is_simple(), concealed) {
+                    (true, false) => 2,
+                    (true, true) | (false, false) => 4,
+                    (false, true) => 8,
+                };
+                match m.kind.is_kong() {
+                    true => f * 4,
+                    false => f,
+                }
+            })
+            .sum::<u8>();
+        if s.pair.is_dragon() {
+            fu += 2;
+        }
+        if s.pair == self.seat {
+            fu += 2;
+        }
+        if s.pair == self.round {
+            fu += 2;
+        }
+        if matches!(s.wait, Wait::Kanchan | Wait::Penchan | Wait::Tanki) {
+            fu += 2;
+        }
+
+        match fu {
+            // 喰い平和
+            20 => 30,
+            f => f.div_ceil(10) * 10,
+        }
+    }
+
+    fn seven_pairs(&self, counts: &[u8; TILEVARIANT]) -> Option<Option<RiichiScore>> {
+        if counts.iter().filter(|c| **c == 2).count() != 7 {
+            None?
+        }
+        let mut yaku = self.situational(true);
+        yaku.push(Yaku::Chiitoitsu);
+        let yakuman = self.tile_yakuman(true);
+        let tiles = self.tiles();
+        if tiles.iter().all(|t| t.is_simple()) {
+            yaku.push(Yaku::Tanyao);
+        }
+        if tiles.iter().all(|t| !t.is_simple())
+            && tiles.iter().any(|t| t.is_honor())
+            && tiles.iter().any(|t| t.is_terminal())
+        {
+            yaku.push(Yaku::Honroutou);
+        }
+        if let Some(y) = flush(&tiles) {
+            yaku.push(y);
+        }
+        Some(self.finish(Form::SevenPairs, yaku, yakuman, 25))
+    }
+
+    fn thirteen_orphans(&self, counts: &[u8; TILEVARIANT]) -> Option<Option<RiichiScore>> {
+        let orphans = (0..TILEVARIANT).filter(|i| !tile(*i).is_simple());
+        if !orphans.clone().all(|i| counts[i] >= 1)
+            || orphans.map(|i| counts[i] as usize).sum::<usize>() != HAINUM
+        {
+            None?
+        }
+        let mut yakuman = self.tile_yakuman(true);
+        match counts[self.winning as usize] {
+            2 => yakuman.push(Yakuman::Kokushi13),
+            _ => yakuman.push(Yakuman::Kokushi),
+        }
+        Some(self.finish(Form::ThirteenOrphans, self.situational(true), yakuman, 0))
+    }
+
+    // 寶牌 and payments, None without yaku
+    fn finish(
+        &self,
+        form: Form,
+        yaku: Vec<Yaku>,
+        yakuman: Vec<Yakuman>,
+        fu: u8,
+    ) -> Option<RiichiScore> {
+        let closed = self.is_closed();
+        let yaku: Vec<(Yaku, u8)> = yaku
+            .into_iter()
+            .map(|y| (y, y.han(closed)))
+            .filter(|(_, h)| *h > 0)
+            .collect();
+        if yaku.is_empty() && yakuman.is_empty() {
+            None?
+        }
+
+        let tiles = self.tiles();
+        let count = |indicators: &[Tile]| {
+            indicators
+                .iter()
+                .map(|i| tiles.iter().filter(|t| **t == dora_of(*i)).count() as u8)
+                .sum::<u8>()
+        };
+        let dora = count(&self.dora);
+        let ura = match self.riichi || self.double_riichi {
+            true => count(&self.ura),
+            false => 0,
+        };
+        let han = yaku.iter().map(|(_, h)| h).sum::<u8>() + dora + ura + self.red;
+
+        let (limit, base) = match yakuman.iter().map(|y| y.multiplier()).sum::<u8>() {
+            0 => base_points(han, fu),
+            n => (Limit::Yakuman(n), 8000 * n as u32),
+        };
+        let payment = match (self.tsumo, self.is_dealer()) {
+            (false, false) => Payment::Ron(round_up(base * 4)),
+            (false, true) => Payment::Ron(round_up(base * 6)),
+            (true, true) => Payment::DealerTsumo(round_up(base * 2)),
+            (true, false) => Payment::Tsumo {
+                dealer: round_up(base * 2),
+                others: round_up(base),
+            },
+        };
+
+        Some(RiichiScore {
+            form,
+            yaku,
+            yakuman,
+            dora,
+            ura,
+            red: self.red,
+            han,
+            fu,
+            limit,
+            payment,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::tile::parse_tiles;
+
+    fn win(concealed: &str, winning: &str, seat: Tile) -> RiichiWin {
+        RiichiWin::new(
+            &parse_tiles(concealed).unwrap(),
+            &[],
+            parse_tiles(winning).unwrap()[0],
+            seat,
+        )
+    }
+
+    fn yaku(s: &RiichiScore) -> Vec<Yaku> {
+        s.yaku.iter().map(|(y, _)| *y).collect()
+    }
+
+    #[test]
+    fn parse_and_dora() {
+        assert_eq!(
+            parse_tiles("19m9p1s57z"),
+            Some(vec![
+                Tile::C1,
+                Tile::C9,
+                Tile::D9,
+                Tile::B1,
+                Tile::White,
+                Tile::Red
+            ])
+        );
+        assert_eq!(parse_tiles("8z"), None);
+        assert_eq!(parse_tiles("12"), None);
+        assert_eq!(dora_of(Tile::C9), Tile::C1);
+        assert_eq!(dora_of(Tile::D4), Tile::D5);
+        assert_eq!(dora_of(Tile::North), Tile::East);
+        assert_eq!(dora_of(Tile::White), Tile::Green);
+        assert_eq!(dora_of(Tile::Red), Tile::White);
+    }
+
+    #[test]
+    fn pinfu_tsumo_20_fu() {
+        // 立直 門前清自摸和 平和 ドラ1, 4 han 20 fu, 1300/2600
+        let mut w = win("123m456m789p23455s", "4s", Tile::South);
+        w.tsumo = true;
+        w.riichi = true;
+        w.dora = vec![Tile::C1];
+        let s = w.score().unwrap();
+        assert_eq!(yaku(&s), vec![Yaku::Riichi, Yaku::MenzenTsumo, Yaku::Pinfu]);
+        assert_eq!((s.han, s.fu, s.dora), (4, 20, 1));
+        assert_eq!(
+            s.payment,
+            Payment::Tsumo {
+                dealer: 2600,
+                others: 1300
+            }
+        );
+    }
+
+    #[test]
+    fn fu_from_wait_and_melds() {
+        // 20 + 10 門前加符 + 8 幺九暗刻 + 2 嵌張 = 40 fu, 1 han ron 1300
+        let mut w = win("111s234m68p345s55m7p", "7p", Tile::South);
+        w.riichi = true;
+        let s = w.score().unwrap();
+        assert_eq!((s.han, s.fu), (1, 40));
+        assert_eq!(s.payment, Payment::Ron(1300));
+
+        // 立直 門前清自摸和 斷幺九, 30 fu 3 han, 1000/2000
+        let mut w = win("234m46p678s345s88p5p", "5p", Tile::West);
+        w.tsumo = true;
+        w.riichi = true;
+        let s = w.score().unwrap();
+        assert_eq!((s.han, s.fu), (3, 30));
+        assert_eq!(
+            s.payment,
+            Payment::Tsumo {
+                dealer: 2000,
+                others: 1000
+            }
+        );
+
+        // 立直 平和 ドラ2, 30 fu 4 han, 7700 and 11600 for the dealer
+        let mut w = win("123m456m789p23455s", "4s", Tile::West);
+        w.riichi = true;
+        w.dora = vec![Tile::B4];
+        let s = w.score().unwrap();
+        assert_eq!((s.han, s.fu, s.dora), (4, 30, 2));
+        assert_eq!(s.payment, Payment::Ron(7700));
+        w.seat = Tile::East;
+        assert_eq!(w.score().unwrap().payment, Payment::Ron(11600));
+    }
+
+    #[test]
+    fn open_hand() {
+        // 喰い斷, open pinfu shape is 30 fu, 1000
+        let w = RiichiWin::new(
+            &parse_tiles("456p678s345s88p").unwrap(),
+            &[Meld::new(Tile::C2, MeldKind::RevealedChow)],
+            Tile::B5,
+            Tile::North,
+        );
+        let s = w.score().unwrap();
+        assert_eq!(yaku(&s), vec![Yaku::Tanyao]);
+        assert_eq!((s.han, s.fu), (1, 30));
+        assert_eq!(s.payment, Payment::Ron(1000));
+
+        // no yaku, 立直 ignored on an open hand
+        let mut w = RiichiWin::new(
+            &parse_tiles("456p678s345s11p").unwrap(),
+            &[Meld::new(Tile::C2, MeldKind::RevealedChow)],
+            Tile::B5,
+            Tile::North,
+        );
+        w.riichi = true;
+        assert!(w.score().is_err());
+
+        // 食い下がり, 混一色 2 han and 混全帶 1 han
+        let w = RiichiWin::new(
+            &parse_tiles("123m789m11m666z").unwrap(),
+            &[Meld::new(Tile::Red, MeldKind::RevealedPung)],
+            Tile::Green,
+            Tile::South,
+        );
+        let s = w.score().unwrap();
+        assert!(s.yaku.contains(&(Yaku::Honitsu, 2)));
+        assert!(s.yaku.contains(&(Yaku::Chun, 1)));
+        assert!(s.yaku.contains(&(Yaku::Chanta, 1)));
+    }
+
+    #[test]
+    fn seven_pairs_25_fu() {
+        // 立直 七對子, 3 han 25 fu, 3200
+        let mut w = win("1122m3344p5566s7z7z", "7z", Tile::South);
+        w.riichi = true;
+        let s = w.score().unwrap();
+        assert_eq!(s.form, Form::SevenPairs);
+        assert_eq!((s.han, s.fu), (3, 25));
+        assert_eq!(s.payment, Payment::Ron(3200));
+
+        // 二盃口 beats 七對子
+        let mut w = win("112233m445566p77s", "7s", Tile::South);
+        w.riichi = true;
+        let s = w.score().unwrap();
+        assert!(yaku(&s).contains(&Yaku::Ryanpeikou));
+        assert_eq!(s.han, 4);
+    }
+
+    #[test]
+    fn limit_tiers() {
+        // 混一色 一氣通貫 ダブ東, 7 han, dealer 跳滿 18000
+        let s = win("123456789m11z99m1z", "1z", Tile::East).score().unwrap();
+        assert_eq!(s.han, 7);
+        assert_eq!(s.limit, Limit::Haneman);
+        assert_eq!(s.payment, Payment::Ron(18000));
+
+        // 清一色 一盃口 一氣通貫 平和, 10 han 倍滿 16000
+        let s = win("112233m456m789m99m", "9m", Tile::South)
+            .score()
+            .unwrap();
+        assert_eq!(s.han, 10);
+        assert_eq!(s.limit, Limit::Baiman);
+        assert_eq!(s.payment, Payment::Ron(16000));
+
+        assert_eq!(base_points(4, 40), (Limit::Mangan, 2000));
+        assert_eq!(base_points(3, 70), (Limit::Mangan, 2000));
+        assert_eq!(base_points(3, 60), (Limit::None, 1920));
+        assert_eq!(base_points(11, 30), (Limit::Sanbaiman, 6000));
+        assert_eq!(base_points(13, 30), (Limit::KazoeYakuman, 8000));
+    }
+
+    #[test]
+    fn yakuman() {
+        // 國士無雙十三面 is double, 64000
+        let s = win("19m19p19s1234567z1m", "1m", Tile::South)
+            .score()
+            .unwrap();
+        assert_eq!(s.yakuman, vec![Yakuman::Kokushi13]);
+        assert_eq!(s.limit, Limit::Yakuman(2));
+        assert_eq!(s.payment, Payment::Ron(64000));
+
+        let s = win("19m19p19s1234567z1m", "9m", Tile::South)
+            .score()
+            .unwrap();
+        assert_eq!(s.yakuman, vec![Yakuman::Kokushi]);
+        assert_eq!(s.payment, Payment::Ron(32000));
+
+        // 四暗刻 by tsumo, by ron it is only 三暗刻 對對和
+        let mut w = win("111m333p555s777z99m", "1m", Tile::East);
+        w.tsumo = true;
+        let s = w.score().unwrap();
+        assert_eq!(s.yakuman, vec![Yakuman::Suuankou]);
+        assert_eq!(s.payment, Payment::DealerTsumo(16000));
+        w.tsumo = false;
+        let s = w.score().unwrap();
+        assert!(s.yakuman.is_empty());
+        assert!(yaku(&s).contains(&Yaku::Sanankou));
+        assert!(yaku(&s).contains(&Yaku::Toitoi));
+
+        // 純正九蓮寶燈
+        let s = win("1112345678999p5p", "5p", Tile::South).score().unwrap();
+        assert_eq!(s.yakuman, vec![Yakuman::JunseiChuuren]);
+    }
+
+    #[test]
+    fn rejects_bad_hands() {
+        assert!(win("123m456m789p2345s", "4s", Tile::South).score().is_err());
+        assert!(win("123m456m789p23459s", "9s", Tile::South)
+            .score()
+            .is_err());
+        assert!(win("11111m456m789p234s", "1m", Tile::South)
+            .score()
+            .is_err());
+    }
+}
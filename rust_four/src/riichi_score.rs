@@ -0,0 +1,24 @@
+use std::io::Read;
+
+use riichi::RiichiWin;
+
+mod riichi;
+mod set;
+mod tile;
+
+// score a riichi hand given as JSON, from a file or stdin
+// usage: riichi [hand.json]
+// {"concealed":["C1",...],"called":[{"head":"Red","kind":"RevealedPung"}],
+//  "winning":"C1","seat":"South","tsumo":true,"riichi":true,"dora":["D4"]}
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let mut input = String::new();
+    match std::env::args().nth(1) {
+        Some(filename) => std::fs::File::open(filename)?.read_to_string(&mut input)?,
+        None => std::io::stdin().read_to_string(&mut input)?,
+    };
+
+    let win: RiichiWin = serde_json::from_str(&input)?;
+    println!("{}", serde_json::to_string_pretty(&win.score()?)?);
+
+    Ok(())
+}
@@ -0,0 +1,278 @@
+use std::{collections::HashMap, error::Error, fs::File, io::BufReader};
+
+use crate::{
+    hand::{Hand, HANDMAXSCORE, HANDVARIANT},
+    handchecker::HandList,
+};
+
+// 計分方式
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum Scheme {
+    Additive { cap: u16 },              // 累加, capped
+    Doubling { base: u64, limit: u16 }, // 每番加倍, limit in fan
+    Table(Vec<u64>),                    // 番數對照表, the last entry is the limit
+}
+
+// 計分模型: fan per hand, minimum fan and how fan becomes a payout
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct ScoringModel {
+    pub name: String,
+    pub values: [u16; HANDVARIANT],
+    pub open_values: [Option<u16>; HANDVARIANT], // 副露時, None 不計
+    pub minimum: u16,                            // 起和番
+    pub scheme: Scheme,
+}
+
+// 爆棚
+const HONGKONGLIMIT: u16 = 10;
+
+#[allow(dead_code)]
+impl ScoringModel {
+    // `Hand::score`, `Hand::open_score` with revealed melds, capped at HANDMAXSCORE
+    pub fn additive() -> Self {
+        let mut values = [0; HANDVARIANT];
+        values
+            .iter_mut()
+            .enumerate()
+            .for_each(|(i, v)| *v = Hand::try_from(i).unwrap().score());
+        let mut open_values = [None; HANDVARIANT];
+        open_values
+            .iter_mut()
+            .enumerate()
+            .for_each(|(i, v)| *v = Hand::try_from(i).unwrap().open_score());
+        Self {
+            name: "additive".to_string(),
+            values,
+            open_values,
+            minimum: 0,
+            scheme: Scheme::Additive { cap: HANDMAXSCORE },
+        }
+    }
+
+    // 港式舊規, 三番起和, 十番爆棚
+    pub fn hong_kong() -> Self {
+        let mut values = [0; HANDVARIANT];
+        values
+            .iter_mut()
+            .enumerate()
+            .for_each(|(i, v)| *v = hong_kong_fan(Hand::try_from(i).unwrap()));
+        Self {
+            name: "hong-kong".to_string(),
+            values,
+            // 港式不分門前
+            open_values: values.map(Some),
+            minimum: 3,
+            scheme: Scheme::Table(vec![1, 2, 4, 8, 16, 24, 32, 48, 64, 96, 128]),
+        }
+    }
+
+    // 港式番數, every fan doubles
+    pub fn hong_kong_doubling() -> Self {
+        Self {
+            name: "hong-kong-doubling".to_string(),
+            scheme: Scheme::Doubling {
+                base: 1,
+                limit: HONGKONGLIMIT,
+            },
+            ..Self::hong_kong()
+        }
+    }
+
+    // 改分, the values of some hands replaced, from JSON like {"AllPungs": 4}
+    // a replaced value counts the same when open, 門前限定 stays
+    pub fn with_values(&self, name: &str, values: &HashMap<Hand, u16>) -> Self {
+        let mut model = self.clone();
+        model.name = name.to_string();
+        values.iter().for_each(|(h, v)| {
+            model.values[*h as usize] = *v;
+            if let Some(open) = model.open_values[*h as usize].as_mut() {
+                *open = *v;
+            }
+        });
+        model
+    }
+
+    pub fn with_values_file(&self, filename: &str) -> Result<Self, Box<dyn Error>> {
+        let reader = BufReader::new(File::open(filename)?);
+        let values: HashMap<Hand, u16> = serde_json::from_reader(reader)?;
+        check_values(&values)?;
+        Ok(self.with_values(filename, &values))
+    }
+
+    // value of hand i in the list, by whether it is open
+    pub fn value(&self, list: &HandList, i: usize) -> u16 {
+        match list.is_open() {
+            true => self.open_values[i].unwrap_or(0),
+            false => self.values[i],
+        }
+    }
+
+    pub fn fan(&self, list: &HandList) -> u16 {
+        list.iter()
+            .enumerate()
+            .take(HANDVARIANT)
+            .filter(|(_, b)| **b)
+            .map(|(i, _)| self.value(list, i))
+            .fold(0, u16::saturating_add)
+    }
+
+    // None when the hand is below the minimum
+    pub fn payout(&self, list: &HandList) -> Option<u64> {
+        let fan = self.fan(list);
+        if fan < self.minimum {
+            return None;
+        }
+
+        Some(match &self.scheme {
+            Scheme::Additive { cap } => fan.min(*cap) as u64,
+            Scheme::Doubling { base, limit } => base << fan.min(*limit),
+            Scheme::Table(table) => table[(fan as usize).min(table.len() - 1)],
+        })
+    }
+}
+
+// values from a file stay within one hand's limit
+fn check_values(values: &HashMap<Hand, u16>) -> Result<(), Box<dyn Error>> {
+    if let Some((h, v)) = values.iter().find(|(_, v)| **v > HANDMAXSCORE) {
+        Err(format!(
+            "{:?} is worth {}, above the limit {}",
+            h, v, HANDMAXSCORE
+        ))?
+    }
+    Ok(())
+}
+
+fn hong_kong_fan(hand: Hand) -> u16 {
+    match hand {
+        Hand::AllChows => 1,
+        Hand::RedPung | Hand::GreenPung | Hand::WhitePung | Hand::WindPung => 1,
+        Hand::AllPungs => 3,
+        Hand::LittleThreeDragons => 5,
+        Hand::BigThreeDragons => 8,
+        Hand::AllTerminalsAndHonors => 1,
+        Hand::HalfFlush => 3,
+        Hand::FullFlush => 7,
+        // 花牌, 一台花 is two fan
+        Hand::SeatFlower | Hand::SeatSeason => 1,
+        Hand::FourFlowers | Hand::FourSeasons => 2,
+        // 和牌狀況
+        Hand::SelfDrawn
+        | Hand::LastTileDraw
+        | Hand::LastTileClaim
+        | Hand::OutWithReplacementTile
+        | Hand::RobbingTheKong => 1,
+        // 爆棚
+        Hand::LittleFourWinds
+        | Hand::BigFourWinds
+        | Hand::AllHonors
+        | Hand::AllTerminals
+        | Hand::FourConcealedPungs
+        | Hand::EightFlowers
+        | Hand::BlessingOfHeaven
+        | Hand::BlessingOfEarth => HONGKONGLIMIT,
+        // 港式不計
+        _ => 0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn list(hands: &[Hand]) -> HandList {
+        let mut hl = HandList::new();
+        hands.iter().for_each(|h| hl.set(*h as usize, true));
+        hl
+    }
+
+    #[test]
+    fn additive_matches_hand_scores() {
+        let model = ScoringModel::additive();
+        let hl = list(&[Hand::AllChows, Hand::HalfFlush, Hand::PureDoubleChow]);
+        assert_eq!(model.payout(&hl), Some(6));
+        assert_eq!(
+            model.payout(&list(&[Hand::AllHonors, Hand::AllPungs])),
+            Some(HANDMAXSCORE as u64)
+        );
+    }
+
+    #[test]
+    fn hong_kong_minimum_and_table() {
+        let model = ScoringModel::hong_kong();
+        // 雞胡 and 平和 alone do not reach three fan
+        assert_eq!(model.payout(&list(&[Hand::NoPoint])), None);
+        assert_eq!(model.payout(&list(&[Hand::AllChows, Hand::RedPung])), None);
+
+        let hl = list(&[Hand::AllChows, Hand::HalfFlush]);
+        assert_eq!(model.fan(&hl), 4);
+        assert_eq!(model.payout(&hl), Some(16));
+
+        // 清一色 對對和 is capped at the limit
+        let hl = list(&[Hand::FullFlush, Hand::AllPungs, Hand::ThreeConcealedPungs]);
+        assert_eq!(model.fan(&hl), 10);
+        assert_eq!(model.payout(&hl), Some(128));
+        assert_eq!(model.payout(&list(&[Hand::AllHonors])), Some(128));
+    }
+
+    #[test]
+    fn replaced_values() {
+        let values: HashMap<Hand, u16> =
+            serde_json::from_str(r#"{"AllPungs": 4, "HalfFlush": 0}"#).unwrap();
+        let model = ScoringModel::additive().with_values("rules", &values);
+        assert_eq!(model.name, "rules");
+        let l = list(&[Hand::AllPungs, Hand::HalfFlush]);
+        assert_eq!(model.payout(&l), Some(4));
+        assert_eq!(ScoringModel::additive().payout(&l), Some(l.score() as u64));
+    }
+
+    #[test]
+    fn values_cannot_overflow() {
+        let values: HashMap<Hand, u16> = (0..HANDVARIANT)
+            .map(|i| (Hand::try_from(i).unwrap(), u16::MAX))
+            .collect();
+        let model = ScoringModel::additive().with_values("huge", &values);
+        let l = list(&[Hand::AllPungs, Hand::HalfFlush]);
+        assert_eq!(model.fan(&l), u16::MAX);
+        assert_eq!(model.payout(&l), Some(HANDMAXSCORE as u64));
+        assert!(check_values(&values).is_err());
+        assert!(check_values(&HashMap::from([(Hand::AllPungs, HANDMAXSCORE)])).is_ok());
+    }
+
+    #[test]
+    fn open_lists_use_open_values() {
+        let model = ScoringModel::additive();
+        let mut l = list(&[Hand::PureDoubleChow, Hand::PureStraight, Hand::FullFlush]);
+        let closed = model.fan(&l);
+        l.set_open(true);
+        // 一般高 is dropped, 一氣 and 清一色 drop a step
+        assert_eq!(
+            model.fan(&l),
+            Hand::PureStraight.open_score().unwrap() + Hand::FullFlush.open_score().unwrap()
+        );
+        assert!(model.fan(&l) < closed);
+        assert_eq!(model.payout(&l), Some(l.score() as u64));
+
+        // 改分 follows into open hands, 門前限定 stays dropped
+        let values = HashMap::from([(Hand::FullFlush, 9), (Hand::PureDoubleChow, 2)]);
+        let model = model.with_values("rules", &values);
+        assert_eq!(model.value(&l, Hand::FullFlush as usize), 9);
+        assert_eq!(model.value(&l, Hand::PureDoubleChow as usize), 0);
+
+        // 港式不分門前
+        let model = ScoringModel::hong_kong();
+        let mut l = list(&[Hand::AllChows, Hand::HalfFlush]);
+        let closed = model.fan(&l);
+        l.set_open(true);
+        assert_eq!(model.fan(&l), closed);
+    }
+
+    #[test]
+    fn hong_kong_doubling() {
+        let model = ScoringModel::hong_kong_doubling();
+        assert_eq!(model.payout(&list(&[Hand::AllPungs])), Some(8));
+        assert_eq!(
+            model.payout(&list(&[Hand::FullFlush, Hand::AllPungs])),
+            Some(1 << HONGKONGLIMIT)
+        );
+    }
+}
@@ -0,0 +1,399 @@
+use std::{
+    fs::File,
+    io::{Error, ErrorKind, Read, Write},
+};
+
+use arrayvec::ArrayVec;
+
+use crate::{
+    binary::Binary,
+    context::WinContext,
+    decompose::allsets,
+    handchecker::{HandChecker, HandList},
+    pattern::{HAINUM, VARIANT},
+    tile::{Tile, TILEVARIANT},
+};
+
+// 查表
+// minimal perfect hash from tile histogram to decompositions and HandList
+pub const MAGIC: [u8; 4] = *b"MJLT";
+pub const VERSION: u16 = 3;
+
+const FIXEDHEADERLEN: usize = 4 + 2 + 1 + 1 + 8 + 8 + 8 + 8;
+const SLOTLEN: usize = 16 + 4 + 4 + 2;
+const ENTRYLEN: usize = HandChecker::LEN + HandList::LEN;
+// average keys per bucket
+const BUCKETLOAD: usize = 2;
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Entry {
+    pub checker: HandChecker,
+    pub hands: HandList,
+}
+
+// key, entry range and best score kept together, one cache miss per lookup
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+struct Slot {
+    key: u128,
+    offset: u32,
+    len: u32,
+    best: u16,
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct LookupTable {
+    source: u64, // checksum of pattern file
+    displace: Vec<u32>,
+    slots: Vec<Slot>,
+    entries: Vec<Entry>,
+}
+
+// 3 bits per tile kind, 0 to 4 copies
+pub fn histogram_key(raw: &[u8]) -> Option<u128> {
+    let mut counts = [0u8; TILEVARIANT];
+    for r in raw.iter() {
+        let t = Tile::try_from(*r as char).ok()?;
+        if t.is_bonus() {
+            None?
+        }
+        counts[t as usize] += 1;
+    }
+
+    match counts.iter().any(|c| *c > 4) {
+        true => None,
+        false => Some(
+            counts
+                .iter()
+                .enumerate()
+                .map(|(i, c)| (*c as u128) << (3 * i))
+                .sum(),
+        ),
+    }
+}
+
+fn mix(x: u64) -> u64 {
+    let mut z = x.wrapping_add(0x9e3779b97f4a7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+    z ^ (z >> 31)
+}
+
+fn hash(key: u128, seed: u32) -> u64 {
+    mix(mix(key as u64 ^ seed as u64) ^ (key >> 64) as u64)
+}
+
+#[allow(dead_code)]
+impl LookupTable {
+    pub fn build(records: &[ArrayVec<u8, HAINUM>], source: u64) -> Self {
+        let mut keyed: Vec<(u128, &ArrayVec<u8, HAINUM>)> = records
+            .iter()
+            .map(|r| (histogram_key(r).expect("invalid pattern"), r))
+            .collect();
+        keyed.sort_by_key(|(k, _)| *k);
+        keyed.dedup_by_key(|(k, _)| *k);
+
+        let n = keyed.len();
+        let nb = n / BUCKETLOAD + 1;
+        let mut buckets: Vec<Vec<usize>> = vec![Vec::new(); nb];
+        keyed
+            .iter()
+            .enumerate()
+            .for_each(|(i, (k, _))| buckets[(hash(*k, 0) % nb as u64) as usize].push(i));
+        let mut order: Vec<usize> = (0..nb).collect();
+        order.sort_by_key(|b| std::cmp::Reverse(buckets[*b].len()));
+
+        // hash and displace, largest bucket first
+        let mut displace = vec![0u32; nb];
+        let mut slot_of = vec![0usize; n];
+        let mut taken = vec![false; n];
+        for b in order.into_iter().take_while(|b| !buckets[*b].is_empty()) {
+            let mut slots: Vec<usize> = Vec::with_capacity(buckets[b].len());
+            for d in 1u32.. {
+                slots.clear();
+                let fits = buckets[b].iter().all(|i| {
+                    let s = (hash(keyed[*i].0, d) % n as u64) as usize;
+                    let free = !taken[s] && !slots.contains(&s);
+                    slots.push(s);
+                    free
+                });
+                if fits {
+                    displace[b] = d;
+                    break;
+                }
+            }
+            buckets[b].iter().zip(slots.iter()).for_each(|(i, s)| {
+                taken[*s] = true;
+                slot_of[*i] = *s;
+            });
+        }
+
+        let mut by_slot = vec![0usize; n];
+        slot_of
+            .iter()
+            .enumerate()
+            .for_each(|(i, s)| by_slot[*s] = i);
+
+        let mut slots = Vec::with_capacity(n);
+        let mut entries = Vec::new();
+        for i in by_slot.into_iter() {
+            let (key, raw) = keyed[i];
+            let offset = entries.len() as u32;
+            let mut sets = allsets(raw);
+            sets.dedup();
+            sets.into_iter().for_each(|s| {
+                let hands = s.hands(&WinContext::default());
+                entries.push(Entry { checker: s, hands });
+            });
+            let best = entries[offset as usize..]
+                .iter()
+                .map(|e| e.hands.score())
+                .max()
+                .unwrap_or(0);
+            slots.push(Slot {
+                key,
+                offset,
+                len: entries.len() as u32 - offset,
+                best,
+            });
+        }
+
+        Self {
+            source,
+            displace,
+            slots,
+            entries,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.slots.len()
+    }
+
+    pub fn source(&self) -> u64 {
+        self.source
+    }
+
+    fn slot(&self, raw: &[u8]) -> Option<&Slot> {
+        if raw.len() != HAINUM || self.slots.is_empty() {
+            None?
+        }
+        let key = histogram_key(raw)?;
+        let n = self.slots.len() as u64;
+        let b = (hash(key, 0) % self.displace.len() as u64) as usize;
+        let slot = &self.slots[(hash(key, self.displace[b]) % n) as usize];
+
+        match slot.key == key {
+            true => Some(slot),
+            false => None,
+        }
+    }
+
+    // decompositions of a hand, raw tiles in any order
+    pub fn get(&self, raw: &[u8]) -> Option<&[Entry]> {
+        let slot = self.slot(raw)?;
+        let start = slot.offset as usize;
+        Some(&self.entries[start..start + slot.len as usize])
+    }
+
+    // 高點法, best score among decompositions at build time
+    pub fn score(&self, raw: &[u8]) -> Option<u16> {
+        Some(self.slot(raw)?.best)
+    }
+
+    pub fn encode(&self) -> Vec<u8> {
+        let mut buffer = Vec::with_capacity(
+            FIXEDHEADERLEN
+                + self.displace.len() * 4
+                + self.slots.len() * SLOTLEN
+                + self.entries.len() * ENTRYLEN
+                + 4,
+        );
+        buffer.extend_from_slice(&MAGIC);
+        buffer.extend_from_slice(&VERSION.to_le_bytes());
+        buffer.push(VARIANT.into());
+        buffer.push(HAINUM as u8);
+        buffer.extend_from_slice(&self.source.to_le_bytes());
+        buffer.extend_from_slice(&(self.displace.len() as u64).to_le_bytes());
+        buffer.extend_from_slice(&(self.slots.len() as u64).to_le_bytes());
+        buffer.extend_from_slice(&(self.entries.len() as u64).to_le_bytes());
+        self.displace
+            .iter()
+            .for_each(|d| buffer.extend_from_slice(&d.to_le_bytes()));
+        self.slots.iter().for_each(|s| {
+            buffer.extend_from_slice(&s.key.to_le_bytes());
+            buffer.extend_from_slice(&s.offset.to_le_bytes());
+            buffer.extend_from_slice(&s.len.to_le_bytes());
+            buffer.extend_from_slice(&s.best.to_le_bytes());
+        });
+        self.entries.iter().for_each(|e| {
+            e.checker.write_bytes(&mut buffer);
+            e.hands.write_bytes(&mut buffer);
+        });
+        buffer
+    }
+
+    pub fn decode(buffer: &[u8]) -> std::io::Result<Self> {
+        let invalid = |msg: &str| Error::new(ErrorKind::InvalidData, msg.to_string());
+
+        if buffer.len() < FIXEDHEADERLEN || buffer[0..4] != MAGIC {
+            Err(invalid("not a lookup table"))?
+        }
+        if u16::from_le_bytes([buffer[4], buffer[5]]) != VERSION {
+            Err(invalid("unsupported lookup table version"))?
+        }
+        if buffer[6] != u8::from(VARIANT) || buffer[7] as usize != HAINUM {
+            Err(invalid("lookup table built for another variant"))?
+        }
+        let read_u64 = |at: usize| u64::from_le_bytes(buffer[at..at + 8].try_into().unwrap());
+        let source = read_u64(8);
+        let nb = read_u64(16) as usize;
+        let n = read_u64(24) as usize;
+        let ne = read_u64(32) as usize;
+        // counts come from the file, sizes must not overflow
+        let size = nb
+            .checked_mul(4)
+            .and_then(|d| d.checked_add(n.checked_mul(SLOTLEN)?))
+            .and_then(|d| d.checked_add(ne.checked_mul(ENTRYLEN)?))
+            .and_then(|d| d.checked_add(FIXEDHEADERLEN))
+            .ok_or_else(|| invalid("impossible lookup table size"))?;
+        if buffer.len() != size {
+            Err(invalid("truncated lookup table"))?
+        }
+        if nb == 0 && n > 0 {
+            Err(invalid("lookup table without buckets"))?
+        }
+
+        let mut at = FIXEDHEADERLEN;
+        let displace = buffer[at..at + nb * 4]
+            .chunks(4)
+            .map(|c| u32::from_le_bytes(c.try_into().unwrap()))
+            .collect();
+        at += nb * 4;
+        let slots = buffer[at..at + n * SLOTLEN]
+            .chunks(SLOTLEN)
+            .map(|c| Slot {
+                key: u128::from_le_bytes(c[0..16].try_into().unwrap()),
+                offset: u32::from_le_bytes(c[16..20].try_into().unwrap()),
+                len: u32::from_le_bytes(c[20..24].try_into().unwrap()),
+                best: u16::from_le_bytes(c[24..26].try_into().unwrap()),
+            })
+            .collect::<Vec<Slot>>();
+        // every slot inside the entries, lookups slice without checks
+        if slots.iter().any(|s| {
+            (s.offset as usize)
+                .checked_add(s.len as usize)
+                .is_none_or(|end| end > ne)
+        }) {
+            Err(invalid("lookup table slot out of range"))?
+        }
+        at += n * SLOTLEN;
+        let entries = buffer[at..]
+            .chunks(ENTRYLEN)
+            .map(|c| decode_entry(c).ok_or_else(|| invalid("invalid lookup table entry")))
+            .collect::<std::io::Result<Vec<Entry>>>()?;
+
+        Ok(Self {
+            source,
+            displace,
+            slots,
+            entries,
+        })
+    }
+
+    pub fn write(&self, filename: &str) -> std::io::Result<()> {
+        let mut file = File::create(filename)?;
+        file.write_all(&self.encode())
+    }
+
+    pub fn read(filename: &str) -> std::io::Result<Self> {
+        let mut reader = File::open(filename)?;
+        let mut buffer = Vec::new();
+        reader.read_to_end(&mut buffer)?;
+        Self::decode(&buffer)
+    }
+}
+
+fn decode_entry(c: &[u8]) -> Option<Entry> {
+    Some(Entry {
+        checker: HandChecker::read_bytes(c)?,
+        hands: HandList::read_bytes(c.get(HandChecker::LEN..)?)?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn records() -> Vec<ArrayVec<u8, HAINUM>> {
+        [
+            b"AABBBCCCDDDEEE",
+            b"HHHIIIJJJKKKLL",
+            b"AAHIJKLMNOPQQQ",
+            b"HIJHIJHIJQRSZZ",
+        ]
+        .iter()
+        .map(|r| r.iter().cloned().collect())
+        .collect()
+    }
+
+    #[test]
+    fn lookup_matches_allsets() {
+        let records = records();
+        let table = LookupTable::build(&records, 0);
+        assert_eq!(table.len(), records.len());
+
+        records.iter().for_each(|r| {
+            let mut sets = allsets(r);
+            sets.dedup();
+            let found: Vec<HandChecker> = table
+                .get(r)
+                .unwrap()
+                .iter()
+                .map(|e| e.checker.clone())
+                .collect();
+            assert_eq!(found, sets);
+        });
+
+        // order of tiles does not matter, non-agari hands are rejected
+        assert!(table.get(b"EEEDDDCCCBBBAA").is_some());
+        assert!(table.get(b"AABBBCCCDDDEEF").is_none());
+    }
+
+    #[test]
+    fn table_round_trip() {
+        let table = LookupTable::build(&records(), 42);
+        let decoded = LookupTable::decode(&table.encode()).unwrap();
+        assert_eq!(decoded, table);
+        assert_eq!(
+            decoded.score(b"HHHIIIJJJKKKLL"),
+            table.score(b"HHHIIIJJJKKKLL")
+        );
+    }
+
+    #[test]
+    fn rejects_corrupt_header_and_slots() {
+        let table = LookupTable::build(&records(), 42);
+        let buffer = table.encode();
+        let nb = table.displace.len();
+
+        // entry count so large the size overflows
+        let mut huge = buffer.clone();
+        huge[32..40].copy_from_slice(&u64::MAX.to_le_bytes());
+        assert!(LookupTable::decode(&huge).is_err());
+
+        // slots without buckets
+        let mut empty = LookupTable::build(&records(), 42);
+        empty.displace.clear();
+        assert!(LookupTable::decode(&empty.encode()).is_err());
+
+        // a slot reaching past the entries, and one whose end overflows u32
+        let slot = FIXEDHEADERLEN + nb * 4;
+        let mut past = buffer.clone();
+        past[slot + 20..slot + 24].copy_from_slice(&(table.entries.len() as u32 + 1).to_le_bytes());
+        assert!(LookupTable::decode(&past).is_err());
+        let mut wrap = buffer.clone();
+        wrap[slot + 16..slot + 20].copy_from_slice(&u32::MAX.to_le_bytes());
+        wrap[slot + 20..slot + 24].copy_from_slice(&2u32.to_le_bytes());
+        assert!(LookupTable::decode(&wrap).is_err());
+    }
+}
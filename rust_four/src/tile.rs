@@ -1,7 +1,12 @@
 use std::convert::TryFrom;
 
+use serde::{Deserialize, Serialize};
+
 #[allow(dead_code)]
 pub const TILEVARIANT: usize = 34;
+// 花牌, set aside and replaced, never part of a pattern
+#[allow(dead_code)]
+pub const BONUSVARIANT: usize = 8;
 
 #[allow(dead_code)]
 #[derive(Clone, Copy, PartialEq, Eq)]
@@ -10,9 +15,10 @@ pub enum TileColor {
     Bamboo,
     Character,
     Dot,
+    Bonus,
 }
 
-#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, PartialOrd, Ord)]
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, PartialOrd, Ord, Serialize, Deserialize)]
 pub enum Tile {
     // 字牌, Honor
     Red,
@@ -52,6 +58,16 @@ pub enum Tile {
     D7,
     D8,
     D9,
+    // 花, Flower 梅蘭菊竹
+    F1,
+    F2,
+    F3,
+    F4,
+    // 季, Season 春夏秋冬
+    S1,
+    S2,
+    S3,
+    S4,
 }
 
 #[allow(dead_code)]
@@ -96,15 +112,23 @@ impl Tile {
             | &Self::D7
             | &Self::D8
             | &Self::D9 => TileColor::Dot,
+            &Self::F1
+            | &Self::F2
+            | &Self::F3
+            | &Self::F4
+            | &Self::S1
+            | &Self::S2
+            | &Self::S3
+            | &Self::S4 => TileColor::Bonus,
         }
     }
 
     pub fn number(&self) -> usize {
         match self {
-            &Self::Red | &Self::B1 | &Self::C1 | &Self::D1 => 1,
-            &Self::Green | &Self::B2 | &Self::C2 | &Self::D2 => 2,
-            &Self::White | &Self::B3 | &Self::C3 | &Self::D3 => 3,
-            &Self::East | &Self::B4 | &Self::C4 | &Self::D4 => 4,
+            &Self::Red | &Self::B1 | &Self::C1 | &Self::D1 | &Self::F1 | &Self::S1 => 1,
+            &Self::Green | &Self::B2 | &Self::C2 | &Self::D2 | &Self::F2 | &Self::S2 => 2,
+            &Self::White | &Self::B3 | &Self::C3 | &Self::D3 | &Self::F3 | &Self::S3 => 3,
+            &Self::East | &Self::B4 | &Self::C4 | &Self::D4 | &Self::F4 | &Self::S4 => 4,
             &Self::South | &Self::B5 | &Self::C5 | &Self::D5 => 5,
             &Self::West | &Self::B6 | &Self::C6 | &Self::D6 => 6,
             &Self::North | &Self::B7 | &Self::C7 | &Self::D7 => 7,
@@ -129,6 +153,7 @@ impl Tile {
             | &Self::B9
             | &Self::C9
             | &Self::D9 => false,
+            _ if self.is_bonus() => false,
             _ => (*self as u8 + 1) == (t as u8),
         }
     }
@@ -145,12 +170,13 @@ impl Tile {
             | &Self::B1
             | &Self::C1
             | &Self::D1 => false,
+            _ if self.is_bonus() => false,
             _ => (*self as u8 - 1) == (t as u8),
         }
     }
 
     pub fn is_simple(&self) -> bool {
-        !(self.is_honor() || self.is_terminal())
+        !(self.is_honor() || self.is_terminal() || self.is_bonus())
     }
 
     pub fn is_terminal(&self) -> bool {
@@ -177,6 +203,23 @@ impl Tile {
             _ => false,
         }
     }
+
+    pub fn is_bonus(&self) -> bool {
+        self.color() == TileColor::Bonus
+    }
+
+    pub fn is_flower(&self) -> bool {
+        matches!(self, Self::F1 | Self::F2 | Self::F3 | Self::F4)
+    }
+
+    pub fn is_season(&self) -> bool {
+        matches!(self, Self::S1 | Self::S2 | Self::S3 | Self::S4)
+    }
+
+    // 正花: 梅春 for 東, 蘭夏 for 南, 菊秋 for 西, 竹冬 for 北
+    pub fn is_seat_bonus(&self, wind: Tile) -> bool {
+        self.is_bonus() && wind.is_wind() && self.number() + 3 == wind.number()
+    }
 }
 
 impl TryFrom<char> for Tile {
@@ -218,7 +261,87 @@ impl TryFrom<char> for Tile {
             '`' => Ok(Tile::D7),
             'a' => Ok(Tile::D8),
             'b' => Ok(Tile::D9),
+            'c' => Ok(Tile::F1),
+            'd' => Ok(Tile::F2),
+            'e' => Ok(Tile::F3),
+            'f' => Ok(Tile::F4),
+            'g' => Ok(Tile::S1),
+            'h' => Ok(Tile::S2),
+            'i' => Ok(Tile::S3),
+            'j' => Ok(Tile::S4),
             _ => Err(()),
         }
     }
 }
+
+impl TryFrom<usize> for Tile {
+    type Error = ();
+
+    fn try_from(value: usize) -> Result<Self, Self::Error> {
+        match value < TILEVARIANT + BONUSVARIANT {
+            true => Tile::try_from((value as u8 + b'A') as char),
+            false => Err(()),
+        }
+    }
+}
+
+// tenhou style notation, 123m456p789s1234567z, z is 東南西北白發中
+#[allow(dead_code)]
+pub fn parse_tiles(s: &str) -> Option<Vec<Tile>> {
+    let honors = [
+        Tile::East,
+        Tile::South,
+        Tile::West,
+        Tile::North,
+        Tile::White,
+        Tile::Green,
+        Tile::Red,
+    ];
+    let mut tiles = Vec::new();
+    let mut numbers = Vec::new();
+    for c in s.chars() {
+        match c {
+            '1'..='9' => numbers.push(c as usize - '0' as usize),
+            'm' | 'p' | 's' => {
+                let base = match c {
+                    'm' => Tile::C1,
+                    'p' => Tile::D1,
+                    _ => Tile::B1,
+                } as usize;
+                tiles.extend(
+                    numbers
+                        .drain(..)
+                        .map(|n| Tile::try_from(base + n - 1).unwrap()),
+                );
+            }
+            'z' => tiles.extend(
+                numbers
+                    .drain(..)
+                    .map(|n| honors.get(n - 1).cloned())
+                    .collect::<Option<Vec<Tile>>>()?,
+            ),
+            _ => None?,
+        }
+    }
+    match numbers.is_empty() {
+        true => Some(tiles),
+        false => None,
+    }
+}
+
+// 剩餘張數 of every tile once the visible ones are taken out
+#[allow(dead_code)]
+pub fn available(visible: &[u8]) -> Result<[u64; TILEVARIANT], Box<dyn std::error::Error>> {
+    let mut available = [4u64; TILEVARIANT];
+    for v in visible {
+        match Tile::try_from(*v as char) {
+            Ok(t) if !t.is_bonus() => {
+                available[t as usize] = available[t as usize]
+                    .checked_sub(1)
+                    .ok_or(format!("more than 4 of {:?} visible", t))?
+            }
+            _ => Err(format!("{} is not a visible tile", *v as char))?,
+        }
+    }
+    Ok(available)
+}
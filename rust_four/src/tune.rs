@@ -0,0 +1,90 @@
+use std::{error::Error, fs::File, io::Write};
+
+use crate::{
+    context::ContextWeights,
+    hand::{Hand, HANDVARIANT},
+    histogram::Histogram,
+    report::{classes, report, take_option},
+    scoring::ScoringModel,
+    tuning::{Frequencies, Tuning},
+};
+
+mod context;
+mod cooccurrence;
+mod decompose;
+mod distribution;
+mod hand;
+mod handchecker;
+mod histogram;
+mod pattern;
+mod report;
+mod scoring;
+mod set;
+mod tile;
+mod tuning;
+
+// propose scores from a histogram saved by `agari --save`
+// usage: tune histogram.dat tuning.json [--context context.json]
+// {"policy":{"EqualContribution":{"average":4.0}},"min":1,"max":32,"anchors":{"AllChows":1}}
+// {"policy":{"LogProbability":{"scale":null}},"anchors":{"AllChows":1,"FullFlush":8}}
+// the proposal is written as rules for `rescore --rules`
+fn main() -> Result<(), Box<dyn Error>> {
+    let mut args: Vec<String> = std::env::args().skip(1).collect();
+    let weights = match take_option(&mut args, "--context")? {
+        Some(f) => ContextWeights::from_file(&f)?,
+        None => ContextWeights::default(),
+    };
+    let (histogram, tuning) = match (args.first(), args.get(1)) {
+        (Some(h), Some(t)) => (Histogram::read(h)?, Tuning::from_file(t)?),
+        _ => Err("usage: tune histogram.dat tuning.json [--context context.json]")?,
+    };
+
+    let classes = classes(&weights);
+    let frequencies = Frequencies::new(histogram.hands.iter().flat_map(|(l, (_, c))| {
+        classes
+            .iter()
+            .map(move |(b, w)| (l.with_bonus(b), *c as f64 * w))
+    }));
+    let current = ScoringModel::additive().values;
+    let proposed = tuning.solve(&frequencies, &current);
+
+    let (before, after) = (
+        frequencies.contributions(&current),
+        frequencies.contributions(&proposed),
+    );
+    println!(
+        "{:24}{:>12}{:>6}{:>6}{:>12}{:>12}",
+        "役種", "P", "現分", "建議", "貢獻前", "貢獻後"
+    );
+    (0..HANDVARIANT)
+        .filter(|i| frequencies.probability(Hand::try_from(*i).unwrap()) > 0.0)
+        .for_each(|i| {
+            println!(
+                "{:24}{:>12.3e}{:>8}{:>8}{:>15.5}{:>15.5}",
+                format!("{:?}", Hand::try_from(i).unwrap()),
+                frequencies.probability(Hand::try_from(i).unwrap()),
+                current[i],
+                proposed[i],
+                before[i],
+                after[i]
+            )
+        });
+    println!(
+        "平均分數 {:.5} -> {:.5}",
+        frequencies.average(&current),
+        frequencies.average(&proposed)
+    );
+
+    // every hand in order, ready for `rescore --rules`
+    let filename = "tuned_rules_four.json";
+    let lines: Vec<String> = (0..HANDVARIANT)
+        .map(|i| format!("  \"{:?}\": {}", Hand::try_from(i).unwrap(), proposed[i]))
+        .collect();
+    let mut file = File::create(filename)?;
+    writeln!(file, "{{\n{}\n}}", lines.join(",\n"))?;
+    println!("proposed rules written to {}", filename);
+    println!();
+
+    let model = ScoringModel::additive().with_values_file(filename)?;
+    report(&histogram.hands, &weights, 0, &model, None, None)
+}
@@ -0,0 +1,319 @@
+use std::{collections::HashMap, error::Error, fs::File, io::BufReader};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    hand::{Hand, HANDMAXSCORE, HANDVARIANT},
+    handchecker::HandList,
+};
+
+// 配分方針
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+pub enum Policy {
+    // score = scale × −log2 P(hand), the scale fitted to the anchors when missing
+    LogProbability { scale: Option<f64> },
+    // P(hand) × score equal across hands, the average score reaching `average`
+    EqualContribution { average: f64 },
+}
+
+// 調分設定, integer scores within min..=max and never above the cap
+// anchors keep their value, hands that never happen keep the current one
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct Tuning {
+    pub policy: Policy,
+    #[serde(default = "Tuning::default_min")]
+    pub min: u16,
+    #[serde(default = "Tuning::default_max")]
+    pub max: u16,
+    #[serde(default)]
+    pub anchors: HashMap<Hand, u16>,
+}
+
+// 役種組合頻率, lists of hands with their share, summing to 1
+#[derive(Clone, PartialEq, Debug)]
+pub struct Frequencies {
+    lists: Vec<(Vec<usize>, f64)>,
+    probability: [f64; HANDVARIANT],
+}
+
+#[allow(dead_code)]
+impl Frequencies {
+    pub fn new<I: IntoIterator<Item = (HandList, f64)>>(lists: I) -> Self {
+        let mut merged: HashMap<HandList, f64> = HashMap::new();
+        lists
+            .into_iter()
+            .for_each(|(l, w)| *merged.entry(l).or_insert(0.0) += w);
+        let total: f64 = merged.values().sum();
+
+        let mut probability = [0.0; HANDVARIANT];
+        let lists: Vec<(Vec<usize>, f64)> = merged
+            .into_iter()
+            .filter(|(_, w)| *w > 0.0)
+            .map(|(l, w)| {
+                let hands: Vec<usize> = (0..HANDVARIANT).filter(|i| l[*i]).collect();
+                hands.iter().for_each(|h| probability[*h] += w / total);
+                (hands, w / total)
+            })
+            .collect();
+        Self { lists, probability }
+    }
+
+    pub fn probability(&self, h: Hand) -> f64 {
+        self.probability[h as usize]
+    }
+
+    // each hand's part of the average score, a capped score shared in
+    // proportion to the values of the hands it holds
+    pub fn contributions(&self, values: &[u16; HANDVARIANT]) -> [f64; HANDVARIANT] {
+        let mut contributions = [0.0; HANDVARIANT];
+        self.lists.iter().for_each(|(hands, w)| {
+            let total: u16 = hands.iter().map(|h| values[*h]).sum();
+            if total == 0 {
+                return;
+            }
+            let share = total.min(HANDMAXSCORE) as f64 / total as f64;
+            hands
+                .iter()
+                .for_each(|h| contributions[*h] += w * values[*h] as f64 * share);
+        });
+        contributions
+    }
+
+    pub fn average(&self, values: &[u16; HANDVARIANT]) -> f64 {
+        self.contributions(values).iter().sum()
+    }
+}
+
+#[allow(dead_code)]
+impl Tuning {
+    fn default_min() -> u16 {
+        1
+    }
+
+    fn default_max() -> u16 {
+        HANDMAXSCORE
+    }
+
+    pub fn from_file(filename: &str) -> Result<Self, Box<dyn Error>> {
+        let reader = BufReader::new(File::open(filename)?);
+        let tuning: Self = serde_json::from_reader(reader)?;
+        tuning.validate()?;
+        Ok(tuning)
+    }
+
+    pub fn validate(&self) -> Result<(), Box<dyn Error>> {
+        if self.min > self.max || self.max > HANDMAXSCORE {
+            Err(format!(
+                "bounds must satisfy min <= max <= {}",
+                HANDMAXSCORE
+            ))?
+        }
+        if self.anchors.values().any(|v| *v > HANDMAXSCORE) {
+            Err(format!("anchors must not be above {}", HANDMAXSCORE))?
+        }
+        match self.policy {
+            Policy::LogProbability { scale: None } if self.anchors.is_empty() => {
+                Err("-log2 P needs a scale or some anchors")?
+            }
+            Policy::LogProbability { scale: Some(s) } if s <= 0.0 => {
+                Err("the scale must be positive")?
+            }
+            Policy::EqualContribution { average } if average <= 0.0 => {
+                Err("the average score must be positive")?
+            }
+            _ => Ok(()),
+        }
+    }
+
+    // hands whose value is searched: possible, not anchored, not 無役
+    fn tunable(&self, f: &Frequencies) -> Vec<usize> {
+        (0..HANDVARIANT)
+            .filter(|i| {
+                let h = Hand::try_from(*i).unwrap();
+                f.probability[*i] > 0.0 && h != Hand::NoPoint && !self.anchors.contains_key(&h)
+            })
+            .collect()
+    }
+
+    fn clamp(&self, v: f64) -> u16 {
+        (v.round().max(0.0) as u16).clamp(self.min, self.max)
+    }
+
+    // the proposed value of every hand
+    pub fn solve(&self, f: &Frequencies, current: &[u16; HANDVARIANT]) -> [u16; HANDVARIANT] {
+        let mut values = *current;
+        self.anchors
+            .iter()
+            .for_each(|(h, v)| values[*h as usize] = *v);
+        let tunable = self.tunable(f);
+        let surprise = |i: usize| -f.probability[i].log2();
+
+        match self.policy {
+            Policy::LogProbability { scale } => {
+                // least squares through the origin on the anchors
+                let scale = scale.unwrap_or_else(|| {
+                    let anchored: Vec<(f64, f64)> = self
+                        .anchors
+                        .iter()
+                        .filter(|(h, _)| f.probability(**h) > 0.0)
+                        .map(|(h, v)| (surprise(*h as usize), *v as f64))
+                        .collect();
+                    let xx: f64 = anchored.iter().map(|(x, _)| x * x).sum();
+                    let xy: f64 = anchored.iter().map(|(x, y)| x * y).sum();
+                    match xx > 0.0 {
+                        true => xy / xx,
+                        false => 1.0,
+                    }
+                });
+                tunable
+                    .iter()
+                    .for_each(|i| values[*i] = self.clamp(scale * surprise(*i)));
+            }
+            Policy::EqualContribution { average } => {
+                let n = tunable.len() as f64;
+                tunable
+                    .iter()
+                    .for_each(|i| values[*i] = self.clamp(average / n / f.probability[*i]));
+                // the cap and hands held together move the shares, search ±1 steps
+                let objective = |values: &[u16; HANDVARIANT]| {
+                    let c = f.contributions(values);
+                    let fixed: f64 = (0..HANDVARIANT)
+                        .filter(|i| !tunable.contains(i))
+                        .map(|i| c[i])
+                        .sum();
+                    let target = ((average - fixed) / n).max(f64::EPSILON);
+                    tunable
+                        .iter()
+                        .map(|i| ((c[*i] - target) / target).powi(2))
+                        .sum::<f64>()
+                };
+                let mut best = objective(&values);
+                let mut improved = true;
+                while improved {
+                    improved = false;
+                    for i in tunable.iter() {
+                        for step in [-1i32, 1] {
+                            let v = values[*i] as i32 + step;
+                            if v < self.min as i32 || v > self.max as i32 {
+                                continue;
+                            }
+                            let mut next = values;
+                            next[*i] = v as u16;
+                            let o = objective(&next);
+                            if o < best - 1e-12 {
+                                best = o;
+                                values = next;
+                                improved = true;
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        values
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn list(hands: &[Hand]) -> HandList {
+        let mut list = HandList::new();
+        hands.iter().for_each(|h| list.set(*h as usize, true));
+        list
+    }
+
+    fn frequencies() -> Frequencies {
+        Frequencies::new([
+            (list(&[Hand::AllChows]), 0.5),
+            (list(&[Hand::AllChows, Hand::AllSimples]), 0.25),
+            (list(&[Hand::AllPungs]), 0.125),
+            (list(&[Hand::AllPungs, Hand::FullFlush]), 0.0625),
+            (list(&[Hand::NoPoint]), 0.0625),
+        ])
+    }
+
+    fn current() -> [u16; HANDVARIANT] {
+        let mut values = [0; HANDVARIANT];
+        values
+            .iter_mut()
+            .enumerate()
+            .for_each(|(i, v)| *v = Hand::try_from(i).unwrap().score());
+        values
+    }
+
+    #[test]
+    fn frequencies_and_contributions() {
+        let f = frequencies();
+        assert_eq!(f.probability(Hand::AllChows), 0.75);
+        assert_eq!(f.probability(Hand::FullFlush), 0.0625);
+
+        let mut values = [0; HANDVARIANT];
+        values[Hand::AllChows as usize] = 1;
+        values[Hand::AllSimples as usize] = 1;
+        values[Hand::AllPungs as usize] = 30;
+        values[Hand::FullFlush as usize] = 10;
+        let c = f.contributions(&values);
+        assert_eq!(c[Hand::AllChows as usize], 0.75);
+        // 40 capped to 32, shared 3 to 1
+        assert_eq!(c[Hand::FullFlush as usize], 0.0625 * 8.0);
+        assert_eq!(c[Hand::AllPungs as usize], 0.125 * 30.0 + 0.0625 * 24.0);
+        assert_eq!(f.average(&values), c.iter().sum::<f64>());
+    }
+
+    #[test]
+    fn log_probability_from_anchors() {
+        let f = frequencies();
+        let mut anchors = HashMap::new();
+        anchors.insert(Hand::AllPungs, 6);
+        let tuning = Tuning {
+            policy: Policy::LogProbability { scale: None },
+            min: 1,
+            max: HANDMAXSCORE,
+            anchors,
+        };
+        assert!(tuning.validate().is_ok());
+        let values = tuning.solve(&f, &current());
+        // -log2 P of 對對和 is log2(16 / 3), so the scale is 6 / 2.415
+        assert_eq!(values[Hand::AllPungs as usize], 6);
+        assert_eq!(values[Hand::FullFlush as usize], 10);
+        assert_eq!(values[Hand::AllChows as usize], 1);
+        assert_eq!(values[Hand::AllSimples as usize], 5);
+        // never seen, kept
+        assert_eq!(values[Hand::AllHonors as usize], HANDMAXSCORE);
+        assert_eq!(values[Hand::NoPoint as usize], 0);
+
+        let no_scale = Tuning {
+            anchors: HashMap::new(),
+            ..tuning
+        };
+        assert!(no_scale.validate().is_err());
+    }
+
+    #[test]
+    fn equal_contribution() {
+        let f = frequencies();
+        let tuning = Tuning {
+            policy: Policy::EqualContribution { average: 4.0 },
+            min: 1,
+            max: HANDMAXSCORE,
+            anchors: HashMap::new(),
+        };
+        let values = tuning.solve(&f, &current());
+        let c = f.contributions(&values);
+        // rarer hands score more, each near one share of the average
+        assert!(values[Hand::FullFlush as usize] > values[Hand::AllPungs as usize]);
+        assert!(values[Hand::AllPungs as usize] > values[Hand::AllChows as usize]);
+        [
+            Hand::AllChows,
+            Hand::AllSimples,
+            Hand::AllPungs,
+            Hand::FullFlush,
+        ]
+        .iter()
+        .for_each(|h| assert!((c[*h as usize] - 1.0).abs() < 0.35));
+        assert!((f.average(&values) - 4.0).abs() < 1.0);
+    }
+}
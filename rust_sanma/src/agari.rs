@@ -0,0 +1,123 @@
+use std::{collections::HashMap, time::Instant};
+
+use rust_common::pattern::read_patterns;
+
+use crate::{
+    decompose::{allsets, comb, north_comb},
+    hand::{Hand, HANDVARIANT},
+    handchecker::HandList,
+    pattern::{HAINUM, VARIANT},
+    tile::Tile,
+};
+
+mod decompose;
+mod hand;
+mod handchecker;
+mod pattern;
+mod set;
+mod tile;
+
+fn main() -> std::io::Result<()> {
+    let time_read = Instant::now();
+    let (header, raw_hai_sets) = read_patterns::<HAINUM>("patterns_rust_sanma.dat", VARIANT)?;
+    assert_eq!(raw_hai_sets.len() as u64, header.records);
+    println!(
+        "time read and chunks in {:.2} s",
+        time_read.elapsed().as_secs_f32()
+    );
+
+    println!("produce hand patterns");
+    let start = Instant::now();
+    let mut hands: HashMap<HandList, (u64, u64)> = HashMap::new();
+    raw_hai_sets.into_iter().for_each(|raw_hai| {
+        let mut sets = allsets(&raw_hai);
+        sets.dedup();
+        let combinations = comb(&raw_hai);
+
+        // 三麻 seats are 東南西
+        sets.into_iter().for_each(|mut s| {
+            for wind in [Tile::East, Tile::South, Tile::West] {
+                s.set_wind(wind);
+                let list = s.hands();
+                let v = hands.entry(list).or_insert((0, 0));
+                v.0 += 1;
+                v.1 += combinations;
+            }
+        });
+    });
+
+    println!(
+        "time produce patterns in {:.2} s",
+        start.elapsed().as_secs_f32()
+    );
+
+    println!("calculate combinations and total scores");
+    // 役種、和牌形、組合數、總分
+    let mut result: Vec<(Hand, u64, u64, u64)> =
+        vec![(Hand::try_from(0).unwrap(), 0, 0, 0); HANDVARIANT];
+    result
+        .iter_mut()
+        .enumerate()
+        .for_each(|(i, (h, _, _, _))| *h = Hand::try_from(i).unwrap());
+
+    // 拔北 on top of every hand, 和牌形 counted once
+    let norths = north_comb();
+    hands
+        .into_iter()
+        .for_each(|(handlist, (pattern, combination))| {
+            handlist
+                .iter()
+                .enumerate()
+                .filter(|(_, h)| **h)
+                .for_each(|(i, _)| result[i].1 += pattern);
+            norths.iter().for_each(|(bonus, weight)| {
+                let list = handlist.with_bonus(bonus);
+                let score = list.score() as u64;
+                list.iter()
+                    .enumerate()
+                    .filter(|(_, h)| **h)
+                    .for_each(|(i, _)| {
+                        if !handlist[i] {
+                            result[i].1 += pattern;
+                        }
+                        result[i].2 += combination * weight;
+                        result[i].3 += combination * weight * score;
+                    });
+            });
+        });
+
+    // 役牌特殊處理
+    result[4].1 += result[1].1;
+    result[4].2 += result[1].2;
+    result[4].3 += result[1].3;
+    result[4].1 += result[2].1;
+    result[4].2 += result[2].2;
+    result[4].3 += result[2].3;
+    result[4].1 += result[3].1;
+    result[4].2 += result[3].2;
+    result[4].3 += result[3].3;
+
+    println!("end of process");
+    println!();
+
+    println!("{:4}{:8}{:13}{:4}", "役種", "和牌形", "組合數", "平均分數");
+    result
+        .into_iter()
+        .enumerate()
+        .filter_map(|(i, r)| match i {
+            1..=3 => None,
+            _ => Some(r),
+        })
+        .for_each(|(hand, pattern, combination, score)| {
+            println!(
+                "{:<4}{:>8}{:>16} {:>.*}",
+                hand.name(),
+                pattern,
+                combination,
+                5,
+                (score as f64 / combination as f64).to_string()
+            );
+        });
+
+    Ok(())
+}
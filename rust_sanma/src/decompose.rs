@@ -0,0 +1,488 @@
+use arrayvec::ArrayVec;
+
+use crate::{
+    handchecker::{north_hands, HandChecker, HandList},
+    set::{Meld, MeldKind, SetBuilder, HAINUM},
+    tile::{Tile, NUKIVARIANT, TILEVARIANT},
+};
+
+// main performance problem
+pub fn allsets(raw: &[u8]) -> Vec<HandChecker> {
+    assert_eq!(raw.len(), HAINUM);
+    let mut sorted_raw = raw.to_vec();
+    sorted_raw.sort();
+    let tiles: ArrayVec<Tile, HAINUM> = sorted_raw
+        .into_iter()
+        .map(|x| Tile::try_from(x as char).unwrap())
+        .collect();
+
+    let tmp4 = get_pairs(&tiles);
+    let mut tmp3 = Vec::new();
+    for (sb, remains) in tmp4.into_iter() {
+        let mut tmp = get_first_melds(&remains, sb);
+        tmp3.append(&mut tmp);
+    }
+    let mut tmp2 = Vec::new();
+    for (sb, remains) in tmp3.into_iter() {
+        let mut tmp = get_second_melds(&remains, sb);
+        tmp2.append(&mut tmp);
+    }
+    let mut tmp1 = Vec::new();
+    for (sb, remains) in tmp2.into_iter() {
+        let mut tmp = get_third_melds(&remains, sb);
+        tmp1.append(&mut tmp);
+    }
+    let mut result = Vec::new();
+    for (sb, remains) in tmp1.into_iter() {
+        let tmp = get_last_melds(&remains, sb);
+        if let Some(mut tmp) = tmp {
+            tmp.sort();
+            result.push(tmp);
+        }
+    }
+
+    result
+}
+
+#[allow(dead_code)]
+pub fn comb(raw: &[u8]) -> u64 {
+    assert_eq!(raw.len(), HAINUM);
+
+    // 三麻 keeps four copies of each remaining tile, 2m-8m never appear
+    tile_comb(
+        raw.iter()
+            .map(|r| Tile::try_from(*r as char).unwrap())
+            .inspect(|t| assert!(t.is_sanma() && !t.is_bonus())),
+    )
+}
+
+// ways to pick the tiles out of four copies each
+fn tile_comb(tiles: impl Iterator<Item = Tile>) -> u64 {
+    let mut counts = [0u64; TILEVARIANT];
+    tiles.for_each(|t| counts[t as usize] += 1);
+
+    counts
+        .into_iter()
+        .map(|c| match c {
+            0 | 4 => 1,
+            1 | 3 => 4,
+            2 => 6,
+            _ => unreachable!(),
+        })
+        .product()
+}
+
+// 拔北 hands with their weight out of 3^4
+// each 北 is the winner's with 1/3, so n of them weigh C(4, n) * 2^(4 - n)
+#[allow(dead_code)]
+pub fn north_comb() -> Vec<(HandList, u64)> {
+    let mut binomial = 1;
+    (0..=NUKIVARIANT)
+        .map(|n| {
+            let weight = binomial * 2u64.pow((NUKIVARIANT - n) as u32);
+            binomial = binomial * (NUKIVARIANT - n) as u64 / (n + 1) as u64;
+            (north_hands(n), weight)
+        })
+        .collect()
+}
+
+fn is_chow(meld: &[Tile]) -> bool {
+    match meld.len() {
+        3 => meld[0].is_ascending(meld[1]) && meld[1].is_ascending(meld[2]),
+        _ => false,
+    }
+}
+
+fn is_pung(meld: &[Tile]) -> bool {
+    match meld.len() {
+        3 => meld.windows(2).all(|w| w[0] == w[1]),
+        _ => false,
+    }
+}
+
+fn remove_subset<T>(main: &[T], subset: &[T]) -> Vec<T>
+where
+    T: PartialEq + Clone,
+{
+    let mut sub = subset.iter();
+    let mut current = sub.next();
+    let mut result = Vec::new();
+    for m in main {
+        match current {
+            Some(c) if m == c => current = sub.next(),
+            _ => result.push(m.clone()),
+        }
+    }
+
+    result
+}
+
+const FOURSET: usize = 4 * 3;
+const THREESET: usize = 3 * 3;
+const TWOSET: usize = 2 * 3;
+const ONESET: usize = 3;
+
+fn get_pairs(set: &[Tile]) -> Vec<(SetBuilder, ArrayVec<Tile, FOURSET>)> {
+    assert_eq!(set.len(), HAINUM);
+    let pair_loc: Vec<usize> = set
+        .windows(2)
+        .enumerate()
+        .filter(|(_, w)| w[0] == w[1])
+        .map(|(i, _)| i)
+        .collect();
+    let dup_pair_loc: Vec<usize> = set
+        .windows(3)
+        .enumerate()
+        .filter(|(_, w)| w[0] == w[1] && w[1] == w[2])
+        .map(|(i, _)| i)
+        .collect();
+    let pair_indexes = remove_subset(&pair_loc, &dup_pair_loc);
+
+    let mut result = Vec::new();
+
+    for pi in pair_indexes.into_iter() {
+        let sb = SetBuilder::new().add_pair(set[pi]);
+        let remains: ArrayVec<Tile, FOURSET> = set
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| *i != pi && *i != pi + 1)
+            .map(|(_, t)| *t)
+            .collect();
+
+        result.push((sb, remains));
+    }
+
+    result
+}
+
+fn get_first_melds(set: &[Tile], sb: SetBuilder) -> Vec<(SetBuilder, ArrayVec<Tile, THREESET>)> {
+    assert_eq!(set.len(), FOURSET);
+    let mut result = Vec::new();
+
+    // assume set is sorted
+    // pung is always continuous (0, 1, 2)
+    // chow is possible in various way, get only (0, x, y) where x + y min
+    // FIXME: prove only "first" chow is needed
+    let pung_set = [set[0], set[1], set[2]];
+    let chow_indexes = [
+        [0, 1, 2],
+        [0, 1, 3],
+        [0, 1, 4],
+        [0, 1, 5],
+        [0, 2, 4],
+        [0, 2, 5],
+        [0, 2, 6],
+        [0, 3, 6],
+        [0, 3, 7],
+        [0, 4, 8],
+    ];
+
+    if is_pung(&pung_set) {
+        let new_meld = Meld::new(pung_set[0], MeldKind::ConcealedPung);
+        let new_sb = sb.clone().add_meld(new_meld).unwrap();
+        let meld_pos = [0, 1, 2];
+        let remains: ArrayVec<Tile, THREESET> = set
+            .iter()
+            .copied()
+            .enumerate()
+            .filter(|(i, _)| !meld_pos.contains(i))
+            .map(|(_, t)| t)
+            .collect();
+        assert_eq!(remains.len(), THREESET);
+
+        result.push((new_sb, remains));
+    }
+
+    for idx in chow_indexes.into_iter() {
+        let chow_set = [set[idx[0]], set[idx[1]], set[idx[2]]];
+        if is_chow(&chow_set) {
+            let new_meld = Meld::new(chow_set[0], MeldKind::ConcealedChow);
+            let new_sb = sb.clone().add_meld(new_meld).unwrap();
+            let remains: ArrayVec<Tile, THREESET> = set
+                .iter()
+                .copied()
+                .enumerate()
+                .filter(|(i, _)| !idx.contains(i))
+                .map(|(_, t)| t)
+                .collect();
+            assert_eq!(remains.len(), THREESET);
+
+            result.push((new_sb, remains));
+            break;
+        }
+    }
+
+    result
+}
+
+fn get_second_melds(set: &[Tile], sb: SetBuilder) -> Vec<(SetBuilder, ArrayVec<Tile, TWOSET>)> {
+    assert_eq!(set.len(), THREESET);
+    let mut result = Vec::new();
+    // TODO: rewrite without combinations and unique
+    // assume set is sorted
+    // pung is always continuous (0, 1, 2)
+    // chow is possible in various way, get only (0, x, y) where x + y min
+    // FIXME: prove only "first" chow is needed
+    let pung_set = [set[0], set[1], set[2]];
+    let chow_indexes = [
+        [0, 1, 2],
+        [0, 1, 3],
+        [0, 1, 4],
+        [0, 1, 5],
+        [0, 2, 4],
+        [0, 2, 5],
+        [0, 2, 6],
+        [0, 3, 6],
+        [0, 3, 7],
+        [0, 4, 8],
+    ];
+
+    if is_pung(&pung_set) {
+        let new_meld = Meld::new(pung_set[0], MeldKind::ConcealedPung);
+        let new_sb = sb.clone().add_meld(new_meld).unwrap();
+        let meld_pos = [0, 1, 2];
+        let remains: ArrayVec<Tile, TWOSET> = set
+            .iter()
+            .copied()
+            .enumerate()
+            .filter(|(i, _)| !meld_pos.contains(i))
+            .map(|(_, t)| t)
+            .collect();
+        assert_eq!(remains.len(), TWOSET);
+
+        result.push((new_sb, remains));
+    }
+
+    for idx in chow_indexes.into_iter() {
+        let chow_set = [set[idx[0]], set[idx[1]], set[idx[2]]];
+        if is_chow(&chow_set) {
+            let new_meld = Meld::new(chow_set[0], MeldKind::ConcealedChow);
+            let new_sb = sb.clone().add_meld(new_meld).unwrap();
+            let remains: ArrayVec<Tile, TWOSET> = set
+                .iter()
+                .copied()
+                .enumerate()
+                .filter(|(i, _)| !idx.contains(i))
+                .map(|(_, t)| t)
+                .collect();
+            assert_eq!(remains.len(), TWOSET);
+
+            result.push((new_sb, remains));
+            break;
+        }
+    }
+
+    result
+}
+
+fn get_third_melds(set: &[Tile], sb: SetBuilder) -> Vec<(SetBuilder, ArrayVec<Tile, ONESET>)> {
+    assert_eq!(set.len(), TWOSET);
+    let mut result = Vec::new();
+    // TODO: rewrite without combinations and unique
+    // assume set is sorted
+    // pung is always continuous (0, 1, 2)
+    // chow is possible in various way, get only (0, x, y) where x + y min
+    // FIXME: prove only "first" chow is needed
+    let pung_set = [set[0], set[1], set[2]];
+    let chow_indexes = [
+        [0, 1, 2],
+        [0, 1, 3],
+        [0, 1, 4],
+        [0, 1, 5],
+        [0, 2, 4],
+        [0, 2, 5],
+    ];
+
+    if is_pung(&pung_set) {
+        let new_meld = Meld::new(pung_set[0], MeldKind::ConcealedPung);
+        let new_sb = sb.clone().add_meld(new_meld).unwrap();
+        let meld_pos = [0, 1, 2];
+        let remains: ArrayVec<Tile, ONESET> = set
+            .iter()
+            .copied()
+            .enumerate()
+            .filter(|(i, _)| !meld_pos.contains(i))
+            .map(|(_, t)| t)
+            .collect();
+        assert_eq!(remains.len(), ONESET);
+
+        result.push((new_sb, remains));
+    }
+
+    for idx in chow_indexes.into_iter() {
+        let chow_set = [set[idx[0]], set[idx[1]], set[idx[2]]];
+        if is_chow(&chow_set) {
+            let new_meld = Meld::new(chow_set[0], MeldKind::ConcealedChow);
+            let new_sb = sb.clone().add_meld(new_meld).unwrap();
+            let remains: ArrayVec<Tile, ONESET> = set
+                .iter()
+                .copied()
+                .enumerate()
+                .filter(|(i, _)| !idx.contains(i))
+                .map(|(_, t)| t)
+                .collect();
+            assert_eq!(remains.len(), ONESET);
+
+            result.push((new_sb, remains));
+            break;
+        }
+    }
+
+    result
+}
+
+fn get_last_melds(set: &[Tile], sb: SetBuilder) -> Option<HandChecker> {
+    assert_eq!(set.len(), ONESET);
+    let chow = is_chow(set);
+    let pung = is_pung(set);
+    match chow || pung {
+        true => {
+            let new_meld = match (chow, pung) {
+                (true, false) => Meld::new(set[0], MeldKind::ConcealedChow),
+                (false, true) => Meld::new(set[0], MeldKind::ConcealedPung),
+                _ => unreachable!(),
+            };
+            Some(
+                sb.add_meld(new_meld)
+                    .ok()?
+                    .build()
+                    .ok()?
+                    .to_handchecker(Tile::East),
+            )
+        }
+        false => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use itertools::Itertools;
+
+    use super::*;
+    use crate::{hand::Hand, hand::HANDVARIANT, set::SETNUM};
+
+    #[test]
+    fn comb_all_pungs() {
+        let raw = [
+            b'A', b'A', b'B', b'B', b'B', b'C', b'C', b'C', b'D', b'D', b'D', b'E', b'E', b'E',
+        ];
+
+        assert_eq!(comb(&raw), 6 * 4 * 4 * 4 * 4);
+    }
+
+    #[test]
+    fn sanma_terminal_pungs() {
+        // 111m 999m 123p 456p 77s
+        let raw = *b"QQQYYYZ[\\]^_NN";
+        let sets = allsets(&raw);
+
+        assert_eq!(sets.len(), 1);
+        assert_eq!(sets[0].pair(), Tile::B7);
+        assert_eq!(comb(&raw), 4 * 4 * 4 * 4 * 4 * 4 * 4 * 4 * 6);
+    }
+
+    // 全順子形 over a tile set: total weight, weight of each hand and of 三色同順
+    fn chow_frequencies(tiles: &[Tile]) -> (u64, Vec<u64>, u64) {
+        let heads: Vec<Tile> = tiles
+            .iter()
+            .filter(|t| !t.is_honor() && t.number() <= 7)
+            .filter(|t| tiles.contains(&Tile::try_from(**t as usize + 2).unwrap()))
+            .cloned()
+            .collect();
+        let mut total = 0;
+        let mut hands = vec![0; HANDVARIANT];
+        let mut mixed = 0;
+        for &pair in tiles {
+            for melds in heads.iter().combinations_with_replacement(SETNUM) {
+                let drawn: Vec<Tile> = [pair, pair]
+                    .into_iter()
+                    .chain(melds.iter().flat_map(|h| {
+                        (0..3).map(move |i| Tile::try_from(**h as usize + i).unwrap())
+                    }))
+                    .collect();
+                if drawn
+                    .iter()
+                    .any(|t| drawn.iter().filter(|d| *d == t).count() > 4)
+                {
+                    continue;
+                }
+                let weight = tile_comb(drawn.into_iter());
+                let checker = melds
+                    .iter()
+                    .try_fold(SetBuilder::new().add_pair(pair), |sb, h| {
+                        sb.add_meld(Meld::new(**h, MeldKind::ConcealedChow))
+                    })
+                    .unwrap()
+                    .build()
+                    .unwrap()
+                    .to_handchecker(Tile::East);
+                checker
+                    .hands()
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, b)| **b)
+                    .for_each(|(i, _)| hands[i] += weight);
+                if melds.iter().combinations(3).any(|m| {
+                    m[0].number() == m[1].number()
+                        && m[1].number() == m[2].number()
+                        && !m[0].is_same_color(**m[1])
+                        && !m[1].is_same_color(**m[2])
+                        && !m[0].is_same_color(**m[2])
+                }) {
+                    mixed += weight;
+                }
+                total += weight;
+            }
+        }
+
+        (total, hands, mixed)
+    }
+
+    #[test]
+    fn chow_frequencies_differ_from_four_player() {
+        let four: Vec<Tile> = (0..TILEVARIANT)
+            .map(|i| Tile::try_from(i).unwrap())
+            .collect();
+        let sanma: Vec<Tile> = four
+            .iter()
+            .filter(|t| t.is_sanma() && !t.is_bonus())
+            .cloned()
+            .collect();
+        let (four_total, four_hands, four_mixed) = chow_frequencies(&four);
+        let (sanma_total, sanma_hands, sanma_mixed) = chow_frequencies(&sanma);
+        let freq = |hands: &[u64], total: u64, h: Hand| hands[h as usize] as f64 / total as f64;
+
+        // 三色同順 needs 2m-8m
+        assert!(four_mixed > 0);
+        assert_eq!(sanma_mixed, 0);
+        // fewer simples, two suits left to build a straight or a flush in
+        assert!(
+            freq(&sanma_hands, sanma_total, Hand::AllSimples)
+                < freq(&four_hands, four_total, Hand::AllSimples) * 0.6
+        );
+        assert!(
+            freq(&sanma_hands, sanma_total, Hand::PureStraight)
+                > freq(&four_hands, four_total, Hand::PureStraight) * 3.0
+        );
+        assert!(
+            freq(&sanma_hands, sanma_total, Hand::FullFlush)
+                > freq(&four_hands, four_total, Hand::FullFlush) * 3.0
+        );
+    }
+
+    #[test]
+    fn north_comb_weights() {
+        let norths = north_comb();
+        let weights: Vec<u64> = norths.iter().map(|(_, w)| *w).collect();
+        assert_eq!(weights, vec![16, 32, 24, 8, 1]);
+        assert_eq!(weights.iter().sum::<u64>(), 3u64.pow(NUKIVARIANT as u32));
+        assert!(!norths[0].0.any());
+        assert!(norths[4].0[Hand::FourNorths as usize]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn comb_rejects_removed_characters() {
+        comb(b"AABBBCCCDDDRRR");
+    }
+}
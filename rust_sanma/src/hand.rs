@@ -0,0 +1,182 @@
+use serde::{Deserialize, Serialize};
+
+pub const HANDVARIANT: usize = 32;
+#[allow(dead_code)]
+pub const HANDMAXSCORE: u16 = 32;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum Hand {
+    AllChows,              // 平和
+    RedPung,               // 役牌中
+    GreenPung,             // 役牌發
+    WhitePung,             // 役牌白
+    WindPung,              // 役牌自風, 東南西 by seat
+    PureStraight,          // 一氣通貫
+    AllPungs,              // 對對和
+    LittleThreeDragons,    // 小三元
+    BigThreeDragons,       // 大三元
+    AllSimples,            // 斷幺九
+    OutsideHands,          // 混全帶幺九
+    TerminalsInAllSets,    // 清全帶幺九
+    AllTerminalsAndHonors, // 混老頭
+    HalfFlush,             // 混一色
+    FullFlush,             // 清一色
+    AllHonors,             // 字一色
+    TwoConcealedPungs,     // 二暗刻
+    ThreeConcealedPungs,   // 三暗刻
+    FourConcealedPungs,    // 四暗刻
+    PureDoubleChow,        // 一般高
+    TwicePureDoubleChow,   // 二般高
+    PureTripleChow,        // 三同順
+    TriplePung,            // 三色同刻
+    PureShiftedPungs,      // 三連刻
+    FourPureShiftedPungs,  // 四連刻
+    AllTerminals,          // 清老頭
+    QuadrupleChow,         // 四同順
+    OneNorth,              // 拔北 1 枚
+    TwoNorths,             // 拔北 2 枚
+    ThreeNorths,           // 拔北 3 枚
+    FourNorths,            // 拔北 4 枚
+    NoPoint,               // 無役
+}
+
+#[allow(dead_code)]
+impl Hand {
+    pub fn score(&self) -> u16 {
+        match *self {
+            Self::AllChows => 1,
+            Self::RedPung => 2,
+            Self::GreenPung => 2,
+            Self::WhitePung => 2,
+            Self::WindPung => 2,
+            Self::PureStraight => 4,
+            Self::AllPungs => 4,
+            Self::LittleThreeDragons => 6,
+            Self::BigThreeDragons => 8,
+            Self::AllSimples => 1,
+            Self::OutsideHands => 4,
+            Self::TerminalsInAllSets => 6,
+            Self::AllTerminalsAndHonors => 12,
+            Self::HalfFlush => 4,
+            Self::FullFlush => 8,
+            Self::AllHonors => HANDMAXSCORE,
+            Self::TwoConcealedPungs => 1,
+            Self::ThreeConcealedPungs => 3,
+            Self::FourConcealedPungs => 4,
+            Self::PureDoubleChow => 1,
+            Self::TwicePureDoubleChow => 8,
+            Self::PureTripleChow => 12,
+            Self::TriplePung => 12,
+            Self::PureShiftedPungs => 4,
+            Self::FourPureShiftedPungs => 16,
+            // 絕對滿貫
+            Self::AllTerminals => HANDMAXSCORE,
+            Self::QuadrupleChow => HANDMAXSCORE,
+            // 拔北, dora not yaku
+            Self::OneNorth => 1,
+            Self::TwoNorths => 2,
+            Self::ThreeNorths => 3,
+            Self::FourNorths => 4,
+            // 無役
+            Self::NoPoint => 0,
+        }
+    }
+
+    // 拔北 of n 枚
+    pub fn norths(n: usize) -> Option<Hand> {
+        match n {
+            1 => Some(Self::OneNorth),
+            2 => Some(Self::TwoNorths),
+            3 => Some(Self::ThreeNorths),
+            4 => Some(Self::FourNorths),
+            _ => None,
+        }
+    }
+
+    // 絕對滿貫
+    pub fn is_limit(&self) -> bool {
+        matches!(
+            self,
+            Self::AllHonors | Self::AllTerminals | Self::QuadrupleChow
+        )
+    }
+
+    #[allow(dead_code)]
+    pub fn name(&self) -> String {
+        match self {
+            &Self::AllChows => "平和".to_string(),
+            &Self::RedPung | &Self::GreenPung | &Self::WhitePung | &Self::WindPung => {
+                "役牌".to_string()
+            }
+            &Self::PureStraight => "一氣".to_string(),
+            &Self::AllPungs => "對對和".to_string(),
+            &Self::LittleThreeDragons => "小三元".to_string(),
+            &Self::BigThreeDragons => "大三元".to_string(),
+            &Self::AllSimples => "斷幺九".to_string(),
+            &Self::OutsideHands => "混全帶".to_string(),
+            &Self::TerminalsInAllSets => "清全帶".to_string(),
+            &Self::AllTerminalsAndHonors => "混老頭".to_string(),
+            &Self::HalfFlush => "混一色".to_string(),
+            &Self::FullFlush => "清一色".to_string(),
+            &Self::AllHonors => "字一色".to_string(),
+            &Self::TwoConcealedPungs => "二暗刻".to_string(),
+            &Self::ThreeConcealedPungs => "三暗刻".to_string(),
+            &Self::FourConcealedPungs => "四暗刻".to_string(),
+            &Self::PureDoubleChow => "一般高".to_string(),
+            &Self::TwicePureDoubleChow => "二般高".to_string(),
+            &Self::PureTripleChow => "三同順".to_string(),
+            &Self::TriplePung => "三色刻".to_string(),
+            &Self::PureShiftedPungs => "三連刻".to_string(),
+            &Self::FourPureShiftedPungs => "四連刻".to_string(),
+            &Self::AllTerminals => "清老頭".to_string(),
+            &Self::QuadrupleChow => "四同順".to_string(),
+            &Self::OneNorth => "拔北一".to_string(),
+            &Self::TwoNorths => "拔北二".to_string(),
+            &Self::ThreeNorths => "拔北三".to_string(),
+            &Self::FourNorths => "拔北四".to_string(),
+            &Self::NoPoint => "無役".to_string(),
+        }
+    }
+}
+
+impl TryFrom<usize> for Hand {
+    type Error = ();
+
+    fn try_from(value: usize) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(Self::AllChows),
+            1 => Ok(Self::RedPung),
+            2 => Ok(Self::GreenPung),
+            3 => Ok(Self::WhitePung),
+            4 => Ok(Self::WindPung),
+            5 => Ok(Self::PureStraight),
+            6 => Ok(Self::AllPungs),
+            7 => Ok(Self::LittleThreeDragons),
+            8 => Ok(Self::BigThreeDragons),
+            9 => Ok(Self::AllSimples),
+            10 => Ok(Self::OutsideHands),
+            11 => Ok(Self::TerminalsInAllSets),
+            12 => Ok(Self::AllTerminalsAndHonors),
+            13 => Ok(Self::HalfFlush),
+            14 => Ok(Self::FullFlush),
+            15 => Ok(Self::AllHonors),
+            16 => Ok(Self::TwoConcealedPungs),
+            17 => Ok(Self::ThreeConcealedPungs),
+            18 => Ok(Self::FourConcealedPungs),
+            19 => Ok(Self::PureDoubleChow),
+            20 => Ok(Self::TwicePureDoubleChow),
+            21 => Ok(Self::PureTripleChow),
+            22 => Ok(Self::TriplePung),
+            23 => Ok(Self::PureShiftedPungs),
+            24 => Ok(Self::FourPureShiftedPungs),
+            25 => Ok(Self::AllTerminals),
+            26 => Ok(Self::QuadrupleChow),
+            27 => Ok(Self::OneNorth),
+            28 => Ok(Self::TwoNorths),
+            29 => Ok(Self::ThreeNorths),
+            30 => Ok(Self::FourNorths),
+            31 => Ok(Self::NoPoint),
+            _ => Err(()),
+        }
+    }
+}
@@ -0,0 +1,638 @@
+use arrayvec::ArrayVec;
+use bitvec::array::BitArray;
+use bitvec::BitArr;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::hand::Hand;
+use crate::hand::HANDMAXSCORE;
+use crate::hand::HANDVARIANT;
+use crate::set::{Meld, Set, SetData, SETNUM};
+use crate::tile::{Tile, TileColor};
+
+#[derive(Clone, Hash, PartialEq, Eq, Debug)]
+pub struct HandList(BitArr!(for HANDVARIANT, in u32));
+
+#[allow(dead_code)]
+impl HandList {
+    pub fn new() -> HandList {
+        HandList(BitArray::ZERO)
+    }
+
+    pub fn score(&self) -> u16 {
+        let s: u16 = self
+            .iter()
+            .enumerate()
+            .take(HANDVARIANT)
+            .map(|(i, b)| Hand::try_from(i).unwrap().score() * *b as u16)
+            .sum();
+
+        if s > HANDMAXSCORE {
+            HANDMAXSCORE
+        } else {
+            s
+        }
+    }
+
+    #[allow(dead_code)]
+    pub fn bits(&self) -> u32 {
+        self.0.as_raw_slice()[0]
+    }
+
+    #[allow(dead_code)]
+    pub fn from_bits(bits: u32) -> HandList {
+        HandList(BitArray::new([bits]))
+    }
+
+    // 拔北 is dora, not a yaku: 無役 stays 無役
+    #[allow(dead_code)]
+    pub fn with_bonus(&self, bonus: &HandList) -> HandList {
+        match self[Hand::NoPoint as usize] {
+            true => self.clone(),
+            false => {
+                let mut checker = self.clone();
+                *checker |= bonus.0;
+                limit_hands(checker)
+            }
+        }
+    }
+}
+
+// stable text form: list of hand names
+impl Serialize for HandList {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(
+            self.iter()
+                .enumerate()
+                .take(HANDVARIANT)
+                .filter(|(_, b)| **b)
+                .map(|(i, _)| Hand::try_from(i).unwrap()),
+        )
+    }
+}
+
+impl<'de> Deserialize<'de> for HandList {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let mut list = HandList::new();
+        Vec::<Hand>::deserialize(deserializer)?
+            .into_iter()
+            .for_each(|h| list.set(h as usize, true));
+        Ok(list)
+    }
+}
+
+impl std::ops::Deref for HandList {
+    type Target = BitArr!(for HANDVARIANT, in u32);
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl std::ops::DerefMut for HandList {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+// 絕對滿貫 clears every other hand, nothing left is 無役
+fn limit_hands(mut checker: HandList) -> HandList {
+    if checker
+        .iter()
+        .enumerate()
+        .take(HANDVARIANT)
+        .any(|(i, b)| *b && Hand::try_from(i).unwrap().is_limit())
+    {
+        checker
+            .iter_mut()
+            .enumerate()
+            .take(HANDVARIANT)
+            .filter(|(i, _)| !Hand::try_from(*i).unwrap().is_limit())
+            .for_each(|(_, mut c)| c.set(false));
+    }
+    if !checker.any() {
+        checker.set(Hand::NoPoint as usize, true);
+    }
+
+    checker
+}
+
+// 拔北 dora of the north tiles set aside
+#[allow(dead_code)]
+pub fn north_hands(norths: usize) -> HandList {
+    let mut checker = HandList::new();
+    if let Some(h) = Hand::norths(norths) {
+        checker.set(h as usize, true);
+    }
+    checker
+}
+
+impl Set {
+    pub fn to_handchecker(&self, wind: Tile) -> HandChecker {
+        HandChecker {
+            pair: self.pair,
+            melds: self.melds.clone(),
+            wind,
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Eq, Hash, Debug, Serialize, Deserialize)]
+#[serde(try_from = "HandCheckerData")]
+pub struct HandChecker {
+    pair: Tile,
+    melds: ArrayVec<Meld, SETNUM>,
+    wind: Tile, // 自風
+}
+
+#[derive(Deserialize)]
+struct HandCheckerData {
+    pair: Tile,
+    melds: Vec<Meld>,
+    wind: Tile,
+}
+
+impl TryFrom<HandCheckerData> for HandChecker {
+    type Error = String;
+
+    fn try_from(value: HandCheckerData) -> Result<Self, Self::Error> {
+        let set = Set::try_from(SetData {
+            pair: value.pair,
+            melds: value.melds,
+        })?;
+        Ok(set.to_handchecker(value.wind))
+    }
+}
+
+#[allow(dead_code)]
+impl HandChecker {
+    pub fn pair(&self) -> Tile {
+        self.pair
+    }
+
+    pub fn melds(&self) -> &ArrayVec<Meld, SETNUM> {
+        &self.melds
+    }
+
+    pub fn wind(&self) -> Tile {
+        self.wind
+    }
+
+    pub fn set_wind(&mut self, wind: Tile) {
+        self.wind = wind;
+    }
+
+    pub fn sort(&mut self) {
+        self.melds.sort();
+    }
+
+    pub fn hands(&self) -> HandList {
+        limit_hands(self.unlimited_hands())
+    }
+
+    // hands before limit hands clear the others
+    pub(crate) fn unlimited_hands(&self) -> HandList {
+        let mut checker = HandList::new();
+        // all_chow_pung must be first
+        self.all_chow_pung(&mut checker);
+        self.score_pungs(&mut checker);
+        self.honors(&mut checker);
+        self.straight_simple_terminal(&mut checker);
+        self.pure_mix(&mut checker);
+        self.conceal_pungs(&mut checker);
+        self.same_chows(&mut checker);
+        self.shift_pungs(&mut checker);
+
+        checker
+    }
+}
+
+impl Ord for HandChecker {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        match self.pair.cmp(&other.pair) {
+            std::cmp::Ordering::Equal => self.melds.cmp(&other.melds),
+            std::cmp::Ordering::Less => std::cmp::Ordering::Less,
+            std::cmp::Ordering::Greater => std::cmp::Ordering::Greater,
+        }
+    }
+}
+
+impl PartialOrd for HandChecker {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+macro_rules! perm3_match {
+    (($m1: expr, $m2: expr, $m3: expr), ($pat1: pat, $pat2: pat, $pat3: pat)) => {
+        match ($m1, $m2, $m3) {
+            ($pat1, $pat2, $pat3)
+            | ($pat1, $pat3, $pat2)
+            | ($pat2, $pat1, $pat3)
+            | ($pat2, $pat3, $pat1)
+            | ($pat3, $pat1, $pat2)
+            | ($pat3, $pat2, $pat1) => true,
+            _ => false,
+        }
+    };
+}
+
+macro_rules! comb3_fn {
+    ($self: ident, $f: ident, $checker: ident, $variant: expr) => {
+        if let true = $f($self.melds[0], $self.melds[1], $self.melds[2]) {
+            $checker.set($variant as usize, true);
+        }
+        if let true = $f($self.melds[0], $self.melds[1], $self.melds[3]) {
+            $checker.set($variant as usize, true);
+        }
+        if let true = $f($self.melds[0], $self.melds[2], $self.melds[3]) {
+            $checker.set($variant as usize, true);
+        }
+        if let true = $f($self.melds[1], $self.melds[2], $self.melds[3]) {
+            $checker.set($variant as usize, true);
+        }
+    };
+}
+
+macro_rules! comb2_fn {
+    ($self: ident, $f: ident, $checker: ident, $variant: expr) => {
+        if let true = $f($self.melds[0], $self.melds[1]) {
+            $checker.set($variant as usize, true);
+        }
+        if let true = $f($self.melds[0], $self.melds[2]) {
+            $checker.set($variant as usize, true);
+        }
+        if let true = $f($self.melds[0], $self.melds[3]) {
+            $checker.set($variant as usize, true);
+        }
+        if let true = $f($self.melds[1], $self.melds[2]) {
+            $checker.set($variant as usize, true);
+        }
+        if let true = $f($self.melds[1], $self.melds[3]) {
+            $checker.set($variant as usize, true);
+        }
+        if let true = $f($self.melds[2], $self.melds[3]) {
+            $checker.set($variant as usize, true);
+        }
+    };
+}
+
+impl HandChecker {
+    // 平和、對對和
+    fn all_chow_pung(&self, checker: &mut HandList) {
+        // 平和
+        if self.melds.iter().all(|m| m.kind.is_chow()) {
+            checker.set(Hand::AllChows as usize, true);
+            return;
+        }
+
+        // 對對
+        if self.melds.iter().all(|m| m.kind.is_pung()) {
+            checker.set(Hand::AllPungs as usize, true);
+        }
+    }
+
+    // 役牌
+    fn score_pungs(&self, checker: &mut HandList) {
+        if self.melds.iter().any(|m| m.head == Tile::Red) {
+            checker.set(Hand::RedPung as usize, true)
+        }
+
+        if self.melds.iter().any(|m| m.head == Tile::Green) {
+            checker.set(Hand::GreenPung as usize, true)
+        }
+
+        if self.melds.iter().any(|m| m.head == Tile::White) {
+            checker.set(Hand::WhitePung as usize, true)
+        }
+
+        if self.melds.iter().any(|m| m.head == self.wind) {
+            checker.set(Hand::WindPung as usize, true)
+        }
+    }
+
+    // 字牌類
+    fn honors(&self, checker: &mut HandList) {
+        // 三元和
+        let dragon_melds = self.melds.iter().filter(|m| m.head.is_dragon()).count();
+        match dragon_melds {
+            3 => {
+                checker.set(Hand::BigThreeDragons as usize, true);
+            }
+            2 if self.pair.is_dragon() => {
+                checker.set(Hand::LittleThreeDragons as usize, true);
+            }
+            _ => (),
+        }
+    }
+
+    // 一氣、幺九類
+    fn straight_simple_terminal(&self, checker: &mut HandList) {
+        // 一氣
+        let is_straight = |m1: Meld, m2: Meld, m3: Meld| -> bool {
+            match (m1.kind.is_chow(), m2.kind.is_chow(), m3.kind.is_chow()) {
+                (true, true, true) => {
+                    perm3_match!((m1.head, m2.head, m3.head), (Tile::B1, Tile::B4, Tile::B7))
+                        || perm3_match!((m1.head, m2.head, m3.head), (Tile::C1, Tile::C4, Tile::C7))
+                        || perm3_match!((m1.head, m2.head, m3.head), (Tile::D1, Tile::D4, Tile::D7))
+                }
+                _ => false,
+            }
+        };
+
+        comb3_fn!(self, is_straight, checker, Hand::PureStraight);
+        if checker[Hand::PureStraight as usize] {
+            return;
+        }
+
+        // 斷幺
+        let pair_simple = self.pair.is_simple();
+        let melds_simple = self.melds.iter().all(|m| match m.kind.is_chow() {
+            true => !matches!(
+                m.head,
+                Tile::B1 | Tile::B7 | Tile::C1 | Tile::C7 | Tile::D1 | Tile::D7
+            ),
+            false => m.head.is_simple(),
+        });
+
+        if let (true, true) = (pair_simple, melds_simple) {
+            checker.set(Hand::AllSimples as usize, true);
+            return;
+        }
+
+        // 清老、混老、清全、混全
+        match checker[Hand::AllPungs as usize] {
+            true => {
+                if let (true, true) = (
+                    self.pair.is_terminal(),
+                    self.melds.iter().all(|m| m.head.is_terminal()),
+                ) {
+                    checker.set(Hand::AllTerminals as usize, true);
+                    return;
+                }
+
+                if let (true, true) = (
+                    !self.pair.is_simple(),
+                    self.melds.iter().all(|m| !m.head.is_simple()),
+                ) {
+                    checker.set(Hand::AllTerminalsAndHonors as usize, true);
+                };
+            }
+            false => {
+                let pair_terminal = self.pair.is_terminal();
+                let melds_terminal = self.melds.iter().all(|m| match m.kind.is_chow() {
+                    true => matches!(
+                        m.head,
+                        Tile::B1 | Tile::B7 | Tile::C1 | Tile::C7 | Tile::D1 | Tile::D7
+                    ),
+                    false => m.head.is_terminal(),
+                });
+
+                if let (true, true) = (pair_terminal, melds_terminal) {
+                    checker.set(Hand::TerminalsInAllSets as usize, true);
+                    return;
+                }
+
+                let pair_honor_or_terminal = !pair_simple;
+                let melds_honor_or_terminal = self.melds.iter().all(|m| match m.kind.is_chow() {
+                    true => matches!(
+                        m.head,
+                        Tile::B1 | Tile::B7 | Tile::C1 | Tile::C7 | Tile::D1 | Tile::D7
+                    ),
+                    false => !m.head.is_simple(),
+                });
+
+                if let (true, true) = (pair_honor_or_terminal, melds_honor_or_terminal) {
+                    checker.set(Hand::OutsideHands as usize, true);
+                }
+            }
+        }
+    }
+
+    // 一色類、三色類
+    fn pure_mix(&self, checker: &mut HandList) {
+        // 字一色
+        let pair_honor = self.pair.is_honor();
+        let melds_honor = self.melds.iter().all(|m| m.head.is_honor());
+        if let (true, true) = (pair_honor, melds_honor) {
+            checker.set(Hand::AllHonors as usize, true);
+            return;
+        }
+
+        // 清一色
+        if self.melds.iter().all(|m| m.head.is_same_color(self.pair)) {
+            checker.set(Hand::FullFlush as usize, true);
+            return;
+        }
+
+        // 混一色
+        match self.pair.is_honor() {
+            true => {
+                // set first non-honor meld color
+                let color_tile = self
+                    .melds
+                    .iter()
+                    .filter(|m| !m.head.is_honor())
+                    .take(1)
+                    .map(|m| m.head)
+                    .next()
+                    .unwrap();
+
+                if self
+                    .melds
+                    .iter()
+                    .all(|m| m.head.is_same_color(color_tile) || m.head.is_honor())
+                {
+                    checker.set(Hand::HalfFlush as usize, true);
+                }
+            }
+            false => {
+                if self
+                    .melds
+                    .iter()
+                    .all(|m| m.head.is_same_color(self.pair) || m.head.is_honor())
+                {
+                    checker.set(Hand::HalfFlush as usize, true);
+                    return;
+                }
+            }
+        }
+
+        // 三色同刻
+        let is_mixed_triple_pung = |m1: Meld, m2: Meld, m3: Meld| -> bool {
+            match (m1.kind.is_pung(), m2.kind.is_pung(), m3.kind.is_pung()) {
+                (true, true, true) => {
+                    perm3_match!(
+                        (m1.head.color(), m2.head.color(), m3.head.color()),
+                        (TileColor::Bamboo, TileColor::Character, TileColor::Dot)
+                    ) && m1.head.number() == m2.head.number()
+                        && m2.head.number() == m3.head.number()
+                }
+                _ => false,
+            }
+        };
+
+        comb3_fn!(self, is_mixed_triple_pung, checker, Hand::TriplePung);
+    }
+
+    // 暗刻類
+    fn conceal_pungs(&self, checker: &mut HandList) {
+        // 四暗刻
+        if checker[Hand::AllPungs as usize] && self.melds.iter().all(|m| m.kind.is_concealed()) {
+            checker.set(Hand::FourConcealedPungs as usize, true);
+            return;
+        }
+
+        // 三暗刻
+        let is_three_conceal_pung = |m1: Meld, m2: Meld, m3: Meld| -> bool {
+            match (m1.kind.is_pung(), m2.kind.is_pung(), m3.kind.is_pung()) {
+                (true, true, true) => {
+                    m1.kind.is_concealed() && m2.kind.is_concealed() && m3.kind.is_concealed()
+                }
+                _ => false,
+            }
+        };
+
+        comb3_fn!(
+            self,
+            is_three_conceal_pung,
+            checker,
+            Hand::ThreeConcealedPungs
+        );
+        if checker[Hand::ThreeConcealedPungs as usize] {
+            return;
+        }
+
+        // 二暗刻
+        let is_two_conceal_pung = |m1: Meld, m2: Meld| -> bool {
+            match (m1.kind.is_pung(), m2.kind.is_pung()) {
+                (true, true) => m1.kind.is_concealed() && m2.kind.is_concealed(),
+                _ => false,
+            }
+        };
+
+        comb2_fn!(self, is_two_conceal_pung, checker, Hand::TwoConcealedPungs);
+    }
+    // 同順類
+    fn same_chows(&self, checker: &mut HandList) {
+        let is_same_chow = |m1: Meld, m2: Meld| -> bool {
+            match (m1.kind.is_chow(), m2.kind.is_chow()) {
+                (true, true) => m1.head == m2.head,
+                _ => false,
+            }
+        };
+
+        if checker[Hand::AllChows as usize] {
+            // 四同順
+            if self.melds.windows(2).all(|w| w[0].head == w[1].head) {
+                checker.set(Hand::QuadrupleChow as usize, true);
+                return;
+            }
+
+            // 二般高
+            match (
+                self.melds[0].head == self.melds[1].head
+                    && self.melds[2].head == self.melds[3].head,
+                self.melds[0].head == self.melds[2].head
+                    && self.melds[1].head == self.melds[3].head,
+                self.melds[0].head == self.melds[3].head
+                    && self.melds[1].head == self.melds[2].head,
+            ) {
+                (false, false, false) => {}
+                _ => {
+                    checker.set(Hand::TwicePureDoubleChow as usize, true);
+                    return;
+                }
+            }
+        }
+
+        let is_triple_chow =
+            |m1: Meld, m2: Meld, m3: Meld| -> bool { is_same_chow(m1, m2) && is_same_chow(m2, m3) };
+
+        comb3_fn!(self, is_triple_chow, checker, Hand::PureTripleChow);
+        if checker[Hand::PureTripleChow as usize] {
+            return;
+        }
+
+        comb2_fn!(self, is_same_chow, checker, Hand::PureDoubleChow);
+    }
+
+    // 連刻類
+    fn shift_pungs(&self, checker: &mut HandList) {
+        // 四連刻
+        if checker[Hand::AllPungs as usize]
+            && self
+                .melds
+                .windows(2)
+                .all(|w| w[0].head.is_same_color(w[1].head))
+            && !self.melds[0].head.is_honor()
+        {
+            let mut tmp: ArrayVec<u8, 4> =
+                self.melds.iter().cloned().map(|m| m.head as u8).collect();
+            tmp.sort();
+            let tmp: ArrayVec<Tile, 4> = tmp
+                .into_iter()
+                .map(|t| Tile::try_from((t + b'A') as char).unwrap())
+                .collect();
+            if tmp.windows(2).all(|w| w[0].is_ascending(w[1])) {
+                checker.set(Hand::FourPureShiftedPungs as usize, true);
+                return;
+            }
+        }
+
+        // 三連刻
+
+        let is_shifted_pungs = |m1: Meld, m2: Meld, m3: Meld| -> bool {
+            match (m1.kind.is_pung(), m2.kind.is_pung(), m3.kind.is_pung()) {
+                (true, true, true) => [
+                    m1.head.is_ascending(m2.head) && m2.head.is_ascending(m3.head),
+                    m1.head.is_ascending(m3.head) && m3.head.is_ascending(m2.head),
+                    m2.head.is_ascending(m1.head) && m1.head.is_ascending(m3.head),
+                    m2.head.is_ascending(m3.head) && m3.head.is_ascending(m1.head),
+                    m3.head.is_ascending(m1.head) && m1.head.is_ascending(m2.head),
+                    m3.head.is_ascending(m2.head) && m2.head.is_ascending(m1.head),
+                ]
+                .contains(&true),
+                _ => false,
+            }
+        };
+
+        comb3_fn!(self, is_shifted_pungs, checker, Hand::PureShiftedPungs);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn handlist_score() {
+        let hl = HandList::new();
+        assert_eq!(hl.score(), 0);
+    }
+
+    #[test]
+    fn handlist_pattern_score() {
+        let mut hl = HandList::new();
+        hl.set(Hand::AllChows as usize, true);
+        hl.set(Hand::HalfFlush as usize, true);
+        hl.set(Hand::TwicePureDoubleChow as usize, true);
+        hl.set(Hand::OutsideHands as usize, true);
+
+        assert_eq!(hl.score(), 17);
+    }
+
+    #[test]
+    fn north_is_dora_not_yaku() {
+        let mut hl = HandList::new();
+        hl.set(Hand::NoPoint as usize, true);
+        assert_eq!(hl.with_bonus(&north_hands(2)), hl);
+
+        let mut hl = HandList::new();
+        hl.set(Hand::AllChows as usize, true);
+        assert_eq!(hl.with_bonus(&north_hands(2)).score(), 3);
+
+        let mut hl = HandList::new();
+        hl.set(Hand::AllHonors as usize, true);
+        assert_eq!(hl.with_bonus(&north_hands(4)), hl);
+    }
+}
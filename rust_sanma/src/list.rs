@@ -0,0 +1,232 @@
+use arrayvec::ArrayVec;
+use itertools::Itertools;
+
+use pattern::VARIANT;
+use rust_common::pattern::write_patterns;
+use set::{Meld, MeldKind, Set, SetBuilder, HAINUM, SETNUM};
+use tile::{Tile, SANMAVARIANT, TILEVARIANT};
+
+mod pattern;
+mod set;
+mod tile;
+
+const SETKINDVARIANT: usize = 2_usize.pow(SETNUM as u32);
+// 北 is 拔北 and never in a pattern
+const PAIRNUM: usize = SANMAVARIANT - 1;
+// 字牌 and 1m, 9m head one pung, other tiles up to four melds
+const TILESELECTNUM: usize = PAIRNUM * 4 - 8 * 3;
+
+fn main() {
+    let pairs = get_pairs();
+
+    let kinds: ArrayVec<ArrayVec<MeldKind, SETNUM>, SETKINDVARIANT> =
+        std::iter::repeat_n(MeldKind::ConcealedChow, SETNUM)
+            .chain(std::iter::repeat_n(MeldKind::ConcealedPung, SETNUM))
+            .permutations(SETNUM)
+            .unique()
+            .map(|v| (&v as &[_]).try_into().unwrap())
+            .collect();
+
+    let meld_heads: ArrayVec<_, TILESELECTNUM> = pairs
+        .clone()
+        .into_iter()
+        .flat_map(|x| match x.is_pung_only() {
+            true => std::iter::repeat(x).take(1),
+            false => std::iter::repeat(x).take(4),
+        })
+        .collect();
+
+    let possible_sets: Vec<ArrayVec<Tile, HAINUM>> = pairs
+        .into_iter()
+        .flat_map(|p| {
+            let p_pos = meld_heads.iter().position(|&h| h == p).unwrap();
+            let heads: Vec<ArrayVec<Tile, SETNUM>> = meld_heads
+                .clone()
+                .into_iter()
+                .enumerate()
+                .filter_map(|(i, h)| remove_impossible_head(i, h, p_pos, p))
+                .tuple_combinations::<(_, _, _, _)>()
+                .unique()
+                .map(|(m1, m2, m3, m4)| ArrayVec::from([m1, m2, m3, m4]))
+                .collect();
+
+            let sb = SetBuilder::new().add_pair(p);
+            kinds
+                .clone()
+                .into_iter()
+                .flat_map(|ks| {
+                    heads
+                        .clone()
+                        .into_iter()
+                        .filter_map(|hs| generate_set(sb.clone(), &hs, &ks))
+                        .filter(is_valid_hai)
+                        .collect::<Vec<ArrayVec<Tile, HAINUM>>>()
+                })
+                .collect::<Vec<ArrayVec<Tile, HAINUM>>>()
+        })
+        .collect();
+
+    let mut possible_sets: Vec<ArrayVec<u8, HAINUM>> = possible_sets
+        .into_iter()
+        .map(|s| s.into_iter().map(|t| t.to_char() as u8).collect())
+        .collect();
+
+    possible_sets.sort();
+    possible_sets.dedup();
+
+    let tmp: Vec<u8> = possible_sets.into_iter().flatten().collect();
+    let filename = "patterns_rust_sanma.dat";
+    let generator = format!(
+        "{} list {}",
+        env!("CARGO_PKG_NAME"),
+        env!("CARGO_PKG_VERSION")
+    );
+    write_patterns(filename, &generator, VARIANT, pattern::HAINUM, &tmp)
+        .expect("cannot write all into the file");
+}
+
+fn get_pairs() -> ArrayVec<Tile, PAIRNUM> {
+    ArrayVec::from([
+        Tile::Red,
+        Tile::Green,
+        Tile::White,
+        Tile::East,
+        Tile::South,
+        Tile::West,
+        Tile::B1,
+        Tile::B2,
+        Tile::B3,
+        Tile::B4,
+        Tile::B5,
+        Tile::B6,
+        Tile::B7,
+        Tile::B8,
+        Tile::B9,
+        Tile::C1,
+        Tile::C9,
+        Tile::D1,
+        Tile::D2,
+        Tile::D3,
+        Tile::D4,
+        Tile::D5,
+        Tile::D6,
+        Tile::D7,
+        Tile::D8,
+        Tile::D9,
+    ])
+}
+
+fn remove_impossible_head(i: usize, h: Tile, p_pos: usize, p: Tile) -> Option<Tile> {
+    match p.is_pung_only() {
+        true => {
+            if i == p_pos {
+                None
+            } else {
+                Some(h)
+            }
+        }
+        false => {
+            if i == p_pos || i == p_pos + 1 {
+                None
+            } else {
+                Some(h)
+            }
+        }
+    }
+}
+
+fn generate_set(
+    sb: SetBuilder,
+    hs: &ArrayVec<Tile, SETNUM>,
+    ks: &ArrayVec<MeldKind, SETNUM>,
+) -> Option<ArrayVec<Tile, HAINUM>> {
+    sb.add_meld(Meld::new(hs[0], ks[0]))
+        .expect("failed to add 1st meld")
+        .add_meld(Meld::new(hs[1], ks[1]))
+        .expect("failed to add 2nd meld")
+        .add_meld(Meld::new(hs[2], ks[2]))
+        .expect("failed to add 3rd meld")
+        .add_meld(Meld::new(hs[3], ks[3]))
+        .expect("failed to add 4th meld")
+        .build()
+        .expect("cannot build set")
+        .to_arrayvec()
+}
+
+fn is_valid_hai(hai: &ArrayVec<Tile, HAINUM>) -> bool {
+    let mut counters = [0; TILEVARIANT];
+
+    hai.iter().for_each(|h| counters[*h as usize] += 1);
+
+    !counters.into_iter().any(|c| c > 4)
+}
+
+impl Tile {
+    fn to_char(self) -> char {
+        (self as u8 + b'A') as char
+    }
+}
+
+impl Meld {
+    fn tryinto_arrayvec(self) -> Result<ArrayVec<u8, 3>, Box<dyn std::error::Error>> {
+        match self.kind {
+            MeldKind::ConcealedChow => match self.head {
+                Tile::Red
+                | Tile::Green
+                | Tile::White
+                | Tile::East
+                | Tile::South
+                | Tile::West
+                | Tile::North
+                | Tile::B8
+                | Tile::B9
+                | Tile::C1
+                | Tile::C8
+                | Tile::C9
+                | Tile::D8
+                | Tile::D9 => Err("Not valid chow")?,
+                _ => Ok(ArrayVec::from([
+                    self.head as u8,
+                    self.head as u8 + 1,
+                    self.head as u8 + 2,
+                ])),
+            },
+            MeldKind::ConcealedPung => Ok(ArrayVec::from([
+                self.head as u8,
+                self.head as u8,
+                self.head as u8,
+            ])),
+            _ => unreachable!(),
+        }
+    }
+}
+
+impl Set {
+    fn to_arrayvec(&self) -> Option<ArrayVec<Tile, HAINUM>> {
+        let mut tmp = ArrayVec::<u8, HAINUM>::new_const();
+
+        tmp.push(self.pair as u8);
+        tmp.push(self.pair as u8);
+
+        for m in self.melds.clone().into_iter() {
+            match m.tryinto_arrayvec() {
+                Ok(a) => {
+                    tmp.push(a[0]);
+                    tmp.push(a[1]);
+                    tmp.push(a[2]);
+                }
+                Err(_) => None?,
+            }
+        }
+
+        assert!(tmp.is_full());
+
+        tmp.sort();
+
+        Some(
+            tmp.into_iter()
+                .map(|c| Tile::try_from((c + b'A') as char).unwrap())
+                .collect(),
+        )
+    }
+}
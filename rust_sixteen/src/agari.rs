@@ -0,0 +1,112 @@
+use std::{collections::HashMap, time::Instant};
+
+use rust_common::pattern::read_patterns;
+
+use crate::{
+    decompose::{allsets, comb, situation_comb},
+    hand::{Hand, HANDVARIANT},
+    handchecker::HandList,
+    pattern::{HAINUM, VARIANT},
+};
+
+mod decompose;
+mod hand;
+mod handchecker;
+mod pattern;
+mod set;
+mod tile;
+
+use tile::Tile;
+
+fn main() -> std::io::Result<()> {
+    let time_read = Instant::now();
+    let (header, raw_hai_sets) = read_patterns::<HAINUM>("patterns_rust_sixteen.dat", VARIANT)?;
+    assert_eq!(raw_hai_sets.len() as u64, header.records);
+    println!(
+        "time read and chunks in {:.2} s",
+        time_read.elapsed().as_secs_f32()
+    );
+
+    println!("produce hand patterns");
+    let start = Instant::now();
+    let mut hands: HashMap<HandList, (u64, u64)> = HashMap::new();
+    raw_hai_sets.into_iter().for_each(|raw_hai| {
+        let mut sets = allsets(&raw_hai);
+        sets.dedup();
+        let combinations = comb(&raw_hai);
+
+        sets.into_iter().for_each(|s| {
+            let list = s.hands();
+            let v = hands.entry(list).or_insert((0, 0));
+            v.0 += 1;
+            v.1 += combinations;
+        });
+    });
+
+    println!(
+        "time produce patterns in {:.2} s",
+        start.elapsed().as_secs_f32()
+    );
+
+    println!("calculate combinations and total scores");
+    // 台型、和牌形、組合數、總台數
+    // 門清、自摸、花牌 multiply the combinations by their weight out of 4^9
+    let mut result: Vec<(Hand, u64, u128, u128)> =
+        vec![(Hand::try_from(0).unwrap(), 0, 0, 0); HANDVARIANT];
+    result
+        .iter_mut()
+        .enumerate()
+        .for_each(|(i, (h, _, _, _))| *h = Hand::try_from(i).unwrap());
+    let situations = situation_comb(Tile::East);
+
+    hands
+        .into_iter()
+        .for_each(|(handlist, (pattern, combination))| {
+            // a pattern counts once for every hand some situation gives it
+            let mut seen = HandList::new();
+            situations.iter().for_each(|(situation, weight)| {
+                let list = handlist.with_situation(situation, Tile::East);
+                let combination = combination as u128 * *weight as u128;
+                let score = list.score() as u128;
+                *seen |= *list;
+                list.into_iter()
+                    .enumerate()
+                    .filter(|(_, h)| *h)
+                    .for_each(|(i, _)| {
+                        result[i].2 += combination;
+                        result[i].3 += combination * score;
+                    });
+            });
+            seen.into_iter()
+                .enumerate()
+                .filter(|(_, h)| *h)
+                .for_each(|(i, _)| result[i].1 += pattern);
+        });
+
+    // 三元牌合併
+    for i in [Hand::GreenPung as usize, Hand::WhitePung as usize] {
+        result[Hand::RedPung as usize].1 += result[i].1;
+        result[Hand::RedPung as usize].2 += result[i].2;
+        result[Hand::RedPung as usize].3 += result[i].3;
+    }
+
+    println!("end of process");
+    println!();
+
+    println!("{:4}{:8}{:21}{:4}", "台型", "和牌形", "組合數", "平均台數");
+    result
+        .into_iter()
+        .filter(|(h, _, _, _)| *h != Hand::GreenPung && *h != Hand::WhitePung)
+        .for_each(|(hand, pattern, combination, score)| {
+            println!(
+                "{:<4}{:>8}{:>24} {:>.*}",
+                hand.name(),
+                pattern,
+                combination,
+                5,
+                (score as f64 / combination as f64).to_string()
+            );
+        });
+
+    Ok(())
+}
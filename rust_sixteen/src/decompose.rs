@@ -0,0 +1,265 @@
+use arrayvec::ArrayVec;
+
+use crate::{
+    hand::Hand,
+    handchecker::{HandChecker, HandList, Situation},
+    set::{Meld, MeldKind, SetBuilder, HAINUM, SETNUM},
+    tile::{Tile, BONUSVARIANT, TILEVARIANT},
+};
+
+// main performance problem
+pub fn allsets(raw: &[u8]) -> Vec<HandChecker> {
+    assert_eq!(raw.len(), HAINUM);
+    let mut sorted_raw = raw.to_vec();
+    sorted_raw.sort();
+    let tiles: ArrayVec<Tile, HAINUM> = sorted_raw
+        .into_iter()
+        .map(|x| Tile::try_from(x as char).unwrap())
+        .collect();
+
+    // five melds, take one at a time and leave the last for get_last_melds
+    let mut partial = get_pairs(&tiles);
+    for _ in 1..SETNUM {
+        partial = partial
+            .into_iter()
+            .flat_map(|(sb, remains)| get_melds(&remains, sb))
+            .collect();
+    }
+
+    let mut result = Vec::new();
+    for (sb, remains) in partial.into_iter() {
+        if let Some(mut tmp) = get_last_melds(&remains, sb) {
+            tmp.sort();
+            result.push(tmp);
+        }
+    }
+
+    result
+}
+
+#[allow(dead_code)]
+pub fn comb(raw: &[u8]) -> u64 {
+    assert_eq!(raw.len(), HAINUM);
+    let mut counts = [0u64; TILEVARIANT];
+
+    raw.iter()
+        .map(|r| Tile::try_from(*r as char).unwrap())
+        .for_each(|t| counts[t as usize] += 1);
+
+    counts
+        .into_iter()
+        .map(|c| match c {
+            0 | 4 => 1,
+            1 | 3 => 4,
+            2 => 6,
+            _ => unreachable!(),
+        })
+        .product()
+}
+
+// 和牌情況 with their weight out of 4^9, the patterns are all concealed
+// each 花牌 is the winner's with 1/4 so n of them weigh 3^(8 - n), the winning
+// tile is self-drawn with 1/4 and discarded by one of the other three otherwise
+// situations giving the same hands, 平和 included, are merged
+#[allow(dead_code)]
+pub fn situation_comb(wind: Tile) -> Vec<(Situation, u64)> {
+    let mut all_chows = HandList::new();
+    all_chows.set(Hand::AllChows as usize, true);
+    let mut result: Vec<(Situation, u64)> = Vec::new();
+    for (self_drawn, drawn) in [(false, 3), (true, 1)] {
+        for mask in 0..1u32 << BONUSVARIANT {
+            let situation = Situation {
+                concealed: true,
+                self_drawn,
+                bonus: (0..BONUSVARIANT)
+                    .filter(|i| mask & (1 << i) != 0)
+                    .map(|i| Tile::try_from(TILEVARIANT + i).unwrap())
+                    .collect(),
+                ..Situation::default()
+            };
+            let weight = 3u64.pow(BONUSVARIANT as u32 - mask.count_ones()) * drawn;
+            let list = all_chows.with_situation(&situation, wind);
+            match result
+                .iter_mut()
+                .find(|(s, _)| all_chows.with_situation(s, wind) == list)
+            {
+                Some((_, w)) => *w += weight,
+                None => result.push((situation, weight)),
+            }
+        }
+    }
+
+    result
+}
+
+fn is_chow(meld: &[Tile]) -> bool {
+    match meld.len() {
+        3 => meld[0].is_ascending(meld[1]) && meld[1].is_ascending(meld[2]),
+        _ => false,
+    }
+}
+
+fn is_pung(meld: &[Tile]) -> bool {
+    match meld.len() {
+        3 => meld.windows(2).all(|w| w[0] == w[1]),
+        _ => false,
+    }
+}
+
+fn remove_subset<T>(main: &[T], subset: &[T]) -> Vec<T>
+where
+    T: PartialEq + Clone,
+{
+    let mut sub = subset.iter();
+    let mut current = sub.next();
+    let mut result = Vec::new();
+    for m in main {
+        match current {
+            Some(c) if m == c => current = sub.next(),
+            _ => result.push(m.clone()),
+        }
+    }
+
+    result
+}
+
+fn get_pairs(set: &[Tile]) -> Vec<(SetBuilder, Vec<Tile>)> {
+    assert_eq!(set.len(), HAINUM);
+    let pair_loc: Vec<usize> = set
+        .windows(2)
+        .enumerate()
+        .filter(|(_, w)| w[0] == w[1])
+        .map(|(i, _)| i)
+        .collect();
+    let dup_pair_loc: Vec<usize> = set
+        .windows(3)
+        .enumerate()
+        .filter(|(_, w)| w[0] == w[1] && w[1] == w[2])
+        .map(|(i, _)| i)
+        .collect();
+    let pair_indexes = remove_subset(&pair_loc, &dup_pair_loc);
+
+    let mut result = Vec::new();
+
+    for pi in pair_indexes.into_iter() {
+        let sb = SetBuilder::new().add_pair(set[pi]);
+        let remains: Vec<Tile> = set
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| *i != pi && *i != pi + 1)
+            .map(|(_, t)| *t)
+            .collect();
+
+        result.push((sb, remains));
+    }
+
+    result
+}
+
+// the first tile is either in a pung or heads a chow
+fn get_melds(set: &[Tile], sb: SetBuilder) -> Vec<(SetBuilder, Vec<Tile>)> {
+    assert!(set.len() > 3 && set.len().is_multiple_of(3));
+    let mut result = Vec::new();
+
+    // assume set is sorted
+    // pung is always continuous (0, 1, 2)
+    // chow takes the first copy of each following tile
+    let remove = |idx: [usize; 3]| -> Vec<Tile> {
+        set.iter()
+            .enumerate()
+            .filter(|(i, _)| !idx.contains(i))
+            .map(|(_, t)| *t)
+            .collect()
+    };
+
+    if is_pung(&set[0..3]) {
+        let new_meld = Meld::new(set[0], MeldKind::ConcealedPung);
+        let new_sb = sb.clone().add_meld(new_meld).unwrap();
+        result.push((new_sb, remove([0, 1, 2])));
+    }
+
+    let second = set.iter().position(|t| set[0].is_ascending(*t));
+    let third = second.and_then(|i| set.iter().position(|t| set[i].is_ascending(*t)));
+    if let (Some(i), Some(j)) = (second, third) {
+        let new_meld = Meld::new(set[0], MeldKind::ConcealedChow);
+        let new_sb = sb.add_meld(new_meld).unwrap();
+        result.push((new_sb, remove([0, i, j])));
+    }
+
+    result
+}
+
+fn get_last_melds(set: &[Tile], sb: SetBuilder) -> Option<HandChecker> {
+    assert_eq!(set.len(), 3);
+    let chow = is_chow(set);
+    let pung = is_pung(set);
+    match chow || pung {
+        true => {
+            let new_meld = match (chow, pung) {
+                (true, false) => Meld::new(set[0], MeldKind::ConcealedChow),
+                (false, true) => Meld::new(set[0], MeldKind::ConcealedPung),
+                _ => unreachable!(),
+            };
+            Some(
+                sb.add_meld(new_meld)
+                    .ok()?
+                    .build()
+                    .ok()?
+                    .to_handchecker(Tile::East),
+            )
+        }
+        false => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn comb_all_pungs() {
+        let raw = *b"AABBBCCCDDDEEEFFF";
+
+        assert_eq!(comb(&raw), 6 * 4 * 4 * 4 * 4 * 4);
+    }
+
+    #[test]
+    fn allsets_five_melds() {
+        // 111222333s 456m 789p 55p
+        let raw = *b"HHHIIIJJJTUV`ab^^";
+        let mut sets = allsets(&raw);
+        sets.dedup();
+
+        // three pungs or three chows in bamboo
+        assert_eq!(sets.len(), 2);
+        assert!(sets.iter().all(|s| s.pair() == Tile::D5));
+        assert!(sets.iter().all(|s| s.melds().len() == SETNUM));
+    }
+
+    #[test]
+    fn situation_weights() {
+        let classes = situation_comb(Tile::East);
+        assert_eq!(classes.iter().map(|(_, w)| w).sum::<u64>(), 4u64.pow(9));
+        let weight = |h: Hand| -> u64 {
+            classes
+                .iter()
+                .filter(|(s, _)| HandList::new().with_situation(s, Tile::East)[h as usize])
+                .map(|(_, w)| w)
+                .sum()
+        };
+        assert_eq!(weight(Hand::EightImmortals), 4);
+        assert_eq!(weight(Hand::ConcealedSelfDrawn), 4u64.pow(8));
+        assert_eq!(weight(Hand::Concealed), 3 * 4u64.pow(8));
+        // 平和 only without flowers and on a discard
+        let mut all_chows = HandList::new();
+        all_chows.set(Hand::AllChows as usize, true);
+        let kept: u64 = classes
+            .iter()
+            .filter(|(s, _)| all_chows.with_situation(s, Tile::East)[Hand::AllChows as usize])
+            .map(|(_, w)| w)
+            .sum();
+        assert_eq!(kept, 3 * 3u64.pow(8));
+        // 梅 and 春 held, 6 others free, minus 八仙過海, self-drawn or not
+        assert_eq!(weight(Hand::BothSeatFlowers), 4 * (4u64.pow(6) - 1));
+    }
+}
@@ -0,0 +1,143 @@
+use serde::{Deserialize, Serialize};
+
+pub const HANDVARIANT: usize = 25;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum Hand {
+    AllChows,            // 平和
+    Concealed,           // 門清
+    SelfDrawn,           // 自摸
+    ConcealedSelfDrawn,  // 門清自摸
+    SeatFlower,          // 正花
+    BothSeatFlowers,     // 雙正花
+    FlowerKong,          // 花槓
+    EightImmortals,      // 八仙過海
+    RedPung,             // 紅中
+    GreenPung,           // 青發
+    WhitePung,           // 白板
+    SeatWindPung,        // 門風
+    RoundWindPung,       // 圈風
+    AllPungs,            // 碰碰胡
+    ThreeConcealedPungs, // 三暗刻
+    FourConcealedPungs,  // 四暗刻
+    FiveConcealedPungs,  // 五暗刻
+    HalfFlush,           // 混一色
+    FullFlush,           // 清一色
+    AllHonors,           // 字一色
+    LittleThreeDragons,  // 小三元
+    BigThreeDragons,     // 大三元
+    LittleFourWinds,     // 小四喜
+    BigFourWinds,        // 大四喜
+    NoPoint,             // 屁胡
+}
+
+#[allow(dead_code)]
+impl Hand {
+    // 台數
+    pub fn score(&self) -> u16 {
+        match self {
+            Self::AllChows => 2,
+            Self::Concealed => 1,
+            Self::SelfDrawn => 1,
+            Self::ConcealedSelfDrawn => 3,
+            Self::SeatFlower => 1,
+            Self::BothSeatFlowers => 2,
+            Self::FlowerKong => 2,
+            Self::EightImmortals => 8,
+            Self::RedPung => 1,
+            Self::GreenPung => 1,
+            Self::WhitePung => 1,
+            Self::SeatWindPung => 1,
+            Self::RoundWindPung => 1,
+            Self::AllPungs => 4,
+            Self::ThreeConcealedPungs => 2,
+            Self::FourConcealedPungs => 5,
+            Self::FiveConcealedPungs => 8,
+            Self::HalfFlush => 4,
+            Self::FullFlush => 8,
+            Self::AllHonors => 16,
+            Self::LittleThreeDragons => 4,
+            Self::BigThreeDragons => 8,
+            Self::LittleFourWinds => 8,
+            Self::BigFourWinds => 16,
+            Self::NoPoint => 0,
+        }
+    }
+
+    // 依和牌情況, not read from tiles
+    pub fn is_situational(&self) -> bool {
+        matches!(
+            self,
+            Self::Concealed
+                | Self::SelfDrawn
+                | Self::ConcealedSelfDrawn
+                | Self::SeatFlower
+                | Self::BothSeatFlowers
+                | Self::FlowerKong
+                | Self::EightImmortals
+        )
+    }
+
+    pub fn name(&self) -> String {
+        match self {
+            Self::AllChows => "平和".to_string(),
+            Self::Concealed => "門清".to_string(),
+            Self::SelfDrawn => "自摸".to_string(),
+            Self::ConcealedSelfDrawn => "門清自摸".to_string(),
+            Self::SeatFlower => "正花".to_string(),
+            Self::BothSeatFlowers => "雙正花".to_string(),
+            Self::FlowerKong => "花槓".to_string(),
+            Self::EightImmortals => "八仙過海".to_string(),
+            Self::RedPung | Self::GreenPung | Self::WhitePung => "三元牌".to_string(),
+            Self::SeatWindPung => "門風".to_string(),
+            Self::RoundWindPung => "圈風".to_string(),
+            Self::AllPungs => "碰碰胡".to_string(),
+            Self::ThreeConcealedPungs => "三暗刻".to_string(),
+            Self::FourConcealedPungs => "四暗刻".to_string(),
+            Self::FiveConcealedPungs => "五暗刻".to_string(),
+            Self::HalfFlush => "混一色".to_string(),
+            Self::FullFlush => "清一色".to_string(),
+            Self::AllHonors => "字一色".to_string(),
+            Self::LittleThreeDragons => "小三元".to_string(),
+            Self::BigThreeDragons => "大三元".to_string(),
+            Self::LittleFourWinds => "小四喜".to_string(),
+            Self::BigFourWinds => "大四喜".to_string(),
+            Self::NoPoint => "屁胡".to_string(),
+        }
+    }
+}
+
+impl TryFrom<usize> for Hand {
+    type Error = ();
+
+    fn try_from(value: usize) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(Self::AllChows),
+            1 => Ok(Self::Concealed),
+            2 => Ok(Self::SelfDrawn),
+            3 => Ok(Self::ConcealedSelfDrawn),
+            4 => Ok(Self::SeatFlower),
+            5 => Ok(Self::BothSeatFlowers),
+            6 => Ok(Self::FlowerKong),
+            7 => Ok(Self::EightImmortals),
+            8 => Ok(Self::RedPung),
+            9 => Ok(Self::GreenPung),
+            10 => Ok(Self::WhitePung),
+            11 => Ok(Self::SeatWindPung),
+            12 => Ok(Self::RoundWindPung),
+            13 => Ok(Self::AllPungs),
+            14 => Ok(Self::ThreeConcealedPungs),
+            15 => Ok(Self::FourConcealedPungs),
+            16 => Ok(Self::FiveConcealedPungs),
+            17 => Ok(Self::HalfFlush),
+            18 => Ok(Self::FullFlush),
+            19 => Ok(Self::AllHonors),
+            20 => Ok(Self::LittleThreeDragons),
+            21 => Ok(Self::BigThreeDragons),
+            22 => Ok(Self::LittleFourWinds),
+            23 => Ok(Self::BigFourWinds),
+            24 => Ok(Self::NoPoint),
+            _ => Err(()),
+        }
+    }
+}
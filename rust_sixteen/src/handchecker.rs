@@ -0,0 +1,420 @@
+use arrayvec::ArrayVec;
+use bitvec::array::BitArray;
+use bitvec::BitArr;
+use itertools::Itertools;
+use serde::{Deserialize, Serialize};
+
+use crate::hand::Hand;
+use crate::hand::HANDVARIANT;
+use crate::set::{Meld, Set, SETNUM};
+use crate::tile::Tile;
+
+#[derive(Clone, Hash, PartialEq, Eq, Debug)]
+pub struct HandList(BitArr!(for HANDVARIANT, in u32));
+
+#[allow(dead_code)]
+impl HandList {
+    pub fn new() -> HandList {
+        HandList(BitArray::ZERO)
+    }
+
+    // 台數, no upper limit
+    pub fn score(&self) -> u16 {
+        self.iter()
+            .enumerate()
+            .take(HANDVARIANT)
+            .map(|(i, b)| Hand::try_from(i).unwrap().score() * *b as u16)
+            .sum()
+    }
+}
+
+impl std::ops::Deref for HandList {
+    type Target = BitArr!(for HANDVARIANT, in u32);
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl std::ops::DerefMut for HandList {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+// 和牌情況, default is a discard win with called melds and no flowers
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub struct Situation {
+    pub round: Tile, // 圈風
+    pub concealed: bool,
+    pub self_drawn: bool,
+    pub bonus: Vec<Tile>, // 花牌 set aside, F1-F4 梅蘭菊竹 and S1-S4 春夏秋冬
+}
+
+impl Default for Situation {
+    fn default() -> Self {
+        Self {
+            round: Tile::East,
+            concealed: false,
+            self_drawn: false,
+            bonus: Vec::new(),
+        }
+    }
+}
+
+impl Set {
+    pub fn to_handchecker(&self, wind: Tile) -> HandChecker {
+        HandChecker {
+            pair: self.pair,
+            melds: self.melds.clone(),
+            wind,
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub struct HandChecker {
+    pair: Tile,
+    melds: ArrayVec<Meld, SETNUM>,
+    wind: Tile, // 門風
+}
+
+#[allow(dead_code)]
+impl HandChecker {
+    pub fn pair(&self) -> Tile {
+        self.pair
+    }
+
+    pub fn melds(&self) -> &ArrayVec<Meld, SETNUM> {
+        &self.melds
+    }
+
+    pub fn wind(&self) -> Tile {
+        self.wind
+    }
+
+    pub fn set_wind(&mut self, wind: Tile) {
+        self.wind = wind;
+    }
+
+    pub fn sort(&mut self) {
+        self.melds.sort();
+    }
+
+    pub fn hands(&self) -> HandList {
+        self.hands_with(&Situation::default())
+    }
+
+    pub fn hands_with(&self, situation: &Situation) -> HandList {
+        let mut checker = HandList::new();
+        self.all_chow_pung(&mut checker);
+        self.score_pungs(&mut checker, situation);
+        self.honors(&mut checker);
+        self.pure_mix(&mut checker);
+        self.conceal_pungs(&mut checker);
+
+        checker.with_situation(situation, self.wind)
+    }
+}
+
+impl HandList {
+    // 門清、自摸、花牌 on top of the hands read from the tiles, as `hands_with`
+    // 平和 is lost with flowers or self-drawn, 圈風 stays as read from the tiles
+    pub fn with_situation(&self, situation: &Situation, wind: Tile) -> HandList {
+        let mut checker = self.clone();
+        checker.set(Hand::NoPoint as usize, false);
+        if situation.self_drawn || !situation.bonus.is_empty() {
+            checker.set(Hand::AllChows as usize, false);
+        }
+        *checker |= situation_hands(situation, wind).0;
+
+        if !checker.any() {
+            checker.set(Hand::NoPoint as usize, true);
+        }
+
+        checker
+    }
+}
+
+// 門清、自摸、花牌
+pub fn situation_hands(situation: &Situation, wind: Tile) -> HandList {
+    let mut checker = HandList::new();
+    match (situation.concealed, situation.self_drawn) {
+        (true, true) => checker.set(Hand::ConcealedSelfDrawn as usize, true),
+        (true, false) => checker.set(Hand::Concealed as usize, true),
+        (false, true) => checker.set(Hand::SelfDrawn as usize, true),
+        (false, false) => (),
+    }
+
+    // 八仙過海
+    let bonus = &situation.bonus;
+    let flowers = bonus.iter().filter(|t| t.is_flower()).unique().count();
+    let seasons = bonus.iter().filter(|t| t.is_season()).unique().count();
+    if flowers == 4 && seasons == 4 {
+        checker.set(Hand::EightImmortals as usize, true);
+        return checker;
+    }
+
+    // 花槓, all four flowers or all four seasons
+    if flowers == 4 || seasons == 4 {
+        checker.set(Hand::FlowerKong as usize, true);
+    }
+
+    // 正花, the flower and season of the seat wind
+    match bonus
+        .iter()
+        .filter(|t| t.is_seat_bonus(wind))
+        .unique()
+        .count()
+    {
+        2 => checker.set(Hand::BothSeatFlowers as usize, true),
+        1 => checker.set(Hand::SeatFlower as usize, true),
+        _ => (),
+    }
+
+    checker
+}
+
+impl Ord for HandChecker {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        match self.pair.cmp(&other.pair) {
+            std::cmp::Ordering::Equal => self.melds.cmp(&other.melds),
+            std::cmp::Ordering::Less => std::cmp::Ordering::Less,
+            std::cmp::Ordering::Greater => std::cmp::Ordering::Greater,
+        }
+    }
+}
+
+impl PartialOrd for HandChecker {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl HandChecker {
+    // 平和、碰碰胡
+    fn all_chow_pung(&self, checker: &mut HandList) {
+        // 平和: no honors, flowers and self-drawn are left to `with_situation`
+        if self.melds.iter().all(|m| m.kind.is_chow()) {
+            if !self.pair.is_honor() {
+                checker.set(Hand::AllChows as usize, true);
+            }
+            return;
+        }
+
+        // 碰碰胡
+        if self.melds.iter().all(|m| m.kind.is_pung()) {
+            checker.set(Hand::AllPungs as usize, true);
+        }
+    }
+
+    // 三元牌、風牌
+    fn score_pungs(&self, checker: &mut HandList, situation: &Situation) {
+        if self.melds.iter().any(|m| m.head == Tile::Red) {
+            checker.set(Hand::RedPung as usize, true);
+        }
+
+        if self.melds.iter().any(|m| m.head == Tile::Green) {
+            checker.set(Hand::GreenPung as usize, true);
+        }
+
+        if self.melds.iter().any(|m| m.head == Tile::White) {
+            checker.set(Hand::WhitePung as usize, true);
+        }
+
+        if self.melds.iter().any(|m| m.head == self.wind) {
+            checker.set(Hand::SeatWindPung as usize, true);
+        }
+
+        if self.melds.iter().any(|m| m.head == situation.round) {
+            checker.set(Hand::RoundWindPung as usize, true);
+        }
+    }
+
+    // 四喜、三元, replacing the single pungs
+    fn honors(&self, checker: &mut HandList) {
+        let wind_melds = self.melds.iter().filter(|m| m.head.is_wind()).count();
+        match (wind_melds, self.pair.is_wind()) {
+            (4, _) => checker.set(Hand::BigFourWinds as usize, true),
+            (3, true) => checker.set(Hand::LittleFourWinds as usize, true),
+            _ => (),
+        }
+        if wind_melds == 4 || checker[Hand::LittleFourWinds as usize] {
+            checker.set(Hand::SeatWindPung as usize, false);
+            checker.set(Hand::RoundWindPung as usize, false);
+        }
+
+        let dragon_melds = self.melds.iter().filter(|m| m.head.is_dragon()).count();
+        match (dragon_melds, self.pair.is_dragon()) {
+            (3, _) => checker.set(Hand::BigThreeDragons as usize, true),
+            (2, true) => checker.set(Hand::LittleThreeDragons as usize, true),
+            _ => (),
+        }
+        if dragon_melds == 3 || checker[Hand::LittleThreeDragons as usize] {
+            checker.set(Hand::RedPung as usize, false);
+            checker.set(Hand::GreenPung as usize, false);
+            checker.set(Hand::WhitePung as usize, false);
+        }
+    }
+
+    // 一色類
+    fn pure_mix(&self, checker: &mut HandList) {
+        // 字一色
+        let pair_honor = self.pair.is_honor();
+        let melds_honor = self.melds.iter().all(|m| m.head.is_honor());
+        if let (true, true) = (pair_honor, melds_honor) {
+            checker.set(Hand::AllHonors as usize, true);
+            return;
+        }
+
+        let color_tile = match pair_honor {
+            true => self
+                .melds
+                .iter()
+                .find(|m| !m.head.is_honor())
+                .map(|m| m.head)
+                .unwrap(),
+            false => self.pair,
+        };
+        let same_color = |t: Tile| t.is_same_color(color_tile);
+
+        // 清一色
+        if let (false, true) = (pair_honor, self.melds.iter().all(|m| same_color(m.head))) {
+            checker.set(Hand::FullFlush as usize, true);
+            return;
+        }
+
+        // 混一色
+        if self
+            .melds
+            .iter()
+            .all(|m| same_color(m.head) || m.head.is_honor())
+        {
+            checker.set(Hand::HalfFlush as usize, true);
+        }
+    }
+
+    // 暗刻類
+    fn conceal_pungs(&self, checker: &mut HandList) {
+        let concealed = self
+            .melds
+            .iter()
+            .filter(|m| m.kind.is_pung() && m.kind.is_concealed())
+            .count();
+        match concealed {
+            5 => checker.set(Hand::FiveConcealedPungs as usize, true),
+            4 => checker.set(Hand::FourConcealedPungs as usize, true),
+            3 => checker.set(Hand::ThreeConcealedPungs as usize, true),
+            _ => (),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::{
+        set::{MeldKind, SetBuilder},
+        tile::BONUSVARIANT,
+    };
+
+    fn checker(pair: Tile, melds: &[(Tile, MeldKind)], wind: Tile) -> HandChecker {
+        melds
+            .iter()
+            .fold(SetBuilder::new().add_pair(pair), |sb, (h, k)| {
+                sb.add_meld(Meld::new(*h, *k)).unwrap()
+            })
+            .build()
+            .unwrap()
+            .to_handchecker(wind)
+    }
+
+    #[test]
+    fn handlist_score() {
+        let hl = HandList::new();
+        assert_eq!(hl.score(), 0);
+    }
+
+    #[test]
+    fn all_chows_and_situation() {
+        let hc = checker(
+            Tile::C5,
+            &[
+                (Tile::B1, MeldKind::ConcealedChow),
+                (Tile::B4, MeldKind::ConcealedChow),
+                (Tile::C2, MeldKind::ConcealedChow),
+                (Tile::D3, MeldKind::ConcealedChow),
+                (Tile::D6, MeldKind::ConcealedChow),
+            ],
+            Tile::East,
+        );
+        let hl = hc.hands();
+        assert!(hl[Hand::AllChows as usize]);
+        assert_eq!(hl.score(), 2);
+
+        // 平和 is lost with flowers or self-drawn
+        let situation = Situation {
+            concealed: true,
+            self_drawn: true,
+            bonus: vec![Tile::F1, Tile::S1],
+            ..Situation::default()
+        };
+        let hl = hc.hands_with(&situation);
+        assert!(!hl[Hand::AllChows as usize]);
+        assert!(hl[Hand::ConcealedSelfDrawn as usize]);
+        assert!(hl[Hand::BothSeatFlowers as usize]);
+        assert_eq!(hl.score(), 5);
+
+        let situation = Situation {
+            bonus: (Tile::F1 as usize..Tile::F1 as usize + BONUSVARIANT)
+                .map(|i| Tile::try_from(i).unwrap())
+                .collect(),
+            ..Situation::default()
+        };
+        let hl = hc.hands_with(&situation);
+        assert!(hl[Hand::EightImmortals as usize]);
+        assert_eq!(hl.score(), 8);
+    }
+
+    #[test]
+    fn honors_replace_pungs() {
+        let hc = checker(
+            Tile::North,
+            &[
+                (Tile::East, MeldKind::ConcealedPung),
+                (Tile::South, MeldKind::ConcealedPung),
+                (Tile::West, MeldKind::ConcealedPung),
+                (Tile::Red, MeldKind::ConcealedPung),
+                (Tile::C1, MeldKind::ConcealedChow),
+            ],
+            Tile::South,
+        );
+        let hl = hc.hands();
+        assert!(hl[Hand::LittleFourWinds as usize]);
+        assert!(!hl[Hand::SeatWindPung as usize]);
+        assert!(!hl[Hand::RoundWindPung as usize]);
+        assert!(hl[Hand::RedPung as usize]);
+        assert!(hl[Hand::HalfFlush as usize]);
+        assert!(hl[Hand::FourConcealedPungs as usize]);
+        assert_eq!(hl.score(), 8 + 1 + 4 + 5);
+
+        let hc = checker(
+            Tile::East,
+            &[
+                (Tile::Red, MeldKind::ConcealedPung),
+                (Tile::Green, MeldKind::ConcealedPung),
+                (Tile::White, MeldKind::RevealedPung),
+                (Tile::South, MeldKind::RevealedPung),
+                (Tile::North, MeldKind::RevealedPung),
+            ],
+            Tile::West,
+        );
+        let hl = hc.hands();
+        assert!(hl[Hand::BigThreeDragons as usize]);
+        assert!(hl[Hand::AllHonors as usize]);
+        assert!(hl[Hand::AllPungs as usize]);
+        assert!(!hl[Hand::RedPung as usize]);
+        assert_eq!(hl.score(), 8 + 16 + 4);
+    }
+}
@@ -0,0 +1,148 @@
+use arrayvec::ArrayVec;
+use serde::{Deserialize, Serialize};
+
+use crate::tile::Tile;
+
+pub const HAINUM: usize = 17;
+pub const SETNUM: usize = HAINUM / 3;
+
+#[allow(dead_code)]
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum MeldKind {
+    RevealedChow,  // 明順
+    ConcealedChow, // 暗順
+    RevealedPung,  // 明刻
+    ConcealedPung, // 暗刻
+    RevealedKong,  // 明槓
+    ConcealedKong, // 暗槓
+}
+
+#[allow(dead_code)]
+impl MeldKind {
+    pub fn is_chow(&self) -> bool {
+        matches!(*self, Self::RevealedChow | Self::ConcealedChow)
+    }
+
+    pub fn is_pung(&self) -> bool {
+        !self.is_chow()
+    }
+
+    pub fn is_kong(&self) -> bool {
+        matches!(*self, Self::RevealedKong | Self::ConcealedKong)
+    }
+
+    pub fn is_revealed(&self) -> bool {
+        matches!(
+            *self,
+            Self::RevealedChow | Self::RevealedPung | Self::RevealedKong
+        )
+    }
+
+    pub fn is_concealed(&self) -> bool {
+        !self.is_revealed()
+    }
+}
+
+// 面子
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Serialize, Deserialize)]
+pub struct Meld {
+    pub(crate) head: Tile,
+    pub(crate) kind: MeldKind,
+}
+
+impl Meld {
+    pub fn new(head: Tile, kind: MeldKind) -> Self {
+        Self { head, kind }
+    }
+}
+
+#[allow(dead_code)]
+#[derive(Debug, Clone)]
+pub struct SetBuilder {
+    pair: Option<Tile>,
+    melds: ArrayVec<Meld, SETNUM>,
+}
+
+#[allow(dead_code)]
+impl SetBuilder {
+    pub fn new() -> Self {
+        let melds = ArrayVec::<_, SETNUM>::new_const();
+        Self { pair: None, melds }
+    }
+
+    pub fn add_pair(mut self, p: Tile) -> Self {
+        self.pair = Some(p);
+        self
+    }
+
+    pub fn add_meld(mut self, m: Meld) -> Result<Self, Box<dyn std::error::Error>> {
+        match self.melds.is_full() {
+            true => Err("Already full of melds")?,
+            false => {
+                self.melds.push(m);
+                Ok(self)
+            }
+        }
+    }
+
+    pub fn build(self) -> Result<Set, Box<dyn std::error::Error>> {
+        match self.melds.is_full() {
+            true => {
+                let pair = self.pair.ok_or("No assigned pair tile")?;
+                Ok(Set {
+                    pair,
+                    melds: self.melds,
+                })
+            }
+            _ => Err("Not valid set")?,
+        }
+    }
+}
+
+impl Ord for Meld {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        match self.head.cmp(&other.head) {
+            std::cmp::Ordering::Equal => self.kind.cmp(&other.kind),
+            std::cmp::Ordering::Less => std::cmp::Ordering::Less,
+            std::cmp::Ordering::Greater => std::cmp::Ordering::Greater,
+        }
+    }
+}
+
+impl PartialOrd for Meld {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+// TODO: more general Set, contains open hands
+#[allow(dead_code)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "SetData")]
+pub struct Set {
+    pub(crate) pair: Tile,
+    pub(crate) melds: ArrayVec<Meld, SETNUM>,
+}
+
+// unchecked Set, only complete sets are accepted
+#[allow(dead_code)]
+#[derive(Deserialize)]
+pub(crate) struct SetData {
+    pub(crate) pair: Tile,
+    pub(crate) melds: Vec<Meld>,
+}
+
+impl TryFrom<SetData> for Set {
+    type Error = String;
+
+    fn try_from(value: SetData) -> Result<Self, Self::Error> {
+        value
+            .melds
+            .into_iter()
+            .try_fold(SetBuilder::new().add_pair(value.pair), |sb, m| {
+                sb.add_meld(m)
+            })
+            .and_then(|sb| sb.build())
+            .map_err(|e| e.to_string())
+    }
+}
@@ -0,0 +1,335 @@
+use arrayvec::ArrayVec;
+
+use crate::{
+    handchecker::{bonus_hands, HandChecker, HandList},
+    set::{Meld, MeldKind, SetBuilder, HAINUM},
+    tile::{Tile, BONUSVARIANT, TILEVARIANT},
+};
+
+// main performance problem
+// 花牌 are set aside and never part of a meld
+pub fn allsets(raw: &[u8]) -> Vec<HandChecker> {
+    let mut sorted_raw: Vec<Tile> = raw
+        .iter()
+        .map(|x| Tile::try_from(*x as char).unwrap())
+        .filter(|t| !t.is_bonus())
+        .collect();
+    assert_eq!(sorted_raw.len(), HAINUM);
+    sorted_raw.sort();
+    let tiles: ArrayVec<Tile, HAINUM> = sorted_raw.into_iter().collect();
+
+    let tmp3 = get_pairs(&tiles);
+    let mut tmp2 = Vec::new();
+    for (sb, remains) in tmp3.into_iter() {
+        let mut tmp = get_first_melds(&remains, sb);
+        tmp2.append(&mut tmp);
+    }
+    let mut tmp1 = Vec::new();
+    for (sb, remains) in tmp2.into_iter() {
+        let mut tmp = get_second_melds(&remains, sb);
+        tmp1.append(&mut tmp);
+    }
+    let mut result = Vec::new();
+    for (sb, remains) in tmp1.into_iter() {
+        let tmp = get_last_melds(&remains, sb);
+        if let Some(mut tmp) = tmp {
+            tmp.sort();
+            result.push(tmp);
+        }
+    }
+
+    result
+}
+
+#[allow(dead_code)]
+pub fn comb(raw: &[u8]) -> u64 {
+    comb_available(raw, &[4; TILEVARIANT])
+}
+
+// 超幾何: C(available, held) for every tile, 0 when too few are left
+#[allow(dead_code)]
+pub fn comb_available(raw: &[u8], available: &[u64; TILEVARIANT]) -> u64 {
+    let mut counts = [0u64; TILEVARIANT];
+
+    // one copy of each 花牌, so they do not change the weight
+    raw.iter()
+        .map(|r| Tile::try_from(*r as char).unwrap())
+        .filter(|t| !t.is_bonus())
+        .for_each(|t| counts[t as usize] += 1);
+    assert_eq!(counts.iter().sum::<u64>(), HAINUM as u64);
+
+    counts
+        .into_iter()
+        .zip(available.iter())
+        .map(|(c, a)| match c <= *a {
+            true => (0..c).fold(1, |acc, i| acc * (a - i) / (i + 1)),
+            false => 0,
+        })
+        .product()
+}
+
+// 花牌 hands with their weight out of 4^8
+// each bonus tile is the winner's with 1/4, so n of them weigh 3^(8 - n)
+#[allow(dead_code)]
+pub fn bonus_comb(wind: Tile) -> Vec<(HandList, u64)> {
+    let mut result: Vec<(HandList, u64)> = Vec::new();
+    for mask in 0..1u32 << BONUSVARIANT {
+        let bonus: Vec<Tile> = (0..BONUSVARIANT)
+            .filter(|i| mask & (1 << i) != 0)
+            .map(|i| Tile::try_from((b'A' + (TILEVARIANT + i) as u8) as char).unwrap())
+            .collect();
+        let list = bonus_hands(&bonus, wind);
+        let weight = 3u64.pow(BONUSVARIANT as u32 - mask.count_ones());
+        match result.iter_mut().find(|(l, _)| *l == list) {
+            Some((_, w)) => *w += weight,
+            None => result.push((list, weight)),
+        }
+    }
+
+    result
+}
+
+fn is_chow(meld: &[Tile]) -> bool {
+    match meld.len() {
+        3 => meld[0].is_ascending(meld[1]) && meld[1].is_ascending(meld[2]),
+        _ => false,
+    }
+}
+
+fn is_pung(meld: &[Tile]) -> bool {
+    match meld.len() {
+        3 => meld.windows(2).all(|w| w[0] == w[1]),
+        _ => false,
+    }
+}
+
+fn remove_subset<T>(main: &Vec<T>, subset: &Vec<T>) -> Vec<T>
+where
+    T: PartialEq + Clone,
+{
+    let mut sub = subset.iter();
+    let mut current = sub.next();
+    let mut result = Vec::new();
+    for m in main {
+        match current {
+            Some(c) if m == c => current = sub.next(),
+            _ => result.push(m.clone()),
+        }
+    }
+
+    result
+}
+
+const THREESET: usize = 3 * 3;
+const TWOSET: usize = 2 * 3;
+const ONESET: usize = 1 * 3;
+
+fn get_pairs(set: &[Tile]) -> Vec<(SetBuilder, ArrayVec<Tile, THREESET>)> {
+    assert_eq!(set.len(), HAINUM);
+    let pair_loc = set
+        .windows(2)
+        .enumerate()
+        .filter(|(_, w)| w[0] == w[1])
+        .map(|(i, _)| i)
+        .collect();
+    let dup_pair_loc = set
+        .windows(3)
+        .enumerate()
+        .filter(|(_, w)| w[0] == w[1] && w[1] == w[2])
+        .map(|(i, _)| i)
+        .collect();
+    let pair_indexes: Vec<usize> = remove_subset(&pair_loc, &dup_pair_loc);
+
+    let mut result = Vec::new();
+
+    for pi in pair_indexes.into_iter() {
+        let sb = SetBuilder::new().add_pair(set[pi]);
+        let remains: ArrayVec<Tile, THREESET> = set
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| *i != pi && *i != pi + 1)
+            .map(|(_, t)| *t)
+            .collect();
+
+        result.push((sb, remains));
+    }
+
+    result
+}
+
+fn get_first_melds(set: &[Tile], sb: SetBuilder) -> Vec<(SetBuilder, ArrayVec<Tile, TWOSET>)> {
+    assert_eq!(set.len(), THREESET);
+    let mut result = Vec::new();
+
+    // assume set is sorted
+    // pung is always continuous (0, 1, 2)
+    // chow is possible in various way, get only (0, x, y) where x + y min
+    // FIXME: prove only "first" chow is needed
+    let pung_set = [set[0], set[1], set[2]];
+    let chow_indexes = [
+        [0, 1, 2],
+        [0, 1, 3],
+        [0, 1, 4],
+        [0, 1, 5],
+        [0, 2, 4],
+        [0, 2, 5],
+        [0, 2, 6],
+        [0, 3, 6],
+        [0, 3, 7],
+        [0, 4, 8],
+    ];
+
+    if is_pung(&pung_set) {
+        let new_meld = Meld::new(pung_set[0], MeldKind::ConcealedPung);
+        let new_sb = sb.clone().add_meld(new_meld).unwrap();
+        let meld_pos = [0, 1, 2];
+        let remains: ArrayVec<Tile, TWOSET> = set
+            .to_owned()
+            .into_iter()
+            .enumerate()
+            .filter(|(i, _)| !meld_pos.contains(i))
+            .map(|(_, t)| t)
+            .collect();
+        assert_eq!(remains.len(), TWOSET);
+
+        result.push((new_sb, remains));
+    }
+
+    for idx in chow_indexes.into_iter() {
+        let chow_set = [set[idx[0]], set[idx[1]], set[idx[2]]];
+        if is_chow(&chow_set) {
+            let new_meld = Meld::new(chow_set[0], MeldKind::ConcealedChow);
+            let new_sb = sb.clone().add_meld(new_meld).unwrap();
+            let remains: ArrayVec<Tile, TWOSET> = set
+                .to_owned()
+                .into_iter()
+                .enumerate()
+                .filter(|(i, _)| !idx.contains(i))
+                .map(|(_, t)| t)
+                .collect();
+            assert_eq!(remains.len(), TWOSET);
+
+            result.push((new_sb, remains));
+            break;
+        }
+    }
+
+    result
+}
+
+fn get_second_melds(set: &[Tile], sb: SetBuilder) -> Vec<(SetBuilder, ArrayVec<Tile, ONESET>)> {
+    assert_eq!(set.len(), TWOSET);
+    let mut result = Vec::new();
+    // TODO: rewrite without combinations and unique
+    // assume set is sorted
+    // pung is always continuous (0, 1, 2)
+    // chow is possible in various way, get only (0, x, y) where x + y min
+    // FIXME: prove only "first" chow is needed
+    let pung_set = [set[0], set[1], set[2]];
+    let chow_indexes = [
+        [0, 1, 2],
+        [0, 1, 3],
+        [0, 1, 4],
+        [0, 1, 5],
+        [0, 2, 4],
+        [0, 2, 5],
+    ];
+
+    if is_pung(&pung_set) {
+        let new_meld = Meld::new(pung_set[0], MeldKind::ConcealedPung);
+        let new_sb = sb.clone().add_meld(new_meld).unwrap();
+        let meld_pos = [0, 1, 2];
+        let remains: ArrayVec<Tile, ONESET> = set
+            .to_owned()
+            .into_iter()
+            .enumerate()
+            .filter(|(i, _)| !meld_pos.contains(i))
+            .map(|(_, t)| t)
+            .collect();
+        assert_eq!(remains.len(), ONESET);
+
+        result.push((new_sb, remains));
+    }
+
+    for idx in chow_indexes.into_iter() {
+        let chow_set = [set[idx[0]], set[idx[1]], set[idx[2]]];
+        if is_chow(&chow_set) {
+            let new_meld = Meld::new(chow_set[0], MeldKind::ConcealedChow);
+            let new_sb = sb.clone().add_meld(new_meld).unwrap();
+            let remains: ArrayVec<Tile, ONESET> = set
+                .to_owned()
+                .into_iter()
+                .enumerate()
+                .filter(|(i, _)| !idx.contains(i))
+                .map(|(_, t)| t)
+                .collect();
+            assert_eq!(remains.len(), ONESET);
+
+            result.push((new_sb, remains));
+            break;
+        }
+    }
+
+    result
+}
+
+fn get_last_melds(set: &[Tile], sb: SetBuilder) -> Option<HandChecker> {
+    assert_eq!(set.len(), ONESET);
+    let chow = is_chow(set);
+    let pung = is_pung(set);
+    match chow || pung {
+        true => {
+            let new_meld = match (chow, pung) {
+                (true, false) => Meld::new(set[0], MeldKind::ConcealedChow),
+                (false, true) => Meld::new(set[0], MeldKind::ConcealedPung),
+                _ => unreachable!(),
+            };
+            Some(
+                sb.add_meld(new_meld)
+                    .ok()?
+                    .build()
+                    .ok()?
+                    .to_handchecker(Tile::Red),
+            )
+        }
+        false => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn comb_all_pungs() {
+        let raw = [
+            b'A', b'A', b'B', b'B', b'B', b'C', b'C', b'C', b'D', b'D', b'D',
+        ];
+
+        assert_eq!(comb(&raw), 6 * 4 * 4 * 4);
+    }
+
+    #[test]
+    fn comb_with_visible_tiles() {
+        let raw = *b"AABBBCCCDDD";
+        assert_eq!(comb_available(&raw, &[4; TILEVARIANT]), 6 * 4 * 4 * 4);
+        // one 月 and two 日 already seen: C(3, 2) for the pair, C(2, 3) is impossible
+        let available = crate::tile::available(b"ABB").unwrap();
+        assert_eq!(available[Tile::try_from('A').unwrap() as usize], 3);
+        assert_eq!(comb_available(&raw, &available), 0);
+        let available = crate::tile::available(b"AA").unwrap();
+        assert_eq!(comb_available(&raw, &available), 4 * 4 * 4);
+        assert!(crate::tile::available(b"AAAAA").is_err());
+    }
+
+    #[test]
+    fn bonus_tiles_set_aside() {
+        // 陰陰 陽陽陽 中中中 發發發 with 梅 and 冬
+        let raw = *b"AABBBCCCDDDX_";
+        assert_eq!(comb(&raw), 6 * 4 * 4 * 4);
+        assert_eq!(allsets(&raw).len(), 1);
+
+        let classes = bonus_comb(Tile::Red);
+        assert_eq!(classes.iter().map(|(_, w)| w).sum::<u64>(), 4u64.pow(8));
+    }
+}
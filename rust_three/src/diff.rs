@@ -1,26 +1,15 @@
-use std::{collections::HashSet, fs::File, io::Read};
+use std::collections::HashSet;
 
 use arrayvec::ArrayVec;
-use itertools::Itertools;
 
-mod tile;
+use pattern::{HAINUM, VARIANT};
+use rust_common::pattern::read_patterns;
 
-const HAINUM: usize = 14;
+mod pattern;
+mod tile;
 
 fn main() -> std::io::Result<()> {
-    let mut hai_sets_general: Vec<ArrayVec<u8, HAINUM>> = {
-        let mut reader_general = File::open("patterns_general_three.dat")?;
-        let fsize_general = reader_general.metadata()?.len() as usize;
-        let mut buffer_general = Vec::with_capacity(fsize_general);
-        let size_read_general = reader_general.read_to_end(&mut buffer_general)?;
-        assert_eq!(size_read_general, fsize_general);
-        buffer_general
-            .into_iter()
-            .chunks(HAINUM)
-            .into_iter()
-            .map(|c| c.collect())
-            .collect()
-    };
+    let mut hai_sets_general: Vec<ArrayVec<u8, HAINUM>> = read_patterns("patterns_general_three.dat", VARIANT)?.1;
 
     let hai_gen_original = hai_sets_general.clone();
     hai_sets_general.sort();
@@ -33,19 +22,7 @@ fn main() -> std::io::Result<()> {
     );
     let hai_sets_general: HashSet<_> = hai_sets_general.into_iter().collect();
 
-    let mut hai_sets_rust: Vec<ArrayVec<u8, HAINUM>> = {
-        let mut reader_rust = File::open("patterns_rust_three.dat")?;
-        let fsize_rust = reader_rust.metadata()?.len() as usize;
-        let mut buffer_rust = Vec::with_capacity(fsize_rust);
-        let size_read_rust = reader_rust.read_to_end(&mut buffer_rust)?;
-        assert_eq!(size_read_rust, fsize_rust);
-        buffer_rust
-            .into_iter()
-            .chunks(HAINUM)
-            .into_iter()
-            .map(|c| c.collect())
-            .collect()
-    };
+    let mut hai_sets_rust: Vec<ArrayVec<u8, HAINUM>> = read_patterns("patterns_rust_three.dat", VARIANT)?.1;
 
     let hai_rust_original = hai_sets_rust.clone();
     hai_sets_rust.sort();
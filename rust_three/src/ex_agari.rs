@@ -1,34 +1,36 @@
 use arrayvec::ArrayVec;
 use ex_set::Pair;
-use itertools::{iproduct, Itertools};
-use std::{collections::HashMap, fs::File, io::Read, time::Instant};
+use itertools::iproduct;
+use std::{collections::HashMap, error::Error, time::Instant};
+
+use rust_common::pattern::read_patterns;
 
 use crate::{
     ex_hand::{Hand, HANDVARIANT},
     ex_handchecker::{HandChecker, HandList},
     ex_set::{Meld, MeldKind, SetBuilder, HAINUM},
-    tile::{Tile, TILEVARIANT},
+    tile::{available, Tile, TILEVARIANT},
 };
 
 mod ex_hand;
 mod ex_handchecker;
 mod ex_set;
+mod pattern;
 mod tile;
 
-fn main() -> std::io::Result<()> {
-    let mut reader = File::open("patterns_general_three.dat")?;
-    let fsize = reader.metadata()?.len() as usize;
-    let mut buffer = Vec::with_capacity(fsize);
+// usage: ex-agari [--visible tiles]
+// visible tiles in pattern file letters are out of the wall
+fn main() -> Result<(), Box<dyn Error>> {
+    let args: Vec<String> = std::env::args().collect();
+    let visible = match args.iter().position(|a| a == "--visible") {
+        Some(i) => args.get(i + 1).ok_or("--visible needs tiles")?.as_str(),
+        None => "",
+    };
+    let available = available(visible.as_bytes())?;
     let time_read = Instant::now();
-    let size_read = reader.read_to_end(&mut buffer)?;
-    assert_eq!(size_read, fsize);
-    let raw_hai_sets: Vec<ArrayVec<u8, HAINUM>> = buffer
-        .into_iter()
-        .chunks(HAINUM)
-        .into_iter()
-        .map(|c| c.collect())
-        .collect();
-    assert_eq!(raw_hai_sets.len(), fsize / HAINUM);
+    let (header, raw_hai_sets) =
+        read_patterns::<{ pattern::HAINUM }>("patterns_general_three.dat", pattern::VARIANT)?;
+    assert_eq!(raw_hai_sets.len() as u64, header.records);
     println!(
         "time read and chunks in {:.2} s",
         time_read.elapsed().as_secs_f32()
@@ -41,9 +43,12 @@ fn main() -> std::io::Result<()> {
         .into_iter()
         .enumerate()
         .for_each(|(_, raw_hai)| {
+            let combinations = comb(&raw_hai, &available);
+            if combinations == 0 {
+                return;
+            }
             let mut sets = allsets(&raw_hai);
             sets.dedup();
-            let combinations = comb(&raw_hai);
 
             sets.into_iter().for_each(|s| {
                 sets_reveal_conceal(s).into_iter().for_each(|s| {
@@ -75,10 +80,14 @@ fn main() -> std::io::Result<()> {
         .enumerate()
         .for_each(|(i, (h, _, _, _))| *h = Hand::try_from(i).unwrap());
 
+    // 門前、副露: 出現數、總分
+    let mut openness: [(u64, u64); 2] = [(0, 0); 2];
     hands
         .into_iter()
         .for_each(|(handlist, (pattern, occurance))| {
             let score = handlist.score() as u64;
+            openness[handlist.is_open() as usize].0 += occurance;
+            openness[handlist.is_open() as usize].1 += occurance * score;
             handlist
                 .into_iter()
                 .enumerate()
@@ -114,6 +123,18 @@ fn main() -> std::io::Result<()> {
             .sum::<u64>()
     );
 
+    ["門前", "副露"]
+        .iter()
+        .zip(openness.iter())
+        .for_each(|(name, (occurance, score))| {
+            println!(
+                "{}: {} 平均分數 {:.5}",
+                name,
+                occurance,
+                *score as f64 / *occurance as f64
+            )
+        });
+
     println!("{:4}{:10}{:15}{:4}", "役種", "和牌形", "出現數", "平均分數");
     result
         .into_iter()
@@ -169,7 +190,8 @@ fn allsets(raw: &[u8]) -> Vec<HandChecker> {
     result
 }
 
-fn comb(raw: &[u8]) -> u64 {
+// 超幾何: C(available, held) for every tile
+fn comb(raw: &[u8], available: &[u64; TILEVARIANT]) -> u64 {
     assert_eq!(raw.len(), HAINUM);
     let mut counts = [0u64; TILEVARIANT];
 
@@ -179,11 +201,10 @@ fn comb(raw: &[u8]) -> u64 {
 
     counts
         .into_iter()
-        .map(|c| match c {
-            0 | 4 => 1,
-            1 | 3 => 4,
-            2 => 6,
-            _ => unreachable!(),
+        .zip(available.iter())
+        .map(|(c, a)| match c <= *a {
+            true => (0..c).fold(1, |acc, i| acc * (a - i) / (i + 1)),
+            false => 0,
         })
         .product()
 }
@@ -72,6 +72,23 @@ impl Hand {
         }
     }
 
+    // 食い下がり: 副露時的分數, None 為門前限定
+    pub fn open_score(&self) -> Option<u16> {
+        match self {
+            Self::PureDoubleChow => None,
+            Self::PureTripleChow => Some(11),
+            Self::MixedTripleChow => Some(2),
+            Self::TerminalsInAllSets => Some(1),
+            Self::HalfFlush => Some(2),
+            Self::FullFlush => Some(5),
+            _ => Some(self.score()),
+        }
+    }
+
+    pub fn is_closed_only(&self) -> bool {
+        self.open_score().is_none()
+    }
+
     pub fn name(&self) -> String {
         match self {
             &Self::AllChows => "平和".to_string(),
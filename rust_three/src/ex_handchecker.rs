@@ -6,20 +6,34 @@ use crate::ex_hand::{Hand, HANDMAXSCORE, HANDVARIANT};
 use crate::ex_set::{Meld, Pair, Set, SETNUM};
 use crate::tile::{Tile, TileColor};
 
+// 役種, 及是否副露
 #[derive(Clone, Hash, PartialEq, Eq, Debug)]
-pub struct HandList(BitArr!(for HANDVARIANT, in u32));
+pub struct HandList(BitArr!(for HANDVARIANT, in u32), bool);
 
 impl HandList {
     pub fn new() -> HandList {
-        HandList(BitArray::ZERO)
+        HandList(BitArray::ZERO, false)
     }
 
+    pub fn is_open(&self) -> bool {
+        self.1
+    }
+
+    pub fn set_open(&mut self, open: bool) {
+        self.1 = open;
+    }
+
+    // 副露時取 open_score
     pub fn score(&self) -> u16 {
+        let score = |h: Hand| match self.1 {
+            true => h.open_score().unwrap_or(0),
+            false => h.score(),
+        };
         let s: u16 = self
             .iter()
             .enumerate()
             .take(HANDVARIANT)
-            .map(|(i, b)| Hand::try_from(i).unwrap().score() * *b as u16)
+            .map(|(i, b)| score(Hand::try_from(i).unwrap()) * *b as u16)
             .sum();
 
         if s > HANDMAXSCORE {
@@ -78,6 +92,11 @@ impl HandChecker {
         self.melds.sort();
     }
 
+    // 有明順、明刻或明槓
+    pub fn is_open(&self) -> bool {
+        self.melds.iter().any(|m| m.kind().is_revealed())
+    }
+
     pub fn hands(&self) -> HandList {
         let mut checker = HandList::new();
         // all_chow_pung must be first
@@ -111,6 +130,13 @@ impl HandChecker {
                     .for_each(|mut c| c.set(false));
             }
         }
+        // 門前限定役 are dropped once a meld is revealed
+        if self.is_open() {
+            checker.set_open(true);
+            (0..HANDVARIANT)
+                .filter(|i| Hand::try_from(*i).unwrap().is_closed_only())
+                .for_each(|i| checker.set(i, false));
+        }
         if checker.any() == false {
             checker.set(Hand::NoPoint as usize, true);
         }
@@ -498,6 +524,8 @@ impl HandChecker {
 mod tests {
     use super::*;
 
+    use crate::ex_set::{MeldKind, SetBuilder};
+
     #[test]
     fn handlist_score() {
         let hl = HandList::new();
@@ -513,4 +541,36 @@ mod tests {
 
         // assert_eq!(hl.score(), 16);
     }
+
+    fn checker(kind: MeldKind) -> HandChecker {
+        // 11s 123s 123s 456s, 一般高, 清一色 and 清全帶
+        SetBuilder::new()
+            .add_pair(Pair::new(Tile::B1, true))
+            .add_meld(Meld::new(Tile::B1, MeldKind::ConcealedChow))
+            .unwrap()
+            .add_meld(Meld::new(Tile::B1, MeldKind::ConcealedChow))
+            .unwrap()
+            .add_meld(Meld::new(Tile::B4, kind))
+            .unwrap()
+            .build()
+            .unwrap()
+            .to_handchecker(Tile::Red)
+    }
+
+    #[test]
+    fn open_hands_reduce_scores() {
+        let closed = checker(MeldKind::ConcealedChow).hands();
+        assert!(!closed.is_open());
+        assert!(closed[Hand::PureDoubleChow as usize]);
+        assert!(closed[Hand::FullFlush as usize]);
+
+        let open = checker(MeldKind::RevealedChow).hands();
+        assert!(open.is_open());
+        assert!(!open[Hand::PureDoubleChow as usize]);
+        assert!(open[Hand::FullFlush as usize]);
+        // 不求人 and 一般高 are gone, 清一色 and 清全帶 drop a step
+        assert_eq!(closed.score(), 2 + 2 + 6 + 2);
+        assert_eq!(open.score(), 5 + 1);
+        assert_ne!(closed, open);
+    }
 }
@@ -1,12 +1,12 @@
-use std::fs::File;
-use std::io::Write;
-
 use arrayvec::ArrayVec;
 use itertools::Itertools;
 
-use ex_set::{Meld, MeldKind, Set, SetBuilder, HAINUM, SETNUM};
+use ex_set::{Meld, MeldKind, Pair, Set, SetBuilder, HAINUM, SETNUM};
+use pattern::VARIANT;
+use rust_common::pattern::write_patterns;
 use tile::{Tile, TILEVARIANT};
 mod ex_set;
+mod pattern;
 mod tile;
 
 const SETKINDVARIANT: usize = 2_usize.pow(SETNUM as u32);
@@ -53,7 +53,7 @@ fn main() {
                 .map(|(m1, m2, m3)| ArrayVec::from([m1, m2, m3]))
                 .collect();
 
-            let sb = SetBuilder::new().add_pair(p);
+            let sb = SetBuilder::new().add_pair(Pair::new(p, true));
             kinds
                 .clone()
                 .into_iter()
@@ -85,8 +85,12 @@ fn main() {
 
     let tmp: Vec<u8> = general_sets.into_iter().flatten().collect();
     let filename = "patterns_rust_three_extended.dat";
-    let mut file = File::create(filename).expect("not able to open file");
-    file.write_all(&tmp)
+    let generator = format!(
+        "{} ex-list {}",
+        env!("CARGO_PKG_NAME"),
+        env!("CARGO_PKG_VERSION")
+    );
+    write_patterns(filename, &generator, VARIANT, pattern::HAINUM, &tmp)
         .expect("cannot write all into the file");
 }
 
@@ -230,8 +234,6 @@ impl Set {
 
 #[cfg(test)]
 mod tests {
-    use super::*;
-
     #[test]
     fn is_ok() {
         assert_eq!(2 + 2, 4);
@@ -76,6 +76,7 @@ pub struct Pair {
     pub(crate) concealed: bool,
 }
 
+#[allow(dead_code)]
 impl Pair {
     pub fn new(head: Tile, concealed: bool) -> Self {
         Self { head, concealed }
@@ -1,7 +1,9 @@
+use serde::{Deserialize, Serialize};
+
 pub const HANDVARIANT: usize = Hand::NoPoint as usize + 1;
 pub const HANDMAXSCORE: u16 = 16;
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Serialize, Deserialize)]
 pub enum Hand {
     AllChows,              // 平和
     MoonPung,              // 役牌陰
@@ -27,6 +29,11 @@ pub enum Hand {
     PureShiftedPungs,      // 二連刻
     ThreePureShiftedPungs, // 三連刻
     AllTerminals,          // 清老頭
+    SeatFlower,            // 正花
+    SeatSeason,            // 正季
+    FourFlowers,           // 四花, 梅蘭菊竹
+    FourSeasons,           // 四季, 春夏秋冬
+    EightFlowers,          // 八仙過海
     NoPoint,               // 無役
 }
 
@@ -57,11 +64,62 @@ impl Hand {
             &Self::PureShiftedPungs => 4,
             &Self::ThreePureShiftedPungs => 4,
             &Self::AllTerminals => 9,
+            // 花牌
+            &Self::SeatFlower => 1,
+            &Self::SeatSeason => 1,
+            &Self::FourFlowers => 2,
+            &Self::FourSeasons => 2,
+            &Self::EightFlowers => 8,
             // 無役
             &Self::NoPoint => 0,
         }
     }
 
+    // 花牌, awarded apart from the decomposition
+    #[allow(dead_code)]
+    pub fn is_bonus(&self) -> bool {
+        matches!(
+            *self,
+            Self::SeatFlower
+                | Self::SeatSeason
+                | Self::FourFlowers
+                | Self::FourSeasons
+                | Self::EightFlowers
+        )
+    }
+
+    // hands the checker drops when this one is set, the larger pattern
+    // taking the place of the smaller
+    #[allow(dead_code)]
+    pub fn supersedes(&self) -> &'static [Hand] {
+        match *self {
+            Self::BigThreeWinds => &[Self::LittleThreeWinds],
+            Self::AllTerminals => &[Self::AllTerminalsAndHonors, Self::TerminalsInAllSets],
+            Self::AllTerminalsAndHonors => &[Self::OutsideHands],
+            Self::TerminalsInAllSets => &[Self::OutsideHands],
+            Self::FullFlush => &[Self::HalfFlush],
+            Self::AllHonors => &[
+                Self::AllPungs,
+                Self::LittleThreeWinds,
+                Self::BigThreeWinds,
+                Self::TwoDragons,
+                Self::AllTerminalsAndHonors,
+                Self::HalfFlush,
+            ],
+            Self::ThreeConcealedPungs => &[Self::TwoConcealedPungs],
+            Self::PureTripleChow => &[Self::PureDoubleChow],
+            Self::ThreePureShiftedPungs => &[Self::PureShiftedPungs],
+            Self::EightFlowers => &[
+                Self::SeatFlower,
+                Self::SeatSeason,
+                Self::FourFlowers,
+                Self::FourSeasons,
+            ],
+            _ => &[],
+        }
+    }
+
+    #[allow(dead_code)]
     pub fn name(&self) -> String {
         match self {
             &Self::AllChows => "平和".to_string(),
@@ -86,6 +144,11 @@ impl Hand {
             &Self::PureShiftedPungs => "二連刻".to_string(),
             &Self::ThreePureShiftedPungs => "三連刻".to_string(),
             &Hand::AllTerminals => "清老頭".to_string(),
+            &Self::SeatFlower => "正花".to_string(),
+            &Self::SeatSeason => "正季".to_string(),
+            &Self::FourFlowers => "四花".to_string(),
+            &Self::FourSeasons => "四季".to_string(),
+            &Self::EightFlowers => "八仙".to_string(),
             &Self::NoPoint => "無役".to_string(),
         }
     }
@@ -120,7 +183,12 @@ impl TryFrom<usize> for Hand {
             21 => Ok(Self::PureShiftedPungs),
             22 => Ok(Self::ThreePureShiftedPungs),
             23 => Ok(Self::AllTerminals),
-            24 => Ok(Self::NoPoint),
+            24 => Ok(Self::SeatFlower),
+            25 => Ok(Self::SeatSeason),
+            26 => Ok(Self::FourFlowers),
+            27 => Ok(Self::FourSeasons),
+            28 => Ok(Self::EightFlowers),
+            29 => Ok(Self::NoPoint),
             _ => Err(()),
         }
     }
@@ -1,11 +1,13 @@
 use arrayvec::ArrayVec;
 use bitvec::array::BitArray;
 use bitvec::BitArr;
+use itertools::Itertools;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
 use crate::hand::Hand;
 use crate::hand::HANDMAXSCORE;
 use crate::hand::HANDVARIANT;
-use crate::set::{Meld, Set, SETNUM};
+use crate::set::{Meld, Set, SetData, SETNUM};
 use crate::tile::{Tile, TileColor};
 
 #[derive(Clone, Hash, PartialEq, Eq, Debug)]
@@ -16,6 +18,7 @@ impl HandList {
         HandList(BitArray::ZERO)
     }
 
+    #[allow(dead_code)]
     pub fn score(&self) -> u16 {
         let s: u16 = self
             .iter()
@@ -30,6 +33,39 @@ impl HandList {
             s
         }
     }
+
+    #[allow(dead_code)]
+    pub fn bits(&self) -> u32 {
+        self.0.as_raw_slice()[0]
+    }
+
+    #[allow(dead_code)]
+    pub fn from_bits(bits: u32) -> HandList {
+        HandList(BitArray::new([bits]))
+    }
+}
+
+// stable text form: list of hand names
+impl Serialize for HandList {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(
+            self.iter()
+                .enumerate()
+                .take(HANDVARIANT)
+                .filter(|(_, b)| **b)
+                .map(|(i, _)| Hand::try_from(i).unwrap()),
+        )
+    }
+}
+
+impl<'de> Deserialize<'de> for HandList {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let mut list = HandList::new();
+        Vec::<Hand>::deserialize(deserializer)?
+            .into_iter()
+            .for_each(|h| list.set(h as usize, true));
+        Ok(list)
+    }
 }
 
 impl std::ops::Deref for HandList {
@@ -56,19 +92,67 @@ impl Set {
     }
 }
 
-#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+#[derive(Clone, PartialEq, Eq, Hash, Debug, Serialize, Deserialize)]
+#[serde(try_from = "HandCheckerData")]
 pub struct HandChecker {
     pair: Tile,
     melds: ArrayVec<Meld, SETNUM>,
     wind: Tile, // 自風
 }
 
+#[derive(Deserialize)]
+struct HandCheckerData {
+    pair: Tile,
+    melds: Vec<Meld>,
+    wind: Tile,
+}
+
+impl TryFrom<HandCheckerData> for HandChecker {
+    type Error = String;
+
+    fn try_from(value: HandCheckerData) -> Result<Self, Self::Error> {
+        let set = Set::try_from(SetData {
+            pair: value.pair,
+            melds: value.melds,
+        })?;
+        Ok(set.to_handchecker(value.wind))
+    }
+}
+
+#[allow(dead_code)]
 impl HandChecker {
+    pub fn pair(&self) -> Tile {
+        self.pair
+    }
+
+    pub fn melds(&self) -> &ArrayVec<Meld, SETNUM> {
+        &self.melds
+    }
+
+    pub fn wind(&self) -> Tile {
+        self.wind
+    }
+
+    pub fn set_wind(&mut self, wind: Tile) {
+        self.wind = wind;
+    }
+
     pub fn sort(&mut self) {
         self.melds.sort();
     }
 
     pub fn hands(&self) -> HandList {
+        limit_hands(self.unlimited_hands())
+    }
+
+    // 花牌 set aside by the winner, scored apart from the decomposition
+    pub fn hands_with_bonus(&self, bonus: &[Tile]) -> HandList {
+        let mut checker = self.unlimited_hands();
+        *checker |= *bonus_hands(bonus, self.wind);
+        limit_hands(checker)
+    }
+
+    fn unlimited_hands(&self) -> HandList {
         let mut checker = HandList::new();
         // all_chow_pung must be first
         self.all_chow_pung(&mut checker);
@@ -80,24 +164,59 @@ impl HandChecker {
         self.same_chows(&mut checker);
         self.shift_pungs(&mut checker);
 
-        match checker[Hand::AllHonors as usize] {
-            false => (),
-            _ => {
-                checker
-                    .iter_mut()
-                    .enumerate()
-                    .filter_map(|(i, c)| match i {
-                        i if i == Hand::AllHonors as usize => None,
-                        _ => Some(c),
-                    })
-                    .for_each(|mut c| c.set(false));
-            }
-        }
-        if checker.any() == false {
-            checker.set(Hand::NoPoint as usize, true);
+        checker
+    }
+}
+
+// 字一色 clears every other hand
+fn limit_hands(mut checker: HandList) -> HandList {
+    match checker[Hand::AllHonors as usize] {
+        false => (),
+        _ => {
+            checker
+                .iter_mut()
+                .enumerate()
+                .filter_map(|(i, c)| match i {
+                    i if i == Hand::AllHonors as usize => None,
+                    _ => Some(c),
+                })
+                .for_each(|mut c| c.set(false));
         }
+    }
+    if checker.any() == false {
+        checker.set(Hand::NoPoint as usize, true);
+    }
 
-        checker
+    checker
+}
+
+// 花牌: 正花, 正季, 四花, 四季, 八仙過海 in place of the others
+pub fn bonus_hands(bonus: &[Tile], wind: Tile) -> HandList {
+    let mut checker = HandList::new();
+    let flowers = bonus.iter().filter(|t| t.is_flower()).unique().count();
+    let seasons = bonus.iter().filter(|t| t.is_season()).unique().count();
+    if flowers == 4 && seasons == 4 {
+        checker.set(Hand::EightFlowers as usize, true);
+        return checker;
+    }
+
+    let seat = |f: fn(&Tile) -> bool| bonus.iter().any(|t| f(t) && t.is_seat_bonus(wind));
+    checker.set(Hand::SeatFlower as usize, seat(Tile::is_flower));
+    checker.set(Hand::SeatSeason as usize, seat(Tile::is_season));
+    checker.set(Hand::FourFlowers as usize, flowers == 4);
+    checker.set(Hand::FourSeasons as usize, seasons == 4);
+
+    checker
+}
+
+impl HandList {
+    // add 花牌 hands to a finished list, same as `hands_with_bonus`
+    #[allow(dead_code)]
+    pub fn with_bonus(&self, bonus: &HandList) -> HandList {
+        let mut checker = self.clone();
+        checker.set(Hand::NoPoint as usize, false);
+        *checker |= **bonus;
+        limit_hands(checker)
     }
 }
 
@@ -455,4 +574,16 @@ mod tests {
 
         // assert_eq!(hl.score(), 16);
     }
+
+    #[test]
+    fn bonus_hands_for_seat() {
+        // 梅春 belong to 中, the first seat
+        let hl = bonus_hands(&[Tile::F1, Tile::S1], Tile::Red);
+        assert!(hl[Hand::SeatFlower as usize] && hl[Hand::SeatSeason as usize]);
+        assert!(bonus_hands(&[Tile::F4, Tile::S4], Tile::White).not_any());
+
+        let mut honors = HandList::new();
+        honors.set(Hand::AllHonors as usize, true);
+        assert_eq!(honors.with_bonus(&hl), honors);
+    }
 }
@@ -0,0 +1,287 @@
+use std::{collections::HashMap, error::Error, fmt, fs::File, io::BufReader};
+
+use crate::{
+    hand::{Hand, HANDMAXSCORE, HANDVARIANT},
+    handchecker::HandList,
+};
+
+// 規則檢查
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Finding {
+    // a hand worth no more than one it takes the place of
+    Supersedes {
+        hand: Hand,
+        value: u16,
+        replaced: Hand,
+        replaced_value: u16,
+    },
+    ZeroValue(Hand),
+    // never set on any pattern
+    Unreachable(Hand),
+    // share of the hand's combinations whose sum is over the cap
+    CapDominates {
+        hand: Hand,
+        share: f64,
+    },
+}
+
+impl fmt::Display for Finding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Supersedes {
+                hand,
+                value,
+                replaced,
+                replaced_value,
+            } => write!(
+                f,
+                "{:?}({}) {} replaces {:?}({}) {} but is not worth more",
+                hand,
+                hand.name(),
+                value,
+                replaced,
+                replaced.name(),
+                replaced_value
+            ),
+            Self::ZeroValue(hand) => write!(f, "{:?}({}) is worth 0", hand, hand.name()),
+            Self::Unreachable(hand) => {
+                write!(f, "{:?}({}) is never set on any pattern", hand, hand.name())
+            }
+            Self::CapDominates { hand, share } => write!(
+                f,
+                "{:?}({}) is over the cap of {} in {:.1}% of its combinations",
+                hand,
+                hand.name(),
+                HANDMAXSCORE,
+                share * 100.0
+            ),
+        }
+    }
+}
+
+// the table of `Hand::score`, some values replaced from JSON like {"AllChows": 1}
+pub fn rule_values(filename: Option<&str>) -> Result<[u16; HANDVARIANT], Box<dyn Error>> {
+    let mut values = [0; HANDVARIANT];
+    values
+        .iter_mut()
+        .enumerate()
+        .for_each(|(i, v)| *v = Hand::try_from(i).unwrap().score());
+    if let Some(filename) = filename {
+        let reader = BufReader::new(File::open(filename)?);
+        let replaced: HashMap<Hand, u16> = serde_json::from_reader(reader)?;
+        replaced
+            .into_iter()
+            .for_each(|(h, v)| values[h as usize] = v);
+    }
+    Ok(values)
+}
+
+// checks on the values alone: precedence and zero
+// a hand at the cap can not be worth more, limit hands pass
+pub fn value_findings(values: &[u16; HANDVARIANT]) -> Vec<Finding> {
+    let mut findings = Vec::new();
+    (0..HANDVARIANT)
+        .map(|i| Hand::try_from(i).unwrap())
+        .for_each(|hand| {
+            hand.supersedes()
+                .iter()
+                .filter(|r| {
+                    values[hand as usize] < HANDMAXSCORE
+                        && values[hand as usize] <= values[**r as usize]
+                })
+                .for_each(|r| {
+                    findings.push(Finding::Supersedes {
+                        hand,
+                        value: values[hand as usize],
+                        replaced: *r,
+                        replaced_value: values[*r as usize],
+                    })
+                });
+        });
+    (0..HANDVARIANT)
+        .map(|i| Hand::try_from(i).unwrap())
+        .filter(|h| *h != Hand::NoPoint && values[*h as usize] == 0)
+        .for_each(|h| findings.push(Finding::ZeroValue(h)));
+    findings
+}
+
+// checks against the hands checked on every pattern, with their combinations
+// hands worth the cap alone are limit hands and never said to be dominated
+pub fn pattern_findings(
+    values: &[u16; HANDVARIANT],
+    lists: &HashMap<HandList, u128>,
+    cap_share: f64,
+) -> Vec<Finding> {
+    let mut total = [0u128; HANDVARIANT];
+    let mut capped = [0u128; HANDVARIANT];
+    lists.iter().filter(|(_, w)| **w > 0).for_each(|(l, w)| {
+        let hands: Vec<usize> = (0..HANDVARIANT).filter(|i| l[*i]).collect();
+        // values come from a rules file, the sum must not overflow
+        let sum: u32 = hands.iter().map(|i| values[*i] as u32).sum();
+        hands.iter().for_each(|i| {
+            total[*i] += w;
+            if sum > HANDMAXSCORE as u32 {
+                capped[*i] += w;
+            }
+        });
+    });
+
+    let mut findings = Vec::new();
+    (0..HANDVARIANT)
+        .map(|i| Hand::try_from(i).unwrap())
+        .for_each(|hand| {
+            let i = hand as usize;
+            if total[i] == 0 {
+                findings.push(Finding::Unreachable(hand));
+                return;
+            }
+            let share = capped[i] as f64 / total[i] as f64;
+            if values[i] < HANDMAXSCORE && share > cap_share {
+                findings.push(Finding::CapDominates { hand, share });
+            }
+        });
+    findings
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use itertools::Itertools;
+
+    use super::*;
+    use crate::{
+        decompose::{allsets, bonus_comb},
+        set::SETNUM,
+        tile::{Tile, TILEVARIANT},
+    };
+
+    fn list(hands: &[Hand]) -> HandList {
+        let mut list = HandList::new();
+        hands.iter().for_each(|h| list.set(*h as usize, true));
+        list
+    }
+
+    // every pattern the lister writes, a pair and the melds with at most four of a tile
+    fn patterns() -> Vec<Vec<u8>> {
+        let tiles: Vec<Tile> = (0..TILEVARIANT)
+            .map(|i| Tile::try_from((i as u8 + b'A') as char).unwrap())
+            .collect();
+        let melds: Vec<[Tile; 3]> = tiles
+            .iter()
+            .map(|t| [*t; 3])
+            .chain(tiles.windows(3).filter_map(|w| {
+                match w[0].is_ascending(w[1]) && w[1].is_ascending(w[2]) {
+                    true => Some([w[0], w[1], w[2]]),
+                    false => None,
+                }
+            }))
+            .collect();
+        let mut result: Vec<Vec<u8>> = tiles
+            .iter()
+            .flat_map(|p| {
+                melds
+                    .iter()
+                    .combinations_with_replacement(SETNUM)
+                    .map(move |ms| {
+                        let mut raw: Vec<u8> = [*p, *p]
+                            .iter()
+                            .chain(ms.into_iter().flatten())
+                            .map(|t| *t as u8 + b'A')
+                            .collect();
+                        raw.sort();
+                        raw
+                    })
+            })
+            .filter(|raw| {
+                raw.iter()
+                    .all(|r| raw.iter().filter(|x| *x == r).count() <= 4)
+            })
+            .collect();
+        result.sort();
+        result.dedup();
+        result
+    }
+
+    #[test]
+    fn precedence_matches_checker() {
+        let patterns = patterns();
+        // same records as patterns_general_three.dat
+        assert_eq!(patterns.len(), 144790);
+        let lists: HashSet<HandList> = patterns
+            .iter()
+            .flat_map(|r| allsets(r))
+            .map(|s| s.hands())
+            .collect();
+        let bonus = bonus_comb(Tile::Red);
+        lists
+            .iter()
+            .flat_map(|hands| bonus.iter().map(|(b, _)| hands.with_bonus(b)))
+            .for_each(|hands| {
+                (0..HANDVARIANT)
+                    .filter(|i| hands[*i])
+                    .flat_map(|i| Hand::try_from(i).unwrap().supersedes())
+                    .for_each(|r| assert!(!hands[*r as usize], "{:?} with {:?}", r, hands));
+            });
+    }
+
+    #[test]
+    fn current_values() {
+        let findings = value_findings(&rule_values(None).unwrap());
+        assert!(findings.contains(&Finding::Supersedes {
+            hand: Hand::ThreePureShiftedPungs,
+            value: 4,
+            replaced: Hand::PureShiftedPungs,
+            replaced_value: 4,
+        }));
+        assert!(findings.contains(&Finding::ZeroValue(Hand::OutsideHands)));
+        assert!(!findings.contains(&Finding::ZeroValue(Hand::NoPoint)));
+        assert!(!findings
+            .iter()
+            .any(|f| matches!(f, Finding::Supersedes { hand, .. } if *hand == Hand::FullFlush)));
+        assert!(!findings
+            .iter()
+            .any(|f| matches!(f, Finding::Supersedes { hand, .. } if *hand == Hand::AllHonors)));
+    }
+
+    #[test]
+    fn unreachable_and_cap() {
+        let mut values = rule_values(None).unwrap();
+        values[Hand::AllPungs as usize] = 10;
+        let mut lists = HashMap::new();
+        lists.insert(list(&[Hand::AllPungs, Hand::FullFlush]), 3);
+        lists.insert(list(&[Hand::AllPungs]), 1);
+        lists.insert(list(&[Hand::AllChows]), 4);
+        lists.insert(list(&[Hand::AllHonors]), 0);
+        let findings = pattern_findings(&values, &lists, 0.5);
+
+        assert!(findings.contains(&Finding::CapDominates {
+            hand: Hand::AllPungs,
+            share: 0.75
+        }));
+        assert!(findings.contains(&Finding::CapDominates {
+            hand: Hand::FullFlush,
+            share: 1.0
+        }));
+        assert!(findings.contains(&Finding::Unreachable(Hand::AllHonors)));
+        assert!(!findings.contains(&Finding::Unreachable(Hand::AllChows)));
+        assert!(pattern_findings(&values, &lists, 0.8)
+            .iter()
+            .all(|f| !matches!(f, Finding::CapDominates { hand, .. } if *hand == Hand::AllPungs)));
+    }
+
+    #[test]
+    fn huge_values() {
+        let mut values = rule_values(None).unwrap();
+        values[Hand::AllPungs as usize] = u16::MAX;
+        values[Hand::FullFlush as usize] = u16::MAX;
+        let mut lists = HashMap::new();
+        lists.insert(list(&[Hand::AllPungs, Hand::FullFlush, Hand::AllChows]), 1);
+        let findings = pattern_findings(&values, &lists, 0.5);
+
+        assert!(findings.contains(&Finding::CapDominates {
+            hand: Hand::AllChows,
+            share: 1.0
+        }));
+    }
+}
@@ -0,0 +1,68 @@
+use std::{collections::HashMap, error::Error};
+
+use rust_common::pattern::read_patterns;
+
+use crate::{
+    decompose::{allsets, bonus_comb, comb},
+    handchecker::HandList,
+    lint::{pattern_findings, rule_values, value_findings},
+    pattern::{HAINUM, VARIANT},
+    tile::Tile,
+};
+
+mod decompose;
+mod hand;
+mod handchecker;
+mod lint;
+mod pattern;
+mod set;
+mod tile;
+
+// check a rule set against the hand checker
+// usage: lint [rules.json] [--cap-share 0.5] [--values-only]
+// rules.json replaces some values, like {"OutsideHands": 1}, exit 1 on findings
+fn main() -> Result<(), Box<dyn Error>> {
+    let mut args: Vec<String> = std::env::args().skip(1).collect();
+    let values_only = args.iter().any(|a| a == "--values-only");
+    args.retain(|a| a != "--values-only");
+    let cap_share = match args.iter().position(|a| a == "--cap-share") {
+        Some(i) => {
+            let v: f64 = args
+                .get(i + 1)
+                .ok_or("--cap-share needs a number")?
+                .parse()?;
+            args.drain(i..i + 2);
+            v
+        }
+        None => 0.5,
+    };
+    let values = rule_values(args.first().map(|a| a.as_str()))?;
+
+    let mut findings = value_findings(&values);
+    if !values_only {
+        let (_, raw_hai_sets) = read_patterns::<HAINUM>("patterns_general_three.dat", VARIANT)?;
+        let mut hands: HashMap<HandList, u128> = HashMap::new();
+        raw_hai_sets.into_iter().for_each(|raw_hai| {
+            let combinations = comb(&raw_hai) as u128;
+            let mut sets = allsets(&raw_hai);
+            sets.dedup();
+            sets.into_iter()
+                .for_each(|s| *hands.entry(s.hands()).or_insert(0) += combinations);
+        });
+        // 花牌 draws weigh each list as agari does
+        let bonus = bonus_comb(Tile::Red);
+        let mut lists: HashMap<HandList, u128> = HashMap::new();
+        hands.into_iter().for_each(|(l, w)| {
+            bonus.iter().for_each(|(b, weight)| {
+                *lists.entry(l.with_bonus(b)).or_insert(0) += w * *weight as u128
+            })
+        });
+        findings.append(&mut pattern_findings(&values, &lists, cap_share));
+    }
+
+    findings.iter().for_each(|f| println!("{}", f));
+    match findings.len() {
+        0 => Ok(()),
+        n => Err(format!("{} findings", n))?,
+    }
+}
@@ -1,12 +1,12 @@
-use std::fs::File;
-use std::io::Write;
-
 use arrayvec::ArrayVec;
 use itertools::Itertools;
 
+use pattern::VARIANT;
+use rust_common::pattern::write_patterns;
 use set::{Meld, MeldKind, Set, SetBuilder, HAINUM, SETNUM};
 use tile::{Tile, TILEVARIANT};
 
+mod pattern;
 mod set;
 mod tile;
 
@@ -81,8 +81,12 @@ fn main() {
 
     let tmp: Vec<u8> = possible_sets.into_iter().flatten().collect();
     let filename = "patterns_rust_three.dat";
-    let mut file = File::create(filename).expect("not able to open file");
-    file.write_all(&tmp)
+    let generator = format!(
+        "{} list {}",
+        env!("CARGO_PKG_NAME"),
+        env!("CARGO_PKG_VERSION")
+    );
+    write_patterns(filename, &generator, VARIANT, pattern::HAINUM, &tmp)
         .expect("cannot write all into the file");
 }
 
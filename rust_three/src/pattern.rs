@@ -0,0 +1,5 @@
+use rust_common::pattern::Variant;
+
+// 牌型檔格式見 rust_common::pattern, 這裡只有本變體的檔頭值
+pub const VARIANT: Variant = Variant::Three;
+pub const HAINUM: usize = 11;
@@ -1,4 +1,5 @@
 use arrayvec::ArrayVec;
+use serde::{Deserialize, Serialize};
 
 use crate::tile::Tile;
 
@@ -6,7 +7,7 @@ pub const HAINUM: usize = 11;
 pub const SETNUM: usize = HAINUM / 3;
 
 #[allow(dead_code)]
-#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, PartialOrd, Ord)]
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, PartialOrd, Ord, Serialize, Deserialize)]
 pub enum MeldKind {
     RevealedChow,  // 明順
     ConcealedChow, // 暗順
@@ -49,7 +50,7 @@ impl MeldKind {
 }
 
 // 面子
-#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Serialize, Deserialize)]
 pub struct Meld {
     pub(crate) head: Tile,
     pub(crate) kind: MeldKind,
@@ -119,8 +120,31 @@ impl PartialOrd for Meld {
 }
 
 // TODO: more general Set, contains open hands
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "SetData")]
 pub struct Set {
     pub(crate) pair: Tile,
     pub(crate) melds: ArrayVec<Meld, SETNUM>,
 }
+
+// unchecked Set, only complete sets are accepted
+#[derive(Deserialize)]
+pub(crate) struct SetData {
+    pub(crate) pair: Tile,
+    pub(crate) melds: Vec<Meld>,
+}
+
+impl TryFrom<SetData> for Set {
+    type Error = String;
+
+    fn try_from(value: SetData) -> Result<Self, Self::Error> {
+        value
+            .melds
+            .into_iter()
+            .try_fold(SetBuilder::new().add_pair(value.pair), |sb, m| {
+                sb.add_meld(m)
+            })
+            .and_then(|sb| sb.build())
+            .map_err(|e| e.to_string())
+    }
+}
@@ -0,0 +1,39 @@
+use std::io::Read;
+
+use rust_common::settlement::{Ledger, SettlementRules, Win};
+use serde::Deserialize;
+
+#[derive(Deserialize)]
+struct Session {
+    rules: SettlementRules,
+    wins: Vec<Win>,
+}
+
+// settle a session of wins given as JSON, from a file or stdin
+// usage: settle [session.json]
+// {"rules":{"players":3,"base":1,"per_point":1,"dealer_doubles":true,
+//  "discard":"DiscarderPays","absent":"Split","responsibility":true},
+//  "wins":[{"winner":1,"dealer":0,"score":3,"discarder":2}]}
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let mut input = String::new();
+    match std::env::args().nth(1) {
+        Some(filename) => std::fs::File::open(filename)?.read_to_string(&mut input)?,
+        None => std::io::stdin().read_to_string(&mut input)?,
+    };
+
+    let session: Session = serde_json::from_str(&input)?;
+    // 三人打 leaves one seat empty
+    if !(3..=4).contains(&session.rules.players) {
+        Err(format!("{} players, expect 3 or 4", session.rules.players))?
+    }
+    let mut ledger = Ledger::new(session.rules)?;
+    for (i, win) in session.wins.into_iter().enumerate() {
+        let payments = ledger
+            .record(win)
+            .map_err(|e| format!("win {}: {}", i + 1, e))?;
+        println!("{:>4} {:?}", i + 1, payments);
+    }
+    println!("{}", serde_json::to_string_pretty(&ledger.summary())?);
+
+    Ok(())
+}
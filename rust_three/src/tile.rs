@@ -1,7 +1,12 @@
 use std::convert::TryFrom;
 
+use serde::{Deserialize, Serialize};
+
 #[allow(dead_code)]
 pub const TILEVARIANT: usize = 23;
+// 花牌, set aside and replaced, never part of a pattern
+#[allow(dead_code)]
+pub const BONUSVARIANT: usize = 8;
 
 #[allow(dead_code)]
 #[derive(Clone, Copy, PartialEq, Eq)]
@@ -10,9 +15,10 @@ pub enum TileColor {
     Bamboo,
     Character,
     Dot,
+    Bonus,
 }
 
-#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, PartialOrd, Ord)]
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, PartialOrd, Ord, Serialize, Deserialize)]
 pub enum Tile {
     // 字牌, Honor
     Moon,
@@ -41,6 +47,16 @@ pub enum Tile {
     D4,
     D5,
     D6,
+    // 花, Flower 梅蘭菊竹
+    F1,
+    F2,
+    F3,
+    F4,
+    // 季, Season 春夏秋冬
+    S1,
+    S2,
+    S3,
+    S4,
 }
 
 #[allow(dead_code)]
@@ -60,15 +76,23 @@ impl Tile {
                 TileColor::Character
             }
             &Self::D1 | &Self::D2 | &Self::D3 | &Self::D4 | &Self::D5 | &Self::D6 => TileColor::Dot,
+            &Self::F1
+            | &Self::F2
+            | &Self::F3
+            | &Self::F4
+            | &Self::S1
+            | &Self::S2
+            | &Self::S3
+            | &Self::S4 => TileColor::Bonus,
         }
     }
 
     pub fn number(&self) -> usize {
         match self {
-            &Self::Moon | &Self::B1 | &Self::C1 | &Self::D1 => 1,
-            &Self::Sun | &Self::B2 | &Self::C2 | &Self::D2 => 2,
-            &Self::Red | &Self::B3 | &Self::C3 | &Self::D3 => 3,
-            &Self::Green | &Self::B4 | &Self::C4 | &Self::D4 => 4,
+            &Self::Moon | &Self::B1 | &Self::C1 | &Self::D1 | &Self::F1 | &Self::S1 => 1,
+            &Self::Sun | &Self::B2 | &Self::C2 | &Self::D2 | &Self::F2 | &Self::S2 => 2,
+            &Self::Red | &Self::B3 | &Self::C3 | &Self::D3 | &Self::F3 | &Self::S3 => 3,
+            &Self::Green | &Self::B4 | &Self::C4 | &Self::D4 | &Self::F4 | &Self::S4 => 4,
             &Self::White | &Self::B5 | &Self::C5 | &Self::D5 => 5,
             &Self::B6 | &Self::C6 | &Self::D6 => 6,
         }
@@ -88,6 +112,7 @@ impl Tile {
             | &Self::B6
             | &Self::C6
             | &Self::D6 => false,
+            _ if self.is_bonus() => false,
             _ => (*self as u8 + 1) == (t as u8),
         }
     }
@@ -102,12 +127,13 @@ impl Tile {
             | &Self::B1
             | &Self::C1
             | &Self::D1 => false,
+            _ if self.is_bonus() => false,
             _ => (*self as u8 - 1) == (t as u8),
         }
     }
 
     pub fn is_simple(&self) -> bool {
-        !(self.is_honor() || self.is_terminal())
+        !(self.is_honor() || self.is_terminal() || self.is_bonus())
     }
 
     pub fn is_terminal(&self) -> bool {
@@ -134,6 +160,23 @@ impl Tile {
             _ => false,
         }
     }
+
+    pub fn is_bonus(&self) -> bool {
+        self.color() == TileColor::Bonus
+    }
+
+    pub fn is_flower(&self) -> bool {
+        matches!(self, Self::F1 | Self::F2 | Self::F3 | Self::F4)
+    }
+
+    pub fn is_season(&self) -> bool {
+        matches!(self, Self::S1 | Self::S2 | Self::S3 | Self::S4)
+    }
+
+    // 正花: 梅春 for 中, 蘭夏 for 發, 菊秋 for 白, 竹冬 has no seat
+    pub fn is_seat_bonus(&self, wind: Tile) -> bool {
+        self.is_bonus() && wind.is_wind() && self.number() + 2 == wind.number()
+    }
 }
 
 impl TryFrom<char> for Tile {
@@ -164,7 +207,32 @@ impl TryFrom<char> for Tile {
             'U' => Ok(Tile::D4),
             'V' => Ok(Tile::D5),
             'W' => Ok(Tile::D6),
+            'X' => Ok(Tile::F1),
+            'Y' => Ok(Tile::F2),
+            'Z' => Ok(Tile::F3),
+            '[' => Ok(Tile::F4),
+            '\\' => Ok(Tile::S1),
+            ']' => Ok(Tile::S2),
+            '^' => Ok(Tile::S3),
+            '_' => Ok(Tile::S4),
             _ => Err(()),
         }
     }
 }
+
+// 剩餘張數 of every tile once the visible ones are taken out
+#[allow(dead_code)]
+pub fn available(visible: &[u8]) -> Result<[u64; TILEVARIANT], Box<dyn std::error::Error>> {
+    let mut available = [4u64; TILEVARIANT];
+    for v in visible {
+        match Tile::try_from(*v as char) {
+            Ok(t) if !t.is_bonus() => {
+                available[t as usize] = available[t as usize]
+                    .checked_sub(1)
+                    .ok_or(format!("more than 4 of {:?} visible", t))?
+            }
+            _ => Err(format!("{} is not a visible tile", *v as char))?,
+        }
+    }
+    Ok(available)
+}